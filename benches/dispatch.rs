@@ -0,0 +1,164 @@
+//! Measures the throughput of [`CompiledTriggers::execute_event`], which reuses its action buffer between events and cascade levels,
+//! against [`CompiledTriggers::execute_event_collect`], which allocates a fresh buffer for each event,
+//! and against `execute_event` of version 0.7.6, which allocated a fresh buffer for each event and each cascade level.
+//! Version 0.7.6 also executed the events of actions produced deeper in a cascade more than once, which the measurement includes.
+//!
+//! Each event starts a cascade through a chain of triggers, such that the events of all but the last action are received by a trigger.
+//!
+//! Run with `cargo bench --bench dispatch`.
+
+use event_trigger_action_system::{
+    event_count, CompiledTriggers, Trigger, TriggerAction, TriggerEvent, TriggerIdentifier,
+    Triggers,
+};
+use event_trigger_action_system_0_7_6 as baseline;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const CHAIN_COUNT: usize = 20_000;
+const CHAIN_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct BenchAction(usize);
+
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct BenchIdentifier(usize);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct BenchEvent(usize);
+
+impl TriggerAction for BenchAction {}
+
+impl TriggerIdentifier for BenchIdentifier {}
+
+impl TriggerEvent for BenchEvent {
+    type Action = BenchAction;
+    type Identifier = BenchIdentifier;
+
+    fn identifier(&self) -> Self::Identifier {
+        BenchIdentifier(self.0)
+    }
+
+    fn value_geq(&self, _other: &Self) -> Option<bool> {
+        None
+    }
+
+    fn value_geq_progress(&self, _other: &Self) -> Option<f64> {
+        None
+    }
+
+    fn action_identifier(action: &Self::Action) -> Option<Self::Identifier> {
+        Some(BenchIdentifier(action.0))
+    }
+}
+
+impl baseline::TriggerAction for BenchAction {}
+
+impl baseline::TriggerIdentifier for BenchIdentifier {}
+
+impl baseline::TriggerEvent for BenchEvent {
+    type Action = BenchAction;
+    type Identifier = BenchIdentifier;
+
+    fn identifier(&self) -> Self::Identifier {
+        BenchIdentifier(self.0)
+    }
+
+    fn value_geq(&self, _other: &Self) -> Option<bool> {
+        None
+    }
+
+    fn value_geq_progress(&self, _other: &Self) -> Option<f64> {
+        None
+    }
+}
+
+impl From<BenchAction> for BenchEvent {
+    fn from(action: BenchAction) -> Self {
+        Self(action.0)
+    }
+}
+
+/// The identifier of the given stage of the given chain.
+/// Stage zero is started by the executed event, and the last stage is the identifier of the last action, which no trigger receives.
+fn stage(chain: usize, stage: usize) -> usize {
+    chain * (CHAIN_DEPTH + 1) + stage
+}
+
+fn compiled_triggers() -> CompiledTriggers<BenchEvent> {
+    Triggers::new(
+        (0..CHAIN_COUNT)
+            .flat_map(|chain| {
+                (0..CHAIN_DEPTH).map(move |depth| {
+                    Trigger::new(
+                        format!("chain {chain} stage {depth}"),
+                        event_count(BenchEvent(stage(chain, depth)), 1),
+                        vec![BenchAction(stage(chain, depth + 1))],
+                    )
+                })
+            })
+            .collect(),
+    )
+    .compile(&|event| event, &|action| action)
+}
+
+fn baseline_compiled_triggers() -> baseline::CompiledTriggers<BenchEvent> {
+    baseline::Triggers::new(
+        (0..CHAIN_COUNT)
+            .flat_map(|chain| {
+                (0..CHAIN_DEPTH).map(move |depth| {
+                    baseline::Trigger::new(
+                        format!("chain {chain} stage {depth}"),
+                        baseline::event_count(BenchEvent(stage(chain, depth)), 1),
+                        vec![BenchAction(stage(chain, depth + 1))],
+                    )
+                })
+            })
+            .collect(),
+    )
+    .compile(&|event| event, &|action| action)
+}
+
+fn measure(name: &str, mut execute: impl FnMut(&BenchEvent) -> usize) -> Duration {
+    let start = Instant::now();
+    let mut action_count = 0;
+    for chain in 0..CHAIN_COUNT {
+        action_count += execute(&BenchEvent(stage(chain, 0)));
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(action_count, CHAIN_COUNT * CHAIN_DEPTH);
+    println!(
+        "{name}: {:.1} ns/event ({CHAIN_COUNT} events, {action_count} actions)",
+        elapsed.as_nanos() as f64 / CHAIN_COUNT as f64
+    );
+    elapsed
+}
+
+fn main() {
+    let mut baseline = baseline_compiled_triggers();
+    let baseline_elapsed = measure("0.7.6 execute_event", |event| {
+        baseline.execute_event(event);
+        baseline.consume_all_actions().count()
+    });
+
+    let mut collected = compiled_triggers();
+    let collected_elapsed = measure("execute_event_collect", |event| {
+        collected.execute_event_collect(event).len()
+    });
+
+    let mut reused = compiled_triggers();
+    let reused_elapsed = measure("execute_event", |event| {
+        reused.execute_event(event);
+        reused.consume_all_actions().count()
+    });
+
+    println!(
+        "buffer reuse speedup: {:.2}x over 0.7.6, {:.2}x over execute_event_collect",
+        baseline_elapsed.as_secs_f64() / reused_elapsed.as_secs_f64(),
+        collected_elapsed.as_secs_f64() / reused_elapsed.as_secs_f64()
+    );
+}
@@ -1,8 +1,14 @@
 use crate::triggers::TriggerEvent;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TriggerCondition<Event> {
     None,
     Never,
@@ -10,22 +16,144 @@ pub enum TriggerCondition<Event> {
         event: Event,
         required: usize,
     },
+    DecayingCount {
+        event: Event,
+        required: usize,
+        penalty_event: Event,
+    },
     Geq {
         event: Event,
     },
+    Neq {
+        event: Event,
+    },
+    EqAny {
+        events: Vec<Event>,
+    },
+    Predicate {
+        event: Event,
+        name: String,
+    },
+    PredicateAnyEvent {
+        name: String,
+    },
+    Accumulate {
+        event: Event,
+        target: OrderedF64,
+        name: String,
+    },
+    DeltaGeq {
+        event: Event,
+        threshold: OrderedF64,
+        name: String,
+    },
+    CountWithin {
+        event: Event,
+        required: usize,
+        window: usize,
+    },
+    StaleFor {
+        trigger_id: String,
+        events: usize,
+    },
+    Between {
+        low: Event,
+        high: Event,
+        #[cfg_attr(feature = "serde", serde(default))]
+        low_bound: BoundKind,
+        #[cfg_attr(feature = "serde", serde(default))]
+        high_bound: BoundKind,
+    },
+    HoldBetween {
+        low: Event,
+        high: Event,
+        tick: Event,
+        required: usize,
+    },
     Sequence {
         conditions: Vec<TriggerCondition<Event>>,
     },
+    StrictSequence {
+        conditions: Vec<TriggerCondition<Event>>,
+    },
+    Pipeline {
+        conditions: Vec<TriggerCondition<Event>>,
+    },
+    /// A condition that is shared with other conditions instead of being cloned into each of them.
+    #[cfg(feature = "shared")]
+    Shared {
+        condition: SharedCondition<Event>,
+    },
     And {
         conditions: Vec<TriggerCondition<Event>>,
     },
     Or {
         conditions: Vec<TriggerCondition<Event>>,
     },
+    /// Like `Or`, but each sub-condition has a priority that decides which one wins if several complete at once.
+    PriorityOr {
+        conditions: Vec<(i32, TriggerCondition<Event>)>,
+    },
+    PrunedOr {
+        conditions: Vec<TriggerCondition<Event>>,
+        max_lag: usize,
+    },
     AnyN {
         conditions: Vec<TriggerCondition<Event>>,
         n: usize,
     },
+    Not {
+        condition: Box<TriggerCondition<Event>>,
+        window: usize,
+    },
+    Retry {
+        condition: Box<TriggerCondition<Event>>,
+        failure_event: Event,
+        max_retries: usize,
+    },
+    Xor {
+        conditions: Vec<TriggerCondition<Event>>,
+    },
+    ExactlyN {
+        conditions: Vec<TriggerCondition<Event>>,
+        n: usize,
+    },
+}
+
+/// The role of a reference event in a condition, see [`TriggerCondition::leaf_events`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ConditionKindTag {
+    /// The counted event of an `event_count`.
+    EventCount,
+    /// The counted event of a `decaying_count`.
+    DecayingCount,
+    /// The penalty event of a `decaying_count`.
+    DecayingCountPenalty,
+    Geq,
+    Neq,
+    /// One of the reference events of an `eq_any`.
+    EqAny,
+    /// The event of a `predicate`.
+    Predicate,
+    /// The summed event of an `accumulate`.
+    Accumulate,
+    /// The event of a `delta_geq`.
+    DeltaGeq,
+    /// The counted event of a `count_within`.
+    CountWithin,
+    /// The lower bound of a `between`.
+    BetweenLow,
+    /// The upper bound of a `between`.
+    BetweenHigh,
+    /// The lower bound of a `hold_between`.
+    HoldBetweenLow,
+    /// The upper bound of a `hold_between`.
+    HoldBetweenHigh,
+    /// The tick event of a `hold_between`.
+    HoldBetweenTick,
+    /// The failure event of a `retry`.
+    RetryFailure,
 }
 
 #[derive(Debug, Clone)]
@@ -33,10 +161,25 @@ pub enum TriggerCondition<Event> {
 pub struct CompiledTriggerCondition<Event: TriggerEvent> {
     pub(crate) kind: CompiledTriggerConditionKind<Event>,
     pub(crate) completed: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) failed: bool,
     pub(crate) required_progress: f64,
     pub(crate) current_progress: f64,
 }
 
+/// A reference-counted condition, see [`crate::shared`].
+/// Comparisons, serialization and debug output treat it like the condition itself.
+#[cfg(feature = "shared")]
+pub struct SharedCondition<Event> {
+    condition: Arc<TriggerCondition<Event>>,
+    /// Clones the condition when it is resolved, such that resolving does not require `Event: Clone`.
+    /// This is only `None` for deserialized conditions, which are not shared.
+    clone: Option<ConditionCloner<Event>>,
+}
+
+#[cfg(feature = "shared")]
+type ConditionCloner<Event> = fn(&TriggerCondition<Event>) -> TriggerCondition<Event>;
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CompiledTriggerConditionKind<Event: TriggerEvent> {
@@ -47,14 +190,125 @@ pub enum CompiledTriggerConditionKind<Event: TriggerEvent> {
         count: usize,
         required: usize,
     },
+    /// Like `EventCount`, but each event with `penalty_identifier` decrements the count, down to zero.
+    DecayingCount {
+        identifier: Event::Identifier,
+        penalty_identifier: Event::Identifier,
+        count: usize,
+        required: usize,
+    },
+    /// The progress is the progress of the latest comparable event.
     Geq {
         event: Event,
         fulfilled: bool,
+        #[cfg_attr(feature = "serde", serde(default))]
+        progress: f64,
+    },
+    /// Completes on the first comparable event whose value differs from the value of `event`.
+    Neq {
+        event: Event,
+        fulfilled: bool,
+    },
+    /// Completes on the first event whose value equals the value of any of `events` with the same identifier.
+    EqAny {
+        events: Vec<Event>,
+        fulfilled: bool,
+    },
+    /// Completes on the first event with `identifier` for which the predicate registered under `name` returns true.
+    /// If `identifier` is `None`, events with any identifier are received.
+    /// Events are ignored while no predicate is registered.
+    Predicate {
+        identifier: Option<Event::Identifier>,
+        name: String,
+        #[cfg_attr(feature = "serde", serde(skip, default = "Default::default"))]
+        predicate: Option<EventPredicate<Event>>,
+        fulfilled: bool,
+    },
+    /// Sums the values that the extractor registered under `name` returns for the events with `identifier`, down to zero.
+    /// Completes when the sum reaches `target`. Events are ignored while no extractor is registered.
+    Accumulate {
+        identifier: Event::Identifier,
+        name: String,
+        #[cfg_attr(feature = "serde", serde(skip, default = "Default::default"))]
+        extractor: Option<EventExtractor<Event>>,
+        sum: f64,
+        target: f64,
+    },
+    /// Completes when the value that the extractor registered under `name` returns for an event with `identifier`
+    /// exceeds the value of the previous such event by at least `threshold`. Events are ignored while no extractor is registered.
+    DeltaGeq {
+        identifier: Event::Identifier,
+        name: String,
+        #[cfg_attr(feature = "serde", serde(skip, default = "Default::default"))]
+        extractor: Option<EventExtractor<Event>>,
+        /// The value of the previous event, or `None` before the first event.
+        previous: Option<f64>,
+        threshold: f64,
+        fulfilled: bool,
+    },
+    /// Counts the events with `identifier` among the last `window` events executed by the trigger system.
+    /// To see all events, this condition subscribes to all events while it is active.
+    /// `events_seen` is the position of the last received event in the stream of events executed by the trigger system,
+    /// and `positions` holds the positions of the counted events, oldest first.
+    CountWithin {
+        identifier: Event::Identifier,
+        required: usize,
+        window: usize,
+        events_seen: usize,
+        positions: VecDeque<usize>,
+    },
+    /// Completes on the first event received after the trigger with the id `trigger_id` has not advanced for `required` events.
+    /// To see all events, this condition subscribes to all events while it is active.
+    /// `target` is the index of the trigger with the id `trigger_id`, which is resolved when the trigger is added to a trigger system.
+    /// The idle events are then counted in the event stream of the trigger system, and if no trigger has the id, the condition never completes.
+    /// Outside of a trigger system, all events received by this condition, which `received` counts, are idle.
+    StaleFor {
+        trigger_id: String,
+        #[cfg_attr(feature = "serde", serde(default))]
+        target: Option<usize>,
+        required: usize,
+        received: usize,
+        fulfilled: bool,
+    },
+    /// Completes on the first event whose value is within the bounds `low` and `high`, which include their own value depending on their kind.
+    /// The progress is the highest progress towards `low` seen so far.
+    Between {
+        low: Event,
+        high: Event,
+        #[cfg_attr(feature = "serde", serde(default))]
+        low_bound: BoundKind,
+        #[cfg_attr(feature = "serde", serde(default))]
+        high_bound: BoundKind,
+        fulfilled: bool,
+        progress: f64,
+    },
+    /// Counts the events with `tick_identifier` received while the latest value event was between `low` and `high`.
+    /// The count is reset to zero when a value event is outside of the range.
+    HoldBetween {
+        low: Event,
+        high: Event,
+        tick_identifier: Event::Identifier,
+        in_range: bool,
+        count: usize,
+        required: usize,
     },
     Sequence {
         current_index: usize,
         conditions: Vec<CompiledTriggerCondition<Event>>,
     },
+    /// Like a sequence, but restarts from the first condition on each event that does not advance the current condition.
+    /// To see events that belong to other conditions, it stays subscribed to the `identifiers` all conditions are initially subscribed to.
+    StrictSequence {
+        current_index: usize,
+        conditions: Vec<CompiledTriggerCondition<Event>>,
+        identifiers: Vec<Event::Identifier>,
+    },
+    /// Like a sequence, but all conditions make progress simultaneously.
+    /// Only the completion of the conditions is counted in order.
+    Pipeline {
+        current_index: usize,
+        conditions: Vec<CompiledTriggerCondition<Event>>,
+    },
     And {
         conditions: Vec<CompiledTriggerCondition<Event>>,
         fulfilled_conditions: Vec<CompiledTriggerCondition<Event>>,
@@ -62,14 +316,166 @@ pub enum CompiledTriggerConditionKind<Event: TriggerEvent> {
     Or {
         conditions: Vec<CompiledTriggerCondition<Event>>,
         fulfilled_conditions: Vec<CompiledTriggerCondition<Event>>,
+        /// The priorities of `conditions` followed by those of `fulfilled_conditions`, or empty if the sub-conditions have no priorities.
+        #[cfg_attr(feature = "serde", serde(default))]
+        priorities: Vec<i32>,
+    },
+    /// Like `Or`, but branches whose remaining progress exceeds the smallest remaining progress by more than `max_lag`
+    /// are moved to `pruned_conditions`, where they do not receive events until the condition is reset.
+    PrunedOr {
+        conditions: Vec<CompiledTriggerCondition<Event>>,
+        fulfilled_conditions: Vec<CompiledTriggerCondition<Event>>,
+        pruned_conditions: Vec<CompiledTriggerCondition<Event>>,
+        max_lag: usize,
     },
     AnyN {
         conditions: Vec<CompiledTriggerCondition<Event>>,
         fulfilled_conditions: Vec<CompiledTriggerCondition<Event>>,
         n: usize,
     },
+    /// Completes after `window` events relevant to `condition` were received without `condition` completing.
+    /// If `condition` completes first, this condition fails and can never complete.
+    Not {
+        condition: Box<CompiledTriggerCondition<Event>>,
+        window: usize,
+        events_seen: usize,
+        failed: bool,
+    },
+    /// Resets `condition` on each event with `failure_identifier`.
+    /// After more than `max_retries` such events, this condition fails and can never complete.
+    Retry {
+        condition: Box<CompiledTriggerCondition<Event>>,
+        failure_identifier: Event::Identifier,
+        max_retries: usize,
+        retries: usize,
+        failed: bool,
+    },
+    /// Completes when exactly `n` of `conditions` are completed.
+    /// If more than `n` conditions are completed after the same event, this condition fails and can never complete.
+    /// `xor` compiles to this with `n = 1`.
+    ExactlyN {
+        conditions: Vec<CompiledTriggerCondition<Event>>,
+        fulfilled_conditions: Vec<CompiledTriggerCondition<Event>>,
+        n: usize,
+        failed: bool,
+    },
+}
+
+/// A problem that makes a condition impossible or pointless to evaluate, see [`TriggerCondition::validate`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ConditionError {
+    /// An operator has no sub-conditions, e.g. an empty `and`. Contains the name of the operator.
+    EmptyOperator(&'static str),
+    /// An `any_n` or `exactly_n` condition requires more sub-conditions than it has.
+    ThresholdTooHigh { n: usize, available: usize },
+}
+
+/// Whether a bound of a range includes its own value, see [`crate::between_bounds`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BoundKind {
+    Inclusive,
+    Exclusive,
+}
+
+/// A float that is totally ordered, such that conditions containing it can be compared and sorted.
+/// The order is the one of [`f64::total_cmp`], so e.g. `-0.0` is less than `0.0`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct OrderedF64(pub f64);
+
+impl OrderedF64 {
+    /// The bits of the float, flipped such that their order as signed integers is the total order of the floats.
+    fn ordered_bits(self) -> i64 {
+        let bits = self.0.to_bits() as i64;
+        bits ^ (((bits >> 63) as u64) >> 1) as i64
+    }
+
+    /// Returns the float, or an error if it is infinite or NaN.
+    fn finite(self) -> Result<f64, CompileError> {
+        if self.0.is_finite() {
+            Ok(self.0)
+        } else {
+            Err(CompileError::NonFiniteValue)
+        }
+    }
+}
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.ordered_bits() == other.ordered_bits()
+    }
+}
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.ordered_bits().cmp(&other.ordered_bits())
+    }
+}
+
+impl From<f64> for OrderedF64 {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for OrderedF64 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// A reason why a condition cannot be compiled, see [`TriggerCondition::try_compile`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CompileError {
+    /// A `sequence` or `strict_sequence` contains a condition that is completed from the start, but is preceded by a condition that is not.
+    /// Contains the index of the completed condition in the sequence.
+    CompletedConditionInSequence { index: usize },
+    /// A `stale_for` condition refers to a trigger id that none of the compiled triggers has.
+    /// Contains the index of the trigger containing the condition.
+    UnknownTriggerId { index: usize },
+    /// An `accumulate` target or a `delta_geq` threshold is infinite or NaN.
+    NonFiniteValue,
+}
+
+/// The position of an event in the event stream of a trigger system, passed to the conditions receiving the event.
+/// This allows conditions to take events into account that they did not receive, e.g. while their trigger was disabled.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EventContext<'a> {
+    /// The number of events executed by the trigger system up to and including this event.
+    pub(crate) index: usize,
+    /// The index of the last event that advanced each trigger, indexed like the triggers, see [`TriggerCondition::StaleFor`].
+    pub(crate) last_advances: &'a [usize],
+    /// The results of the registered predicates and extractors for this event, see [`SharedEvaluations`].
+    pub(crate) predicate_results: &'a [Cell<Option<bool>>],
+    pub(crate) extractor_results: &'a [Cell<Option<f64>>],
+}
+
+/// Buffers for the results of the registered predicates and extractors for the event being executed, indexed by their `evaluation`.
+/// Conditions that use the same registered function are identical in what they compute from an event,
+/// so the function is evaluated once per event and its result is shared by all of them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedEvaluations {
+    pub(crate) predicate_results: Vec<Option<bool>>,
+    pub(crate) extractor_results: Vec<Option<f64>>,
 }
 
+/// A function deciding if an event fulfils a `predicate` condition, see [`crate::CompiledTriggers::register_predicate`].
+/// The `usize` is the index of its result in [`SharedEvaluations`], if it is registered.
+pub struct EventPredicate<Event>(Arc<dyn Fn(&Event) -> bool + Send + Sync>, Option<usize>);
+
+/// A function extracting the value of an event for an `accumulate` condition, see [`crate::CompiledTriggers::register_extractor`].
+/// The `usize` is the index of its result in [`SharedEvaluations`], if it is registered.
+pub struct EventExtractor<Event>(Arc<dyn Fn(&Event) -> f64 + Send + Sync>, Option<usize>);
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TriggerConditionUpdate<Identifier> {
@@ -78,13 +484,37 @@ pub enum TriggerConditionUpdate<Identifier> {
 }
 
 impl<Event> TriggerCondition<Event> {
+    /// Compiles this condition, panicking if it is invalid, see [`Self::try_compile`].
     pub fn compile<EventCompiler: Fn(Event) -> CompiledEvent, CompiledEvent: TriggerEvent>(
         self,
         event_compiler: &EventCompiler,
     ) -> CompiledTriggerCondition<CompiledEvent> {
-        CompiledTriggerCondition::new(match self {
+        self.try_compile(event_compiler)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Compiles this condition, or returns an error if it cannot be compiled.
+    pub fn try_compile<EventCompiler: Fn(Event) -> CompiledEvent, CompiledEvent: TriggerEvent>(
+        self,
+        event_compiler: &EventCompiler,
+    ) -> Result<CompiledTriggerCondition<CompiledEvent>, CompileError> {
+        Ok(CompiledTriggerCondition::new(match self {
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { condition } => {
+                condition.resolve().try_compile(event_compiler)?.kind
+            }
             TriggerCondition::None => CompiledTriggerConditionKind::None,
             TriggerCondition::Never => CompiledTriggerConditionKind::Never,
+            TriggerCondition::DecayingCount {
+                event,
+                required,
+                penalty_event,
+            } => CompiledTriggerConditionKind::DecayingCount {
+                identifier: event_compiler(event).identifier(),
+                penalty_identifier: event_compiler(penalty_event).identifier(),
+                count: 0,
+                required,
+            },
             TriggerCondition::EventCount { event, required } => {
                 CompiledTriggerConditionKind::EventCount {
                     identifier: event_compiler(event).identifier(),
@@ -95,18 +525,167 @@ impl<Event> TriggerCondition<Event> {
             TriggerCondition::Geq { event } => CompiledTriggerConditionKind::Geq {
                 event: event_compiler(event),
                 fulfilled: false,
+                progress: 0.0,
+            },
+            TriggerCondition::Neq { event } => CompiledTriggerConditionKind::Neq {
+                event: event_compiler(event),
+                fulfilled: false,
+            },
+            TriggerCondition::EqAny { events } => CompiledTriggerConditionKind::EqAny {
+                events: events.into_iter().map(event_compiler).collect(),
+                fulfilled: false,
+            },
+            TriggerCondition::Predicate { event, name } => {
+                CompiledTriggerConditionKind::Predicate {
+                    identifier: Some(event_compiler(event).identifier()),
+                    name,
+                    predicate: None,
+                    fulfilled: false,
+                }
+            }
+            TriggerCondition::PredicateAnyEvent { name } => {
+                CompiledTriggerConditionKind::Predicate {
+                    identifier: None,
+                    name,
+                    predicate: None,
+                    fulfilled: false,
+                }
+            }
+            TriggerCondition::Accumulate {
+                event,
+                target,
+                name,
+            } => CompiledTriggerConditionKind::Accumulate {
+                identifier: event_compiler(event).identifier(),
+                name,
+                extractor: None,
+                sum: 0.0,
+                target: target.finite()?,
+            },
+            TriggerCondition::DeltaGeq {
+                event,
+                threshold,
+                name,
+            } => CompiledTriggerConditionKind::DeltaGeq {
+                identifier: event_compiler(event).identifier(),
+                name,
+                extractor: None,
+                previous: None,
+                threshold: threshold.finite()?,
+                fulfilled: false,
+            },
+            TriggerCondition::CountWithin {
+                event,
+                required,
+                window,
+            } => CompiledTriggerConditionKind::CountWithin {
+                identifier: event_compiler(event).identifier(),
+                required,
+                window,
+                events_seen: 0,
+                positions: VecDeque::new(),
+            },
+            TriggerCondition::StaleFor { trigger_id, events } => {
+                CompiledTriggerConditionKind::StaleFor {
+                    trigger_id,
+                    target: None,
+                    required: events,
+                    received: 0,
+                    fulfilled: false,
+                }
+            }
+            TriggerCondition::Between {
+                low,
+                high,
+                low_bound,
+                high_bound,
+            } => CompiledTriggerConditionKind::Between {
+                low: event_compiler(low),
+                high: event_compiler(high),
+                low_bound,
+                high_bound,
+                fulfilled: false,
+                progress: 0.0,
+            },
+            TriggerCondition::HoldBetween {
+                low,
+                high,
+                tick,
+                required,
+            } => CompiledTriggerConditionKind::HoldBetween {
+                low: event_compiler(low),
+                high: event_compiler(high),
+                tick_identifier: event_compiler(tick).identifier(),
+                in_range: false,
+                count: 0,
+                required,
             },
             TriggerCondition::Sequence { conditions } => {
-                let conditions = conditions
+                let conditions: Vec<_> = conditions
                     .into_iter()
-                    .map(|condition| {
-                        let condition = condition.compile(event_compiler);
-                        assert!(!condition.completed()); // sequences are not allowed to contain `None` conditions.
-                        condition
-                    })
-                    .collect();
+                    .map(|condition| condition.try_compile(event_compiler))
+                    .collect::<Result<_, _>>()?;
+                // Leading conditions that are already completed are skipped, such that the sequence starts at the first condition that can actually make progress.
+                let current_index = conditions
+                    .iter()
+                    .take_while(|condition| condition.completed())
+                    .count();
+                // Apart from the leading ones, sequences are not allowed to contain completed conditions.
+                if let Some(index) = conditions
+                    .iter()
+                    .skip(current_index)
+                    .position(|condition| condition.completed())
+                {
+                    return Err(CompileError::CompletedConditionInSequence {
+                        index: current_index + index,
+                    });
+                }
                 CompiledTriggerConditionKind::Sequence {
-                    current_index: 0,
+                    current_index,
+                    conditions,
+                }
+            }
+            TriggerCondition::StrictSequence { conditions } => {
+                let conditions: Vec<_> = conditions
+                    .into_iter()
+                    .map(|condition| condition.try_compile(event_compiler))
+                    .collect::<Result<_, _>>()?;
+                let current_index = conditions
+                    .iter()
+                    .take_while(|condition| condition.completed())
+                    .count();
+                if let Some(index) = conditions
+                    .iter()
+                    .skip(current_index)
+                    .position(|condition| condition.completed())
+                {
+                    return Err(CompileError::CompletedConditionInSequence {
+                        index: current_index + index,
+                    });
+                }
+                let mut identifiers: Vec<_> = conditions
+                    .iter()
+                    .flat_map(|condition| condition.subscriptions())
+                    .collect();
+                identifiers.sort();
+                identifiers.dedup();
+                CompiledTriggerConditionKind::StrictSequence {
+                    current_index,
+                    conditions,
+                    identifiers,
+                }
+            }
+            TriggerCondition::Pipeline { conditions } => {
+                let conditions: Vec<_> = conditions
+                    .into_iter()
+                    .map(|condition| condition.try_compile(event_compiler))
+                    .collect::<Result<_, _>>()?;
+                let current_index = conditions
+                    .iter()
+                    .take_while(|condition| condition.completed())
+                    .count();
+                CompiledTriggerConditionKind::Pipeline {
+                    current_index,
                     conditions,
                 }
             }
@@ -114,7 +693,7 @@ impl<Event> TriggerCondition<Event> {
                 let mut compiled_conditions = Vec::new();
                 let mut compiled_fulfilled_conditions = Vec::new();
                 for condition in conditions {
-                    let compiled_condition = condition.compile(event_compiler);
+                    let compiled_condition = condition.try_compile(event_compiler)?;
                     if compiled_condition.completed() {
                         compiled_fulfilled_conditions.push(compiled_condition);
                     } else {
@@ -130,7 +709,7 @@ impl<Event> TriggerCondition<Event> {
                 let mut compiled_conditions = Vec::new();
                 let mut compiled_fulfilled_conditions = Vec::new();
                 for condition in conditions {
-                    let compiled_condition = condition.compile(event_compiler);
+                    let compiled_condition = condition.try_compile(event_compiler)?;
                     if compiled_condition.completed() {
                         compiled_fulfilled_conditions.push(compiled_condition);
                     } else {
@@ -140,13 +719,62 @@ impl<Event> TriggerCondition<Event> {
                 CompiledTriggerConditionKind::Or {
                     conditions: compiled_conditions,
                     fulfilled_conditions: compiled_fulfilled_conditions,
+                    priorities: Vec::new(),
+                }
+            }
+            TriggerCondition::PriorityOr { conditions } => {
+                let mut priorities = Vec::new();
+                let mut compiled_conditions = Vec::new();
+                for (priority, condition) in conditions {
+                    priorities.push(priority);
+                    compiled_conditions.push(condition.try_compile(event_compiler)?);
+                }
+                let mut compiled_fulfilled_conditions = Vec::new();
+                CompiledTriggerConditionKind::partition_or(
+                    &mut compiled_conditions,
+                    &mut compiled_fulfilled_conditions,
+                    &mut priorities,
+                );
+                CompiledTriggerConditionKind::Or {
+                    conditions: compiled_conditions,
+                    fulfilled_conditions: compiled_fulfilled_conditions,
+                    priorities,
+                }
+            }
+            TriggerCondition::PrunedOr {
+                conditions,
+                max_lag,
+            } => {
+                let mut compiled_conditions = Vec::new();
+                let mut compiled_fulfilled_conditions = Vec::new();
+                for condition in conditions {
+                    let compiled_condition = condition.try_compile(event_compiler)?;
+                    if compiled_condition.completed() {
+                        compiled_fulfilled_conditions.push(compiled_condition);
+                    } else {
+                        compiled_conditions.push(compiled_condition);
+                    }
+                }
+                let mut pruned_conditions = Vec::new();
+                if compiled_fulfilled_conditions.is_empty() {
+                    CompiledTriggerConditionKind::prune(
+                        &mut compiled_conditions,
+                        &mut pruned_conditions,
+                        max_lag,
+                    );
+                }
+                CompiledTriggerConditionKind::PrunedOr {
+                    conditions: compiled_conditions,
+                    fulfilled_conditions: compiled_fulfilled_conditions,
+                    pruned_conditions,
+                    max_lag,
                 }
             }
             TriggerCondition::AnyN { conditions, n } => {
                 let mut compiled_conditions = Vec::new();
                 let mut compiled_fulfilled_conditions = Vec::new();
                 for condition in conditions {
-                    let compiled_condition = condition.compile(event_compiler);
+                    let compiled_condition = condition.try_compile(event_compiler)?;
                     if compiled_condition.completed() {
                         compiled_fulfilled_conditions.push(compiled_condition);
                     } else {
@@ -159,64 +787,1247 @@ impl<Event> TriggerCondition<Event> {
                     n,
                 }
             }
-        })
+            TriggerCondition::Not { condition, window } => {
+                let condition = condition.try_compile(event_compiler)?;
+                CompiledTriggerConditionKind::Not {
+                    failed: condition.completed(),
+                    condition: Box::new(condition),
+                    window,
+                    events_seen: 0,
+                }
+            }
+            TriggerCondition::Retry {
+                condition,
+                failure_event,
+                max_retries,
+            } => CompiledTriggerConditionKind::Retry {
+                condition: Box::new(condition.try_compile(event_compiler)?),
+                failure_identifier: event_compiler(failure_event).identifier(),
+                max_retries,
+                retries: 0,
+                failed: false,
+            },
+            TriggerCondition::Xor { conditions } => {
+                return TriggerCondition::ExactlyN { conditions, n: 1 }.try_compile(event_compiler)
+            }
+            TriggerCondition::ExactlyN { conditions, n } => {
+                let mut compiled_conditions = Vec::new();
+                let mut compiled_fulfilled_conditions = Vec::new();
+                for condition in conditions {
+                    let compiled_condition = condition.try_compile(event_compiler)?;
+                    if compiled_condition.completed() {
+                        compiled_fulfilled_conditions.push(compiled_condition);
+                    } else {
+                        compiled_conditions.push(compiled_condition);
+                    }
+                }
+                CompiledTriggerConditionKind::ExactlyN {
+                    failed: compiled_fulfilled_conditions.len() > n,
+                    conditions: compiled_conditions,
+                    fulfilled_conditions: compiled_fulfilled_conditions,
+                    n,
+                }
+            }
+        }))
     }
-}
 
-impl<Event: TriggerEvent> CompiledTriggerCondition<Event> {
-    pub(crate) fn new(kind: CompiledTriggerConditionKind<Event>) -> Self {
-        Self {
-            required_progress: kind.required_progress(),
-            current_progress: 0.0,
-            completed: kind.completed(),
-            kind,
+    /// Multiplies the required counts of all count-based conditions by `factor`.
+    /// The results are rounded, but a non-zero count never becomes less than one.
+    pub fn scale_requirements(&mut self, factor: f64) {
+        match self {
+            // Scaling must not affect the other users of the shared condition.
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { .. } => {
+                if let TriggerCondition::Shared { condition } =
+                    std::mem::replace(self, TriggerCondition::None)
+                {
+                    *self = condition.resolve();
+                }
+                self.scale_requirements(factor);
+            }
+            TriggerCondition::None
+            | TriggerCondition::Never
+            | TriggerCondition::Geq { .. }
+            | TriggerCondition::Neq { .. }
+            | TriggerCondition::EqAny { .. }
+            | TriggerCondition::Predicate { .. }
+            | TriggerCondition::PredicateAnyEvent { .. }
+            | TriggerCondition::StaleFor { .. }
+            | TriggerCondition::DeltaGeq { .. }
+            | TriggerCondition::Between { .. } => {}
+            TriggerCondition::EventCount { required, .. }
+            | TriggerCondition::DecayingCount { required, .. }
+            | TriggerCondition::HoldBetween { required, .. }
+            | TriggerCondition::CountWithin { required, .. } => {
+                if *required > 0 {
+                    *required = ((*required as f64 * factor).round() as usize).max(1);
+                }
+            }
+            TriggerCondition::Accumulate { target, .. } => target.0 *= factor,
+            TriggerCondition::Sequence { conditions }
+            | TriggerCondition::StrictSequence { conditions }
+            | TriggerCondition::Pipeline { conditions }
+            | TriggerCondition::And { conditions }
+            | TriggerCondition::Or { conditions }
+            | TriggerCondition::PrunedOr { conditions, .. }
+            | TriggerCondition::AnyN { conditions, .. }
+            | TriggerCondition::Xor { conditions }
+            | TriggerCondition::ExactlyN { conditions, .. } => conditions
+                .iter_mut()
+                .for_each(|condition| condition.scale_requirements(factor)),
+            TriggerCondition::PriorityOr { conditions } => conditions
+                .iter_mut()
+                .for_each(|(_, condition)| condition.scale_requirements(factor)),
+            TriggerCondition::Not { condition, .. } | TriggerCondition::Retry { condition, .. } => {
+                condition.scale_requirements(factor)
+            }
         }
     }
 
-    pub fn required_progress(&self) -> f64 {
-        self.required_progress
-    }
-
-    pub fn current_progress(&self) -> f64 {
-        assert!(self.current_progress.is_finite());
-        self.current_progress
-    }
-
-    pub fn completed(&self) -> bool {
-        self.completed
-    }
-
-    pub(crate) fn execute_event(
-        &mut self,
-        event: &Event,
-    ) -> (Vec<TriggerConditionUpdate<Event::Identifier>>, bool, f64) {
-        assert!(!self.completed);
-        let (trigger_condition_update, result, current_progress) = self.kind.execute_event(event);
-        assert!(current_progress >= self.current_progress - 1e-6);
-        self.current_progress = current_progress;
-        self.completed = result;
-        (trigger_condition_update, result, self.current_progress)
-    }
-
-    pub(crate) fn subscriptions(&self) -> Vec<Event::Identifier> {
-        if self.completed {
-            return Default::default();
-        }
-
-        match &self.kind {
-            CompiledTriggerConditionKind::None => Default::default(),
-            CompiledTriggerConditionKind::Never => Default::default(),
-            CompiledTriggerConditionKind::EventCount { identifier, .. } => vec![identifier.clone()],
-            CompiledTriggerConditionKind::Geq { event, .. } => vec![event.identifier()],
-            CompiledTriggerConditionKind::Sequence {
-                current_index,
-                conditions,
-            } => conditions[*current_index].subscriptions(),
+    /// Simplifies this condition into an equivalent condition with fewer nodes.
+    /// Nested `And`, `Or` and `Sequence` conditions are flattened, trivially fulfilled or unfulfillable sub-conditions are removed,
+    /// and operators with zero or one sub-condition are replaced by their result.
+    /// A `Not` with a window of zero is fulfilled from the start, unless its condition is.
+    /// Otherwise, a `Not` of `Never` is `Never`, since `Never` receives no events that could fill the window.
+    pub fn simplify(self) -> Self {
+        match self {
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { condition } => condition.resolve().simplify(),
+            TriggerCondition::EventCount { required: 0, .. }
+            | TriggerCondition::DecayingCount { required: 0, .. }
+            | TriggerCondition::HoldBetween { required: 0, .. }
+            | TriggerCondition::CountWithin { required: 0, .. } => TriggerCondition::None,
+            TriggerCondition::Accumulate { target, .. }
+                if target.0.is_finite() && target.0 <= 0.0 =>
+            {
+                TriggerCondition::None
+            }
+            TriggerCondition::CountWithin {
+                required, window, ..
+            } if required > window => TriggerCondition::Never,
+            TriggerCondition::Sequence { conditions } => {
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => {}
+                        TriggerCondition::Never => return TriggerCondition::Never,
+                        TriggerCondition::Sequence { mut conditions } => {
+                            simplified_conditions.append(&mut conditions)
+                        }
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                Self::simplify_singleton(simplified_conditions)
+                    .unwrap_or_else(|conditions| TriggerCondition::Sequence { conditions })
+            }
+            TriggerCondition::StrictSequence { conditions } => {
+                // Nested and single conditions are kept, since restarting on events that do not advance changes their meaning.
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => {}
+                        TriggerCondition::Never => return TriggerCondition::Never,
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                if simplified_conditions.is_empty() {
+                    TriggerCondition::None
+                } else {
+                    TriggerCondition::StrictSequence {
+                        conditions: simplified_conditions,
+                    }
+                }
+            }
+            TriggerCondition::Pipeline { conditions } => {
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => {}
+                        TriggerCondition::Never => return TriggerCondition::Never,
+                        TriggerCondition::Pipeline { mut conditions } => {
+                            simplified_conditions.append(&mut conditions)
+                        }
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                Self::simplify_singleton(simplified_conditions)
+                    .unwrap_or_else(|conditions| TriggerCondition::Pipeline { conditions })
+            }
+            TriggerCondition::And { conditions } => {
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => {}
+                        TriggerCondition::Never => return TriggerCondition::Never,
+                        TriggerCondition::And { mut conditions } => {
+                            simplified_conditions.append(&mut conditions)
+                        }
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                Self::simplify_singleton(simplified_conditions)
+                    .unwrap_or_else(|conditions| TriggerCondition::And { conditions })
+            }
+            TriggerCondition::Or { conditions } => {
+                if conditions.is_empty() {
+                    return TriggerCondition::None;
+                }
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => return TriggerCondition::None,
+                        TriggerCondition::Never => {}
+                        TriggerCondition::Or { mut conditions } => {
+                            simplified_conditions.append(&mut conditions)
+                        }
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                if simplified_conditions.is_empty() {
+                    return TriggerCondition::Never;
+                }
+                Self::simplify_singleton(simplified_conditions)
+                    .unwrap_or_else(|conditions| TriggerCondition::Or { conditions })
+            }
+            // Not flattened, since nested `or` conditions decide the winner among their own sub-conditions.
+            TriggerCondition::PriorityOr { conditions } => {
+                if conditions.is_empty() {
+                    return TriggerCondition::None;
+                }
+                let mut simplified_conditions = Vec::new();
+                for (priority, condition) in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => return TriggerCondition::None,
+                        TriggerCondition::Never => {}
+                        condition => simplified_conditions.push((priority, condition)),
+                    }
+                }
+                if simplified_conditions.len() == 1 {
+                    simplified_conditions.pop().unwrap().1
+                } else if simplified_conditions.is_empty() {
+                    TriggerCondition::Never
+                } else {
+                    TriggerCondition::PriorityOr {
+                        conditions: simplified_conditions,
+                    }
+                }
+            }
+            TriggerCondition::PrunedOr {
+                conditions,
+                max_lag,
+            } => {
+                if conditions.is_empty() {
+                    return TriggerCondition::None;
+                }
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => return TriggerCondition::None,
+                        TriggerCondition::Never => {}
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                if simplified_conditions.is_empty() {
+                    return TriggerCondition::Never;
+                }
+                Self::simplify_singleton(simplified_conditions).unwrap_or_else(|conditions| {
+                    TriggerCondition::PrunedOr {
+                        conditions,
+                        max_lag,
+                    }
+                })
+            }
+            TriggerCondition::AnyN { conditions, mut n } => {
+                let mut simplified_conditions = Vec::new();
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => n = n.saturating_sub(1),
+                        TriggerCondition::Never => {}
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                if n == 0 {
+                    TriggerCondition::None
+                } else if n > simplified_conditions.len() {
+                    TriggerCondition::Never
+                } else if n == 1 {
+                    TriggerCondition::Or {
+                        conditions: simplified_conditions,
+                    }
+                    .simplify()
+                } else if n == simplified_conditions.len() {
+                    TriggerCondition::And {
+                        conditions: simplified_conditions,
+                    }
+                    .simplify()
+                } else {
+                    TriggerCondition::AnyN {
+                        conditions: simplified_conditions,
+                        n,
+                    }
+                }
+            }
+            TriggerCondition::Not { condition, window } => match condition.simplify() {
+                TriggerCondition::None => TriggerCondition::Never,
+                _ if window == 0 => TriggerCondition::None,
+                TriggerCondition::Never => TriggerCondition::Never,
+                condition => TriggerCondition::Not {
+                    condition: Box::new(condition),
+                    window,
+                },
+            },
+            TriggerCondition::Retry {
+                condition,
+                failure_event,
+                max_retries,
+            } => match condition.simplify() {
+                TriggerCondition::None => TriggerCondition::None,
+                TriggerCondition::Never => TriggerCondition::Never,
+                condition => TriggerCondition::Retry {
+                    condition: Box::new(condition),
+                    failure_event,
+                    max_retries,
+                },
+            },
+            TriggerCondition::Xor { conditions } => {
+                let mut simplified_conditions = Vec::new();
+                let mut fulfilled_count = 0;
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => fulfilled_count += 1,
+                        TriggerCondition::Never => {}
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                match fulfilled_count {
+                    0 => match simplified_conditions.len() {
+                        0 => TriggerCondition::Never,
+                        1 => simplified_conditions.pop().unwrap(),
+                        _ => TriggerCondition::Xor {
+                            conditions: simplified_conditions,
+                        },
+                    },
+                    1 => TriggerCondition::None,
+                    _ => TriggerCondition::Never,
+                }
+            }
+            TriggerCondition::ExactlyN { conditions, n } => {
+                let mut simplified_conditions = Vec::new();
+                let mut fulfilled_count = 0;
+                for condition in conditions {
+                    match condition.simplify() {
+                        TriggerCondition::None => fulfilled_count += 1,
+                        TriggerCondition::Never => {}
+                        condition => simplified_conditions.push(condition),
+                    }
+                }
+                if fulfilled_count > n || fulfilled_count + simplified_conditions.len() < n {
+                    TriggerCondition::Never
+                } else if fulfilled_count == n {
+                    TriggerCondition::None
+                } else if n - fulfilled_count == simplified_conditions.len() {
+                    TriggerCondition::And {
+                        conditions: simplified_conditions,
+                    }
+                    .simplify()
+                } else if n - fulfilled_count == 1 {
+                    TriggerCondition::Xor {
+                        conditions: simplified_conditions,
+                    }
+                    .simplify()
+                } else {
+                    TriggerCondition::ExactlyN {
+                        conditions: simplified_conditions,
+                        n: n - fulfilled_count,
+                    }
+                }
+            }
+            TriggerCondition::EqAny { events } if events.is_empty() => TriggerCondition::Never,
+            condition => condition,
+        }
+    }
+
+    /// Returns the result of an operator with the given sub-conditions, if the operator is redundant.
+    fn simplify_singleton(mut conditions: Vec<Self>) -> Result<Self, Vec<Self>> {
+        match conditions.len() {
+            0 => Ok(TriggerCondition::None),
+            1 => Ok(conditions.pop().unwrap()),
+            _ => Err(conditions),
+        }
+    }
+
+    /// Transforms this condition into a canonical form, such that equivalent conditions that differ only
+    /// in the order of the sub-conditions of `And`, `Or` and `AnyN` become equal.
+    /// The result is simplified as by [`simplify`](TriggerCondition::simplify).
+    pub fn canonicalize(self) -> Self
+    where
+        Event: Ord,
+    {
+        self.simplify().sort_sub_conditions()
+    }
+
+    fn sort_sub_conditions(self) -> Self
+    where
+        Event: Ord,
+    {
+        let sort = |conditions: Vec<Self>| {
+            let mut conditions: Vec<_> = conditions
+                .into_iter()
+                .map(|condition| condition.sort_sub_conditions())
+                .collect();
+            conditions.sort();
+            conditions
+        };
+
+        match self {
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { condition } => condition.resolve().sort_sub_conditions(),
+            TriggerCondition::Sequence { conditions } => TriggerCondition::Sequence {
+                conditions: conditions
+                    .into_iter()
+                    .map(|condition| condition.sort_sub_conditions())
+                    .collect(),
+            },
+            TriggerCondition::StrictSequence { conditions } => TriggerCondition::StrictSequence {
+                conditions: conditions
+                    .into_iter()
+                    .map(|condition| condition.sort_sub_conditions())
+                    .collect(),
+            },
+            TriggerCondition::Pipeline { conditions } => TriggerCondition::Pipeline {
+                conditions: conditions
+                    .into_iter()
+                    .map(|condition| condition.sort_sub_conditions())
+                    .collect(),
+            },
+            TriggerCondition::And { conditions } => TriggerCondition::And {
+                conditions: sort(conditions),
+            },
+            TriggerCondition::Or { conditions } => TriggerCondition::Or {
+                conditions: sort(conditions),
+            },
+            TriggerCondition::PriorityOr { conditions } => {
+                let mut conditions: Vec<_> = conditions
+                    .into_iter()
+                    .map(|(priority, condition)| (priority, condition.sort_sub_conditions()))
+                    .collect();
+                conditions.sort();
+                TriggerCondition::PriorityOr { conditions }
+            }
+            TriggerCondition::PrunedOr {
+                conditions,
+                max_lag,
+            } => TriggerCondition::PrunedOr {
+                conditions: sort(conditions),
+                max_lag,
+            },
+            TriggerCondition::AnyN { conditions, n } => TriggerCondition::AnyN {
+                conditions: sort(conditions),
+                n,
+            },
+            TriggerCondition::Not { condition, window } => TriggerCondition::Not {
+                condition: Box::new(condition.sort_sub_conditions()),
+                window,
+            },
+            TriggerCondition::Retry {
+                condition,
+                failure_event,
+                max_retries,
+            } => TriggerCondition::Retry {
+                condition: Box::new(condition.sort_sub_conditions()),
+                failure_event,
+                max_retries,
+            },
+            TriggerCondition::EqAny { mut events } => {
+                events.sort();
+                TriggerCondition::EqAny { events }
+            }
+            TriggerCondition::Xor { conditions } => TriggerCondition::Xor {
+                conditions: sort(conditions),
+            },
+            TriggerCondition::ExactlyN { conditions, n } => TriggerCondition::ExactlyN {
+                conditions: sort(conditions),
+                n,
+            },
+            condition => condition,
+        }
+    }
+}
+
+impl<Event> TriggerCondition<Event> {
+    /// Checks this condition and all its sub-conditions for authoring mistakes, such as operators without sub-conditions.
+    /// Returns the first problem found, depth-first.
+    pub fn validate(&self) -> Result<(), ConditionError> {
+        let (name, conditions) = match self {
+            TriggerCondition::Sequence { conditions } => ("sequence", conditions),
+            TriggerCondition::StrictSequence { conditions } => ("strict_sequence", conditions),
+            TriggerCondition::Pipeline { conditions } => ("pipeline", conditions),
+            TriggerCondition::And { conditions } => ("and", conditions),
+            TriggerCondition::Or { conditions } => ("or", conditions),
+            TriggerCondition::PrunedOr { conditions, .. } => ("or_pruned", conditions),
+            TriggerCondition::Xor { conditions } => ("xor", conditions),
+            TriggerCondition::AnyN { conditions, .. } => ("any_n", conditions),
+            TriggerCondition::ExactlyN { conditions, .. } => ("exactly_n", conditions),
+            TriggerCondition::EqAny { events } if events.is_empty() => {
+                return Err(ConditionError::EmptyOperator("eq_any"))
+            }
+            TriggerCondition::PriorityOr { conditions } if conditions.is_empty() => {
+                return Err(ConditionError::EmptyOperator("or_priority"))
+            }
+            TriggerCondition::PriorityOr { conditions } => {
+                return conditions
+                    .iter()
+                    .try_for_each(|(_, condition)| condition.validate())
+            }
+            TriggerCondition::Not { condition, .. } | TriggerCondition::Retry { condition, .. } => {
+                return condition.validate()
+            }
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { condition } => return condition.condition.validate(),
+            _ => return Ok(()),
+        };
+
+        if conditions.is_empty() {
+            return Err(ConditionError::EmptyOperator(name));
+        }
+        if let TriggerCondition::AnyN { n, .. } | TriggerCondition::ExactlyN { n, .. } = self {
+            if *n > conditions.len() {
+                return Err(ConditionError::ThresholdTooHigh {
+                    n: *n,
+                    available: conditions.len(),
+                });
+            }
+        }
+        conditions.iter().try_for_each(TriggerCondition::validate)
+    }
+
+    /// Iterates over the reference events of all leaf conditions, depth-first in the order of the sub-conditions.
+    /// Each event is yielded with the role it has in its leaf condition.
+    pub fn leaf_events(&self) -> impl Iterator<Item = (&Event, ConditionKindTag)> {
+        let mut leaf_events = Vec::new();
+        self.collect_leaf_events(&mut leaf_events);
+        leaf_events.into_iter()
+    }
+
+    fn collect_leaf_events<'event>(
+        &'event self,
+        leaf_events: &mut Vec<(&'event Event, ConditionKindTag)>,
+    ) {
+        match self {
+            TriggerCondition::None
+            | TriggerCondition::Never
+            | TriggerCondition::PredicateAnyEvent { .. }
+            | TriggerCondition::StaleFor { .. } => {}
+            TriggerCondition::EventCount { event, .. } => {
+                leaf_events.push((event, ConditionKindTag::EventCount))
+            }
+            TriggerCondition::DecayingCount {
+                event,
+                penalty_event,
+                ..
+            } => {
+                leaf_events.push((event, ConditionKindTag::DecayingCount));
+                leaf_events.push((penalty_event, ConditionKindTag::DecayingCountPenalty));
+            }
+            TriggerCondition::Geq { event } => leaf_events.push((event, ConditionKindTag::Geq)),
+            TriggerCondition::Neq { event } => leaf_events.push((event, ConditionKindTag::Neq)),
+            TriggerCondition::EqAny { events } => {
+                leaf_events.extend(events.iter().map(|event| (event, ConditionKindTag::EqAny)))
+            }
+            TriggerCondition::Predicate { event, .. } => {
+                leaf_events.push((event, ConditionKindTag::Predicate))
+            }
+            TriggerCondition::Accumulate { event, .. } => {
+                leaf_events.push((event, ConditionKindTag::Accumulate))
+            }
+            TriggerCondition::DeltaGeq { event, .. } => {
+                leaf_events.push((event, ConditionKindTag::DeltaGeq))
+            }
+            TriggerCondition::CountWithin { event, .. } => {
+                leaf_events.push((event, ConditionKindTag::CountWithin))
+            }
+            TriggerCondition::Between { low, high, .. } => {
+                leaf_events.push((low, ConditionKindTag::BetweenLow));
+                leaf_events.push((high, ConditionKindTag::BetweenHigh));
+            }
+            TriggerCondition::HoldBetween {
+                low, high, tick, ..
+            } => {
+                leaf_events.push((low, ConditionKindTag::HoldBetweenLow));
+                leaf_events.push((high, ConditionKindTag::HoldBetweenHigh));
+                leaf_events.push((tick, ConditionKindTag::HoldBetweenTick));
+            }
+            TriggerCondition::Sequence { conditions }
+            | TriggerCondition::StrictSequence { conditions }
+            | TriggerCondition::Pipeline { conditions }
+            | TriggerCondition::And { conditions }
+            | TriggerCondition::Or { conditions }
+            | TriggerCondition::PrunedOr { conditions, .. }
+            | TriggerCondition::AnyN { conditions, .. }
+            | TriggerCondition::Xor { conditions }
+            | TriggerCondition::ExactlyN { conditions, .. } => conditions
+                .iter()
+                .for_each(|condition| condition.collect_leaf_events(leaf_events)),
+            TriggerCondition::PriorityOr { conditions } => conditions
+                .iter()
+                .for_each(|(_, condition)| condition.collect_leaf_events(leaf_events)),
+            TriggerCondition::Not { condition, .. } => condition.collect_leaf_events(leaf_events),
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { condition } => {
+                condition.condition.collect_leaf_events(leaf_events)
+            }
+            TriggerCondition::Retry {
+                condition,
+                failure_event,
+                ..
+            } => {
+                condition.collect_leaf_events(leaf_events);
+                leaf_events.push((failure_event, ConditionKindTag::RetryFailure));
+            }
+        }
+    }
+
+    /// Renders this condition using infix `&` and `|` operators, naming events with `event_namer`.
+    /// Parentheses are placed such that the grouping matches the structure of the condition.
+    pub fn to_infix_string<EventNamer: Fn(&Event) -> String>(
+        &self,
+        event_namer: &EventNamer,
+    ) -> String {
+        let list = |conditions: &Vec<Self>| {
+            conditions
+                .iter()
+                .map(|condition| condition.to_infix_string(event_namer))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        // `&` binds stronger than `|`, and nested operators of the same kind are parenthesized to keep the structure visible.
+        let infix = |conditions: &Vec<Self>, operator: &str, is_and: bool| {
+            conditions
+                .iter()
+                .map(|condition| match condition {
+                    TriggerCondition::And { conditions } if !is_and && conditions.len() > 1 => {
+                        condition.to_infix_string(event_namer)
+                    }
+                    TriggerCondition::And { conditions } | TriggerCondition::Or { conditions }
+                        if conditions.len() > 1 =>
+                    {
+                        format!("({})", condition.to_infix_string(event_namer))
+                    }
+                    condition => condition.to_infix_string(event_namer),
+                })
+                .collect::<Vec<_>>()
+                .join(operator)
+        };
+
+        match self {
+            TriggerCondition::None => "none".to_string(),
+            TriggerCondition::Never => "never".to_string(),
+            TriggerCondition::EventCount { event, required } => {
+                format!("event_count({}, {required})", event_namer(event))
+            }
+            TriggerCondition::DecayingCount {
+                event,
+                required,
+                penalty_event,
+            } => format!(
+                "decaying_count({}, {required}, {})",
+                event_namer(event),
+                event_namer(penalty_event)
+            ),
+            TriggerCondition::Geq { event } => format!("geq({})", event_namer(event)),
+            TriggerCondition::Neq { event } => format!("neq({})", event_namer(event)),
+            TriggerCondition::Predicate { event, name } => {
+                format!("predicate({}, {name})", event_namer(event))
+            }
+            TriggerCondition::PredicateAnyEvent { name } => format!("predicate_any_event({name})"),
+            TriggerCondition::StaleFor { trigger_id, events } => {
+                format!("stale_for({trigger_id}, {events})")
+            }
+            TriggerCondition::Accumulate {
+                event,
+                target,
+                name,
+            } => format!("accumulate({}, {target}, {name})", event_namer(event)),
+            TriggerCondition::DeltaGeq {
+                event,
+                threshold,
+                name,
+            } => format!("delta_geq({}, {threshold}, {name})", event_namer(event)),
+            TriggerCondition::CountWithin {
+                event,
+                required,
+                window,
+            } => format!("count_within({}, {required}, {window})", event_namer(event)),
+            TriggerCondition::EqAny { events } => format!(
+                "eq_any([{}])",
+                events
+                    .iter()
+                    .map(event_namer)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TriggerCondition::Between {
+                low,
+                high,
+                low_bound: BoundKind::Inclusive,
+                high_bound: BoundKind::Inclusive,
+            } => format!("between({}, {})", event_namer(low), event_namer(high)),
+            TriggerCondition::Between {
+                low,
+                high,
+                low_bound,
+                high_bound,
+            } => format!(
+                "between_bounds({}, {low_bound:?}, {}, {high_bound:?})",
+                event_namer(low),
+                event_namer(high)
+            ),
+            TriggerCondition::HoldBetween {
+                low,
+                high,
+                tick,
+                required,
+            } => format!(
+                "hold_between({}, {}, {}, {required})",
+                event_namer(low),
+                event_namer(high),
+                event_namer(tick)
+            ),
+            TriggerCondition::Sequence { conditions } => {
+                format!("sequence([{}])", list(conditions))
+            }
+            TriggerCondition::StrictSequence { conditions } => {
+                format!("strict_sequence([{}])", list(conditions))
+            }
+            #[cfg(feature = "shared")]
+            TriggerCondition::Shared { condition } => {
+                condition.condition.to_infix_string(event_namer)
+            }
+            TriggerCondition::Pipeline { conditions } => {
+                format!("pipeline([{}])", list(conditions))
+            }
+            TriggerCondition::And { conditions } if conditions.len() > 1 => {
+                infix(conditions, " & ", true)
+            }
+            TriggerCondition::And { conditions } => format!("and([{}])", list(conditions)),
+            TriggerCondition::Or { conditions } if conditions.len() > 1 => {
+                infix(conditions, " | ", false)
+            }
+            TriggerCondition::Or { conditions } => format!("or([{}])", list(conditions)),
+            TriggerCondition::PriorityOr { conditions } => format!(
+                "or_priority([{}])",
+                conditions
+                    .iter()
+                    .map(|(priority, condition)| format!(
+                        "({priority}, {})",
+                        condition.to_infix_string(event_namer)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TriggerCondition::PrunedOr {
+                conditions,
+                max_lag,
+            } => format!("or_pruned([{}], {max_lag})", list(conditions)),
+            TriggerCondition::AnyN { conditions, n } => {
+                format!("any_n([{}], {n})", list(conditions))
+            }
+            TriggerCondition::Not { condition, window } => {
+                format!("not({}, {window})", condition.to_infix_string(event_namer))
+            }
+            TriggerCondition::Retry {
+                condition,
+                failure_event,
+                max_retries,
+            } => format!(
+                "retry({}, {}, {max_retries})",
+                condition.to_infix_string(event_namer),
+                event_namer(failure_event)
+            ),
+            TriggerCondition::Xor { conditions } => format!("xor([{}])", list(conditions)),
+            TriggerCondition::ExactlyN { conditions, n } => {
+                format!("exactly_n([{}], {n})", list(conditions))
+            }
+        }
+    }
+}
+
+impl<Event: Debug> Display for TriggerCondition<Event> {
+    /// Renders the condition with [`TriggerCondition::to_infix_string`], naming events by their debug representation.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.to_infix_string(&|event: &Event| format!("{event:?}"))
+        )
+    }
+}
+
+impl<Event: TriggerEvent> CompiledTriggerCondition<Event> {
+    pub(crate) fn new(kind: CompiledTriggerConditionKind<Event>) -> Self {
+        let mut result = Self {
+            required_progress: 0.0,
+            current_progress: 0.0,
+            completed: false,
+            failed: false,
+            kind,
+        };
+        result.derive_cached_state();
+        result
+    }
+
+    /// Re-derives the state of this condition after it was changed, e.g. by [`Self::set_any_n_threshold`].
+    /// The sub-conditions are recomputed first, completed sub-conditions become fulfilled, and sequences advance past completed steps.
+    /// Then the cached `required_progress`, `completed` and `failed` values are re-derived,
+    /// and the current progress is clamped to the required progress, or set to it if the condition is completed.
+    /// Returns the resulting subscription updates.
+    pub fn recompute(&mut self) -> Vec<TriggerConditionUpdate<Event::Identifier>> {
+        let subscriptions = self.subscriptions();
+        for condition in self.kind.sub_conditions_mut() {
+            condition.recompute();
+        }
+        self.kind.settle();
+        self.derive_cached_state();
+
+        let mut new_subscriptions = self.subscriptions();
+        let mut trigger_condition_updates = Vec::new();
+        for identifier in subscriptions {
+            if let Some(index) = new_subscriptions.iter().position(|new| *new == identifier) {
+                new_subscriptions.swap_remove(index);
+            } else {
+                trigger_condition_updates.push(TriggerConditionUpdate::Unsubscribe(identifier));
+            }
+        }
+        trigger_condition_updates.extend(
+            new_subscriptions
+                .into_iter()
+                .map(TriggerConditionUpdate::Subscribe),
+        );
+        trigger_condition_updates
+    }
+
+    /// Re-derives the cached `required_progress`, `completed` and `failed` values from the state of the condition, see [`Self::recompute`].
+    fn derive_cached_state(&mut self) {
+        self.required_progress = self.kind.required_progress();
+        self.completed = self.kind.completed();
+        self.failed = !self.completed && self.kind.failed();
+        self.current_progress = if self.completed {
+            self.required_progress
+        } else {
+            self.current_progress.max(0.0).min(self.required_progress)
+        };
+    }
+
+    /// Sets the predicate of all `predicate` conditions with the given name, including those nested in other conditions.
+    pub(crate) fn register_predicate(&mut self, name: &str, predicate: &EventPredicate<Event>) {
+        if let CompiledTriggerConditionKind::Predicate {
+            name: predicate_name,
+            predicate: registered_predicate,
+            ..
+        } = &mut self.kind
+        {
+            if predicate_name == name {
+                *registered_predicate = Some(predicate.clone());
+            }
+        }
+        for condition in self.kind.sub_conditions_mut() {
+            condition.register_predicate(name, predicate);
+        }
+    }
+
+    /// Sets the target trigger of all `stale_for` conditions to the index returned by `resolve` for their trigger id, including those nested in other conditions.
+    /// Returns false if `resolve` returned `None` for any of them.
+    pub(crate) fn resolve_stale_for(&mut self, resolve: &dyn Fn(&str) -> Option<usize>) -> bool {
+        let mut resolved = true;
+        if let CompiledTriggerConditionKind::StaleFor {
+            trigger_id, target, ..
+        } = &mut self.kind
+        {
+            *target = resolve(trigger_id);
+            resolved = target.is_some();
+        }
+        for condition in self.kind.sub_conditions_mut() {
+            resolved &= condition.resolve_stale_for(resolve);
+        }
+        resolved
+    }
+
+    /// Sets the extractor of all `accumulate` and `delta_geq` conditions with the given name, including those nested in other conditions.
+    pub(crate) fn register_extractor(&mut self, name: &str, extractor: &EventExtractor<Event>) {
+        if let CompiledTriggerConditionKind::Accumulate {
+            name: extractor_name,
+            extractor: registered_extractor,
+            ..
+        }
+        | CompiledTriggerConditionKind::DeltaGeq {
+            name: extractor_name,
+            extractor: registered_extractor,
+            ..
+        } = &mut self.kind
+        {
+            if extractor_name == name {
+                *registered_extractor = Some(extractor.clone());
+            }
+        }
+        for condition in self.kind.sub_conditions_mut() {
+            condition.register_extractor(name, extractor);
+        }
+    }
+
+    /// Sets the number of sub-conditions required by an `any_n` condition and recomputes it, see [`Self::recompute`].
+    /// Returns the resulting subscription updates.
+    ///
+    /// Panics if this is not an `any_n` condition.
+    pub fn set_any_n_threshold(
+        &mut self,
+        threshold: usize,
+    ) -> Vec<TriggerConditionUpdate<Event::Identifier>> {
+        match &mut self.kind {
+            CompiledTriggerConditionKind::AnyN { n, .. } => *n = threshold,
+            _ => panic!("not an any_n condition"),
+        }
+        self.recompute()
+    }
+
+    /// Returns the number of completed sub-conditions and the number of required sub-conditions if this is an `any_n` condition.
+    pub fn any_n_progress(&self) -> Option<(usize, usize)> {
+        match &self.kind {
+            CompiledTriggerConditionKind::AnyN {
+                fulfilled_conditions,
+                n,
+                ..
+            } => Some((fulfilled_conditions.len(), *n)),
+            _ => None,
+        }
+    }
+
+    /// Returns the sub-condition that completed this condition if this is a completed `or` condition.
+    /// If several sub-conditions completed on the same event, this is the one with the highest priority, see [`crate::or_priority`].
+    pub fn or_winner(&self) -> Option<&CompiledTriggerCondition<Event>> {
+        match &self.kind {
+            CompiledTriggerConditionKind::Or {
+                fulfilled_conditions,
+                ..
+            } => fulfilled_conditions.first(),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier and the number of missing events of each `event_count` condition that is not completed yet,
+    /// including those nested in other conditions, e.g. for a checklist of objectives.
+    pub fn remaining_counts(&self) -> Vec<(Event::Identifier, usize)> {
+        let mut remaining_counts = Vec::new();
+        self.collect_remaining_counts(&mut remaining_counts);
+        remaining_counts
+    }
+
+    fn collect_remaining_counts(&self, remaining_counts: &mut Vec<(Event::Identifier, usize)>) {
+        if let CompiledTriggerConditionKind::EventCount {
+            identifier,
+            count,
+            required,
+        } = &self.kind
+        {
+            if count < required {
+                remaining_counts.push((identifier.clone(), required - count));
+            }
+        }
+        for condition in self.kind.sub_conditions() {
+            condition.collect_remaining_counts(remaining_counts);
+        }
+    }
+
+    pub fn required_progress(&self) -> f64 {
+        self.required_progress
+    }
+
+    pub fn current_progress(&self) -> f64 {
+        assert!(self.current_progress.is_finite());
+        self.current_progress
+    }
+
+    pub fn completed(&self) -> bool {
+        self.completed
+    }
+
+    /// Returns the given progress of this condition relative to its required progress.
+    /// Conditions that require no progress count as complete.
+    fn relative_progress(&self, progress: f64) -> f64 {
+        if self.required_progress > 0.0 {
+            progress / self.required_progress
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns true if this condition can never complete anymore, e.g. because the inner condition of a `not` completed.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Executes the given event on this condition.
+    /// Returns the resulting subscription updates, whether the condition completed, and the current progress.
+    ///
+    /// Without a trigger system, conditions counting events assume that each event directly follows the previous event they received.
+    pub fn execute_event(
+        &mut self,
+        event: &Event,
+    ) -> (Vec<TriggerConditionUpdate<Event::Identifier>>, bool, f64) {
+        self.execute_event_in(event, None)
+    }
+
+    /// Like [`Self::execute_event`], but with the position of the event in the event stream of the trigger system, if any.
+    pub(crate) fn execute_event_in(
+        &mut self,
+        event: &Event,
+        context: Option<EventContext<'_>>,
+    ) -> (Vec<TriggerConditionUpdate<Event::Identifier>>, bool, f64) {
+        assert!(!self.completed);
+        if self.failed {
+            return (Default::default(), false, self.current_progress);
+        }
+
+        let (mut trigger_condition_update, result, current_progress) =
+            self.kind.execute_event(event, context);
+        if self.kind.failed() {
+            assert!(!result);
+            // A failed condition does not need any events anymore.
+            trigger_condition_update.extend(
+                self.subscriptions()
+                    .into_iter()
+                    .map(TriggerConditionUpdate::Unsubscribe),
+            );
+            self.failed = true;
+            return (trigger_condition_update, false, self.current_progress);
+        }
+        // The progress is not necessarily monotonic, e.g. `decaying_count` loses progress on penalty events.
+        self.current_progress = current_progress;
+        self.completed = result;
+        (trigger_condition_update, result, self.current_progress)
+    }
+
+    /// Resets this condition and all its sub-conditions to the state they had directly after compilation.
+    /// The order of the sub-conditions of `and`, `or`, `any_n` and `xor` conditions may change.
+    pub fn reset(&mut self) {
+        self.kind.reset();
+        self.current_progress = 0.0;
+        self.derive_cached_state();
+    }
+
+    /// Returns an estimate of the number of bytes allocated on the heap by this condition.
+    /// Heap allocations made by events and identifiers are not included.
+    pub(crate) fn heap_size(&self) -> usize {
+        let vec_size = |conditions: &Vec<CompiledTriggerCondition<Event>>| {
+            conditions.capacity() * std::mem::size_of::<CompiledTriggerCondition<Event>>()
+                + conditions
+                    .iter()
+                    .map(|condition| condition.heap_size())
+                    .sum::<usize>()
+        };
+
+        match &self.kind {
+            CompiledTriggerConditionKind::None
+            | CompiledTriggerConditionKind::Never
+            | CompiledTriggerConditionKind::EventCount { .. }
+            | CompiledTriggerConditionKind::DecayingCount { .. }
+            | CompiledTriggerConditionKind::Geq { .. }
+            | CompiledTriggerConditionKind::Neq { .. }
+            | CompiledTriggerConditionKind::Between { .. }
+            | CompiledTriggerConditionKind::HoldBetween { .. } => 0,
+            CompiledTriggerConditionKind::EqAny { events, .. } => {
+                events.capacity() * std::mem::size_of::<Event>()
+            }
+            CompiledTriggerConditionKind::Predicate { name, .. }
+            | CompiledTriggerConditionKind::Accumulate { name, .. }
+            | CompiledTriggerConditionKind::DeltaGeq { name, .. }
+            | CompiledTriggerConditionKind::StaleFor {
+                trigger_id: name, ..
+            } => name.capacity(),
+            CompiledTriggerConditionKind::CountWithin { positions, .. } => {
+                positions.capacity() * std::mem::size_of::<usize>()
+            }
+            CompiledTriggerConditionKind::Sequence { conditions, .. }
+            | CompiledTriggerConditionKind::Pipeline { conditions, .. } => vec_size(conditions),
+            CompiledTriggerConditionKind::StrictSequence {
+                conditions,
+                identifiers,
+                ..
+            } => {
+                vec_size(conditions)
+                    + identifiers.capacity() * std::mem::size_of::<Event::Identifier>()
+            }
+            CompiledTriggerConditionKind::And {
+                conditions,
+                fulfilled_conditions,
+            }
+            | CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => vec_size(conditions) + vec_size(fulfilled_conditions),
+            CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                priorities,
+            } => {
+                vec_size(conditions)
+                    + vec_size(fulfilled_conditions)
+                    + priorities.capacity() * std::mem::size_of::<i32>()
+            }
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                pruned_conditions,
+                ..
+            } => {
+                vec_size(conditions) + vec_size(fulfilled_conditions) + vec_size(pruned_conditions)
+            }
+            CompiledTriggerConditionKind::Not { condition, .. }
+            | CompiledTriggerConditionKind::Retry { condition, .. } => {
+                std::mem::size_of::<CompiledTriggerCondition<Event>>() + condition.heap_size()
+            }
+        }
+    }
+
+    /// Returns true if this condition currently needs to receive all events, regardless of their identifier.
+    /// This is the case while a `predicate_any_event` condition is waiting for events.
+    pub fn subscribes_to_all(&self) -> bool {
+        if self.completed || self.failed {
+            return false;
+        }
+
+        match &self.kind {
+            CompiledTriggerConditionKind::Predicate { identifier, .. } => identifier.is_none(),
+            CompiledTriggerConditionKind::CountWithin { .. }
+            | CompiledTriggerConditionKind::StaleFor { .. } => true,
+            CompiledTriggerConditionKind::Sequence {
+                current_index,
+                conditions,
+            }
+            | CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                ..
+            } => conditions[*current_index].subscribes_to_all(),
+            CompiledTriggerConditionKind::Pipeline { conditions, .. }
+            | CompiledTriggerConditionKind::And { conditions, .. }
+            | CompiledTriggerConditionKind::Or { conditions, .. }
+            | CompiledTriggerConditionKind::PrunedOr { conditions, .. }
+            | CompiledTriggerConditionKind::AnyN { conditions, .. }
+            | CompiledTriggerConditionKind::ExactlyN { conditions, .. } => conditions
+                .iter()
+                .any(|condition| condition.subscribes_to_all()),
+            CompiledTriggerConditionKind::Not { condition, .. }
+            | CompiledTriggerConditionKind::Retry { condition, .. } => {
+                condition.subscribes_to_all()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the identifiers of all leaf conditions, regardless of their state, depth-first in the order of the sub-conditions.
+    /// Leaf conditions that receive all events, see [`Self::subscribes_to_all`], have no identifier.
+    pub(crate) fn leaf_identifiers(&self) -> Vec<Event::Identifier> {
+        let mut identifiers = match &self.kind {
+            CompiledTriggerConditionKind::EventCount { identifier, .. }
+            | CompiledTriggerConditionKind::CountWithin { identifier, .. }
+            | CompiledTriggerConditionKind::DeltaGeq { identifier, .. }
+            | CompiledTriggerConditionKind::Accumulate { identifier, .. }
+            | CompiledTriggerConditionKind::Retry {
+                failure_identifier: identifier,
+                ..
+            } => vec![identifier.clone()],
+            CompiledTriggerConditionKind::Predicate { identifier, .. } => {
+                identifier.iter().cloned().collect()
+            }
+            CompiledTriggerConditionKind::DecayingCount {
+                identifier,
+                penalty_identifier,
+                ..
+            } => vec![identifier.clone(), penalty_identifier.clone()],
+            CompiledTriggerConditionKind::Geq { event, .. }
+            | CompiledTriggerConditionKind::Neq { event, .. }
+            | CompiledTriggerConditionKind::Between { low: event, .. } => vec![event.identifier()],
+            CompiledTriggerConditionKind::EqAny { events, .. } => {
+                CompiledTriggerConditionKind::distinct_identifiers(events)
+            }
+            CompiledTriggerConditionKind::HoldBetween {
+                low,
+                tick_identifier,
+                ..
+            } => vec![low.identifier(), tick_identifier.clone()],
+            CompiledTriggerConditionKind::StrictSequence { identifiers, .. } => identifiers.clone(),
+            _ => Vec::new(),
+        };
+        for condition in self.kind.sub_conditions() {
+            identifiers.extend(condition.leaf_identifiers());
+        }
+        identifiers
+    }
+
+    /// Returns the identifiers of the events this condition currently needs to make progress.
+    pub fn subscriptions(&self) -> Vec<Event::Identifier> {
+        if self.completed || self.failed {
+            return Default::default();
+        }
+
+        match &self.kind {
+            CompiledTriggerConditionKind::None
+            | CompiledTriggerConditionKind::Never
+            | CompiledTriggerConditionKind::StaleFor { .. } => Default::default(),
+            CompiledTriggerConditionKind::EventCount { identifier, .. }
+            | CompiledTriggerConditionKind::CountWithin { identifier, .. }
+            | CompiledTriggerConditionKind::DeltaGeq { identifier, .. }
+            | CompiledTriggerConditionKind::Accumulate { identifier, .. } => {
+                vec![identifier.clone()]
+            }
+            CompiledTriggerConditionKind::Predicate { identifier, .. } => {
+                identifier.iter().cloned().collect()
+            }
+            CompiledTriggerConditionKind::DecayingCount {
+                identifier,
+                penalty_identifier,
+                ..
+            } => vec![identifier.clone(), penalty_identifier.clone()],
+            CompiledTriggerConditionKind::Geq { event, .. }
+            | CompiledTriggerConditionKind::Neq { event, .. }
+            | CompiledTriggerConditionKind::Between { low: event, .. } => vec![event.identifier()],
+            CompiledTriggerConditionKind::EqAny { events, .. } => {
+                CompiledTriggerConditionKind::distinct_identifiers(events)
+            }
+            CompiledTriggerConditionKind::HoldBetween {
+                low,
+                tick_identifier,
+                ..
+            } => vec![low.identifier(), tick_identifier.clone()],
+            CompiledTriggerConditionKind::Sequence {
+                current_index,
+                conditions,
+            } => conditions[*current_index].subscriptions(),
+            CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                identifiers,
+            } => identifiers
+                .iter()
+                .cloned()
+                .chain(conditions[*current_index].subscriptions())
+                .collect(),
+            CompiledTriggerConditionKind::Pipeline { conditions, .. } => conditions
+                .iter()
+                .flat_map(|condition| condition.subscriptions())
+                .collect(),
             CompiledTriggerConditionKind::And { conditions, .. } => conditions
                 .iter()
                 .flat_map(|condition| condition.subscriptions())
                 .collect(),
-            CompiledTriggerConditionKind::Or { conditions, .. } => conditions
+            CompiledTriggerConditionKind::Or { conditions, .. }
+            | CompiledTriggerConditionKind::PrunedOr { conditions, .. } => conditions
                 .iter()
                 .flat_map(|condition| condition.subscriptions())
                 .collect(),
@@ -224,79 +2035,659 @@ impl<Event: TriggerEvent> CompiledTriggerCondition<Event> {
                 .iter()
                 .flat_map(|condition| condition.subscriptions())
                 .collect(),
+            CompiledTriggerConditionKind::Not { condition, .. } => condition.subscriptions(),
+            CompiledTriggerConditionKind::Retry {
+                condition,
+                failure_identifier,
+                ..
+            } => {
+                let mut subscriptions = condition.subscriptions();
+                subscriptions.push(failure_identifier.clone());
+                subscriptions
+            }
+            CompiledTriggerConditionKind::ExactlyN { conditions, .. } => conditions
+                .iter()
+                .flat_map(|condition| condition.subscriptions())
+                .collect(),
+        }
+    }
+}
+
+impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
+    fn required_progress(&self) -> f64 {
+        match self {
+            CompiledTriggerConditionKind::None => 0.0,
+            CompiledTriggerConditionKind::Never => 1.0,
+            CompiledTriggerConditionKind::EventCount { required, .. }
+            | CompiledTriggerConditionKind::DecayingCount { required, .. }
+            | CompiledTriggerConditionKind::HoldBetween { required, .. }
+            | CompiledTriggerConditionKind::CountWithin { required, .. }
+            | CompiledTriggerConditionKind::StaleFor { required, .. } => *required as f64,
+            CompiledTriggerConditionKind::Accumulate { target, .. } => target.max(0.0),
+            CompiledTriggerConditionKind::Geq { .. }
+            | CompiledTriggerConditionKind::Neq { .. }
+            | CompiledTriggerConditionKind::EqAny { .. }
+            | CompiledTriggerConditionKind::Predicate { .. }
+            | CompiledTriggerConditionKind::DeltaGeq { .. }
+            | CompiledTriggerConditionKind::Between { .. } => 1.0,
+            CompiledTriggerConditionKind::Sequence { conditions, .. }
+            | CompiledTriggerConditionKind::StrictSequence { conditions, .. }
+            | CompiledTriggerConditionKind::Pipeline { conditions, .. } => conditions
+                .iter()
+                .map(|condition| condition.required_progress())
+                .sum(),
+            CompiledTriggerConditionKind::And {
+                conditions,
+                fulfilled_conditions,
+            } => conditions
+                .iter()
+                .chain(fulfilled_conditions.iter())
+                .map(|condition| condition.required_progress())
+                .sum(),
+            CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => conditions
+                .iter()
+                .chain(fulfilled_conditions.iter())
+                .map(|condition| condition.required_progress())
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(0.0),
+            // Pruned conditions are included, such that the required progress does not change when pruning.
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                pruned_conditions,
+                ..
+            } => conditions
+                .iter()
+                .chain(fulfilled_conditions.iter())
+                .chain(pruned_conditions.iter())
+                .map(|condition| condition.required_progress())
+                .min_by(|a, b| a.partial_cmp(b).unwrap())
+                .unwrap_or(0.0),
+            CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                n,
+            }
+            | CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                n,
+                ..
+            } => {
+                let mut required_progresses: Vec<_> = conditions
+                    .iter()
+                    .chain(fulfilled_conditions.iter())
+                    .map(|condition| condition.required_progress())
+                    .collect();
+                required_progresses.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+                required_progresses.iter().take(*n).sum()
+            }
+            CompiledTriggerConditionKind::Not { window, .. } => *window as f64,
+            CompiledTriggerConditionKind::Retry { condition, .. } => condition.required_progress(),
+        }
+    }
+
+    fn completed(&self) -> bool {
+        match self {
+            CompiledTriggerConditionKind::None => true,
+            CompiledTriggerConditionKind::Never => false,
+            CompiledTriggerConditionKind::EventCount {
+                count, required, ..
+            }
+            | CompiledTriggerConditionKind::DecayingCount {
+                count, required, ..
+            }
+            | CompiledTriggerConditionKind::HoldBetween {
+                count, required, ..
+            } => count >= required,
+            CompiledTriggerConditionKind::Accumulate { sum, target, .. } => *sum >= *target,
+            CompiledTriggerConditionKind::CountWithin {
+                positions,
+                required,
+                ..
+            } => positions.len() >= *required,
+            CompiledTriggerConditionKind::Geq { fulfilled, .. }
+            | CompiledTriggerConditionKind::Neq { fulfilled, .. }
+            | CompiledTriggerConditionKind::EqAny { fulfilled, .. }
+            | CompiledTriggerConditionKind::Predicate { fulfilled, .. }
+            | CompiledTriggerConditionKind::DeltaGeq { fulfilled, .. }
+            | CompiledTriggerConditionKind::StaleFor { fulfilled, .. }
+            | CompiledTriggerConditionKind::Between { fulfilled, .. } => *fulfilled,
+            CompiledTriggerConditionKind::Sequence {
+                current_index,
+                conditions,
+            }
+            | CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::Pipeline {
+                current_index,
+                conditions,
+            } => *current_index >= conditions.len(),
+            CompiledTriggerConditionKind::And { conditions, .. } => conditions.is_empty(),
+            CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => !fulfilled_conditions.is_empty() || conditions.is_empty(),
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                pruned_conditions,
+                ..
+            } => {
+                !fulfilled_conditions.is_empty()
+                    || (conditions.is_empty() && pruned_conditions.is_empty())
+            }
+            CompiledTriggerConditionKind::AnyN {
+                fulfilled_conditions,
+                n,
+                ..
+            } => fulfilled_conditions.len() >= *n,
+            CompiledTriggerConditionKind::Not {
+                window,
+                events_seen,
+                failed,
+                ..
+            } => !*failed && events_seen >= window,
+            CompiledTriggerConditionKind::Retry {
+                condition, failed, ..
+            } => !*failed && condition.completed(),
+            CompiledTriggerConditionKind::ExactlyN {
+                fulfilled_conditions,
+                n,
+                failed,
+                ..
+            } => !*failed && fulfilled_conditions.len() == *n,
+        }
+    }
+
+    fn reset(&mut self) {
+        // Moves all fulfilled conditions back, resets everything, and moves conditions that are completed from the start back again.
+        fn reset_partitioned<Event: TriggerEvent>(
+            conditions: &mut Vec<CompiledTriggerCondition<Event>>,
+            fulfilled_conditions: &mut Vec<CompiledTriggerCondition<Event>>,
+        ) {
+            conditions.append(fulfilled_conditions);
+            let mut i = 0;
+            while i < conditions.len() {
+                conditions[i].reset();
+                if conditions[i].completed() {
+                    fulfilled_conditions.push(conditions.remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        match self {
+            CompiledTriggerConditionKind::None | CompiledTriggerConditionKind::Never => {}
+            CompiledTriggerConditionKind::EventCount { count, .. }
+            | CompiledTriggerConditionKind::DecayingCount { count, .. } => *count = 0,
+            CompiledTriggerConditionKind::Accumulate { sum, .. } => *sum = 0.0,
+            CompiledTriggerConditionKind::DeltaGeq {
+                previous,
+                fulfilled,
+                ..
+            } => {
+                *previous = None;
+                *fulfilled = false;
+            }
+            CompiledTriggerConditionKind::CountWithin {
+                events_seen,
+                positions,
+                ..
+            } => {
+                *events_seen = 0;
+                positions.clear();
+            }
+            CompiledTriggerConditionKind::StaleFor {
+                received,
+                fulfilled,
+                ..
+            } => {
+                *received = 0;
+                *fulfilled = false;
+            }
+            CompiledTriggerConditionKind::Neq { fulfilled, .. }
+            | CompiledTriggerConditionKind::EqAny { fulfilled, .. }
+            | CompiledTriggerConditionKind::Predicate { fulfilled, .. } => *fulfilled = false,
+            CompiledTriggerConditionKind::Geq {
+                fulfilled,
+                progress,
+                ..
+            }
+            | CompiledTriggerConditionKind::Between {
+                fulfilled,
+                progress,
+                ..
+            } => {
+                *fulfilled = false;
+                *progress = 0.0;
+            }
+            CompiledTriggerConditionKind::HoldBetween {
+                in_range, count, ..
+            } => {
+                *in_range = false;
+                *count = 0;
+            }
+            CompiledTriggerConditionKind::Sequence {
+                current_index,
+                conditions,
+            }
+            | CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::Pipeline {
+                current_index,
+                conditions,
+            } => {
+                conditions
+                    .iter_mut()
+                    .for_each(|condition| condition.reset());
+                *current_index = conditions
+                    .iter()
+                    .take_while(|condition| condition.completed())
+                    .count();
+            }
+            CompiledTriggerConditionKind::And {
+                conditions,
+                fulfilled_conditions,
+            }
+            | CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => reset_partitioned(conditions, fulfilled_conditions),
+            CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                priorities,
+            } => {
+                // Appending keeps the priorities aligned, since they are ordered like the appended conditions.
+                conditions.append(fulfilled_conditions);
+                conditions
+                    .iter_mut()
+                    .for_each(|condition| condition.reset());
+                Self::partition_or(conditions, fulfilled_conditions, priorities);
+            }
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                pruned_conditions,
+                max_lag,
+            } => {
+                conditions.append(pruned_conditions);
+                reset_partitioned(conditions, fulfilled_conditions);
+                if fulfilled_conditions.is_empty() {
+                    Self::prune(conditions, pruned_conditions, *max_lag);
+                }
+            }
+            CompiledTriggerConditionKind::Not {
+                condition,
+                events_seen,
+                failed,
+                ..
+            } => {
+                condition.reset();
+                *events_seen = 0;
+                *failed = condition.completed();
+            }
+            CompiledTriggerConditionKind::Retry {
+                condition,
+                retries,
+                failed,
+                ..
+            } => {
+                condition.reset();
+                *retries = 0;
+                *failed = false;
+            }
+            CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                n,
+                failed,
+            } => {
+                reset_partitioned(conditions, fulfilled_conditions);
+                *failed = fulfilled_conditions.len() > *n;
+            }
+        }
+    }
+
+    /// Returns true if the condition can never complete anymore.
+    /// Relies on the cached `failed` flags of the sub-conditions.
+    fn failed(&self) -> bool {
+        match self {
+            CompiledTriggerConditionKind::None
+            | CompiledTriggerConditionKind::Never
+            | CompiledTriggerConditionKind::EventCount { .. }
+            | CompiledTriggerConditionKind::DecayingCount { .. }
+            | CompiledTriggerConditionKind::Geq { .. }
+            | CompiledTriggerConditionKind::Neq { .. }
+            | CompiledTriggerConditionKind::EqAny { .. }
+            | CompiledTriggerConditionKind::Predicate { .. }
+            | CompiledTriggerConditionKind::Accumulate { .. }
+            | CompiledTriggerConditionKind::DeltaGeq { .. }
+            | CompiledTriggerConditionKind::CountWithin { .. }
+            | CompiledTriggerConditionKind::StaleFor { .. }
+            | CompiledTriggerConditionKind::Between { .. }
+            | CompiledTriggerConditionKind::HoldBetween { .. } => false,
+            CompiledTriggerConditionKind::Sequence {
+                current_index,
+                conditions,
+            }
+            | CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                ..
+            } => conditions
+                .get(*current_index)
+                .map(|condition| condition.failed())
+                .unwrap_or(false),
+            CompiledTriggerConditionKind::Pipeline { conditions, .. } => {
+                conditions.iter().any(|condition| condition.failed())
+            }
+            CompiledTriggerConditionKind::And { conditions, .. } => {
+                conditions.iter().any(|condition| condition.failed())
+            }
+            CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => {
+                fulfilled_conditions.is_empty()
+                    && !conditions.is_empty()
+                    && conditions.iter().all(|condition| condition.failed())
+            }
+            // Pruned conditions are only evaluated again after a reset, so they cannot prevent failure.
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => {
+                fulfilled_conditions.is_empty()
+                    && !conditions.is_empty()
+                    && conditions.iter().all(|condition| condition.failed())
+            }
+            CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                n,
+            } => {
+                fulfilled_conditions.len()
+                    + conditions
+                        .iter()
+                        .filter(|condition| !condition.failed())
+                        .count()
+                    < *n
+            }
+            CompiledTriggerConditionKind::Not { failed, .. } => *failed,
+            CompiledTriggerConditionKind::Retry {
+                condition, failed, ..
+            } => *failed || condition.failed(),
+            CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                n,
+                failed,
+            } => {
+                *failed
+                    || fulfilled_conditions.len()
+                        + conditions
+                            .iter()
+                            .filter(|condition| !condition.failed())
+                            .count()
+                        < *n
+            }
+        }
+    }
+
+    /// Moves the completed sub-conditions of an `or` condition to `fulfilled_conditions`, keeping `priorities` aligned.
+    /// If the sub-conditions have priorities, the moved ones are ordered by descending priority, such that the first fulfilled condition wins.
+    /// Sub-conditions with equal priority keep their order.
+    /// Moves completed sub-conditions to the fulfilled sub-conditions, and advances sequences past completed steps.
+    fn settle(&mut self) {
+        match self {
+            CompiledTriggerConditionKind::Sequence {
+                current_index,
+                conditions,
+            }
+            | CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::Pipeline {
+                current_index,
+                conditions,
+            } => {
+                while conditions
+                    .get(*current_index)
+                    .map_or(false, |condition| condition.completed())
+                {
+                    *current_index += 1;
+                }
+            }
+            CompiledTriggerConditionKind::And {
+                conditions,
+                fulfilled_conditions,
+            }
+            | CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => {
+                let (completed, remaining) = std::mem::take(conditions)
+                    .into_iter()
+                    .partition(|condition| condition.completed());
+                *conditions = remaining;
+                fulfilled_conditions.extend::<Vec<_>>(completed);
+            }
+            CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                priorities,
+            } => Self::partition_or(conditions, fulfilled_conditions, priorities),
+            _ => {}
         }
     }
-}
 
-impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
-    fn required_progress(&self) -> f64 {
+    fn partition_or(
+        conditions: &mut Vec<CompiledTriggerCondition<Event>>,
+        fulfilled_conditions: &mut Vec<CompiledTriggerCondition<Event>>,
+        priorities: &mut Vec<i32>,
+    ) {
+        let first_fulfilled = fulfilled_conditions.len();
+        // TODO replace with drain_filter once stable
+        let mut i = 0;
+        while i < conditions.len() {
+            if conditions[i].completed() {
+                fulfilled_conditions.push(conditions.remove(i));
+                if !priorities.is_empty() {
+                    let priority = priorities.remove(i);
+                    priorities.push(priority);
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        if !priorities.is_empty() {
+            let mut fulfilled: Vec<_> = priorities
+                .drain(conditions.len() + first_fulfilled..)
+                .zip(fulfilled_conditions.drain(first_fulfilled..))
+                .collect();
+            fulfilled.sort_by_key(|(priority, _)| Reverse(*priority));
+            for (priority, condition) in fulfilled {
+                priorities.push(priority);
+                fulfilled_conditions.push(condition);
+            }
+        }
+    }
+
+    /// Returns all direct sub-conditions, including completed and pruned ones.
+    fn sub_conditions(&self) -> Vec<&CompiledTriggerCondition<Event>> {
         match self {
-            CompiledTriggerConditionKind::None => 0.0,
-            CompiledTriggerConditionKind::Never => 1.0,
-            CompiledTriggerConditionKind::EventCount { required, .. } => *required as f64,
-            CompiledTriggerConditionKind::Geq { .. } => 1.0,
-            CompiledTriggerConditionKind::Sequence { conditions, .. } => conditions
-                .iter()
-                .map(|condition| condition.required_progress())
-                .sum(),
+            CompiledTriggerConditionKind::Sequence { conditions, .. }
+            | CompiledTriggerConditionKind::StrictSequence { conditions, .. }
+            | CompiledTriggerConditionKind::Pipeline { conditions, .. } => {
+                conditions.iter().collect()
+            }
             CompiledTriggerConditionKind::And {
                 conditions,
                 fulfilled_conditions,
+            }
+            | CompiledTriggerConditionKind::Or {
+                conditions,
+                fulfilled_conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                ..
             } => conditions
                 .iter()
                 .chain(fulfilled_conditions.iter())
-                .map(|condition| condition.required_progress())
-                .sum(),
-            CompiledTriggerConditionKind::Or {
+                .collect(),
+            CompiledTriggerConditionKind::PrunedOr {
                 conditions,
                 fulfilled_conditions,
+                pruned_conditions,
+                ..
             } => conditions
                 .iter()
                 .chain(fulfilled_conditions.iter())
-                .map(|condition| condition.required_progress())
-                .min_by(|a, b| a.partial_cmp(b).unwrap())
-                .unwrap_or(0.0),
-            CompiledTriggerConditionKind::AnyN {
-                conditions,
-                fulfilled_conditions,
-                n,
-            } => {
-                let mut required_progresses: Vec<_> = conditions
-                    .iter()
-                    .chain(fulfilled_conditions.iter())
-                    .map(|condition| condition.required_progress())
-                    .collect();
-                required_progresses.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
-                required_progresses.iter().take(*n).sum()
-            }
+                .chain(pruned_conditions.iter())
+                .collect(),
+            CompiledTriggerConditionKind::Not { condition, .. }
+            | CompiledTriggerConditionKind::Retry { condition, .. } => vec![condition.as_ref()],
+            _ => Vec::new(),
         }
     }
 
-    fn completed(&self) -> bool {
+    /// Returns all direct sub-conditions mutably, see [`Self::sub_conditions`].
+    fn sub_conditions_mut(&mut self) -> Vec<&mut CompiledTriggerCondition<Event>> {
         match self {
-            CompiledTriggerConditionKind::None => true,
-            CompiledTriggerConditionKind::Never => false,
-            CompiledTriggerConditionKind::EventCount {
-                count, required, ..
-            } => count >= required,
-            CompiledTriggerConditionKind::Geq { fulfilled, .. } => *fulfilled,
-            CompiledTriggerConditionKind::Sequence {
-                current_index,
+            CompiledTriggerConditionKind::Sequence { conditions, .. }
+            | CompiledTriggerConditionKind::StrictSequence { conditions, .. }
+            | CompiledTriggerConditionKind::Pipeline { conditions, .. } => {
+                conditions.iter_mut().collect()
+            }
+            CompiledTriggerConditionKind::And {
+                conditions,
+                fulfilled_conditions,
+            }
+            | CompiledTriggerConditionKind::Or {
                 conditions,
-            } => *current_index >= conditions.len(),
-            CompiledTriggerConditionKind::And { conditions, .. } => conditions.is_empty(),
-            CompiledTriggerConditionKind::Or { conditions, .. } => conditions.is_empty(),
-            CompiledTriggerConditionKind::AnyN {
                 fulfilled_conditions,
-                n,
                 ..
-            } => fulfilled_conditions.len() >= *n,
+            }
+            | CompiledTriggerConditionKind::AnyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            }
+            | CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                ..
+            } => conditions
+                .iter_mut()
+                .chain(fulfilled_conditions.iter_mut())
+                .collect(),
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                pruned_conditions,
+                ..
+            } => conditions
+                .iter_mut()
+                .chain(fulfilled_conditions.iter_mut())
+                .chain(pruned_conditions.iter_mut())
+                .collect(),
+            CompiledTriggerConditionKind::Not { condition, .. }
+            | CompiledTriggerConditionKind::Retry { condition, .. } => vec![condition.as_mut()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the identifiers of the given events, without duplicates.
+    fn distinct_identifiers(events: &[Event]) -> Vec<Event::Identifier> {
+        let mut identifiers: Vec<_> = events.iter().map(|event| event.identifier()).collect();
+        identifiers.sort();
+        identifiers.dedup();
+        identifiers
+    }
+
+    /// Moves the conditions whose remaining progress exceeds the smallest remaining progress by more than `max_lag` to `pruned_conditions`.
+    /// Failed conditions are ignored. Returns the updates that unsubscribe the pruned conditions.
+    fn prune(
+        conditions: &mut Vec<CompiledTriggerCondition<Event>>,
+        pruned_conditions: &mut Vec<CompiledTriggerCondition<Event>>,
+        max_lag: usize,
+    ) -> Vec<TriggerConditionUpdate<Event::Identifier>> {
+        let remaining = |condition: &CompiledTriggerCondition<Event>| {
+            condition.required_progress() - condition.current_progress()
+        };
+        let min_remaining = if let Some(min_remaining) = conditions
+            .iter()
+            .filter(|condition| !condition.failed())
+            .map(remaining)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+        {
+            min_remaining
+        } else {
+            return Default::default();
+        };
+
+        let mut trigger_condition_updates = Vec::new();
+        let mut i = 0;
+        while i < conditions.len() {
+            if !conditions[i].failed() && remaining(&conditions[i]) > min_remaining + max_lag as f64
+            {
+                trigger_condition_updates.extend(
+                    conditions[i]
+                        .subscriptions()
+                        .into_iter()
+                        .map(TriggerConditionUpdate::Unsubscribe),
+                );
+                pruned_conditions.push(conditions.remove(i));
+            } else {
+                i += 1;
+            }
         }
+        trigger_condition_updates
     }
 
     fn execute_event(
         &mut self,
         event: &Event,
+        context: Option<EventContext<'_>>,
     ) -> (Vec<TriggerConditionUpdate<Event::Identifier>>, bool, f64) {
         match self {
             CompiledTriggerConditionKind::None => (Default::default(), true, 0.0),
@@ -308,43 +2699,331 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
             } => {
                 assert!(count < required);
                 let identifier = event.identifier();
-                if *counted_identifier == identifier {
+                if *counted_identifier == identifier {
+                    *count += 1;
+                }
+
+                assert!(count <= required);
+                if count == required {
+                    (
+                        vec![TriggerConditionUpdate::Unsubscribe(
+                            counted_identifier.clone(),
+                        )],
+                        true,
+                        *count as f64,
+                    )
+                } else {
+                    (Default::default(), count >= required, *count as f64)
+                }
+            }
+            CompiledTriggerConditionKind::DecayingCount {
+                identifier: counted_identifier,
+                penalty_identifier,
+                count,
+                required,
+            } => {
+                assert!(count < required);
+                let identifier = event.identifier();
+                if *counted_identifier == identifier {
+                    *count += 1;
+                } else if *penalty_identifier == identifier {
+                    *count = count.saturating_sub(1);
+                }
+
+                if count == required {
+                    (
+                        vec![
+                            TriggerConditionUpdate::Unsubscribe(counted_identifier.clone()),
+                            TriggerConditionUpdate::Unsubscribe(penalty_identifier.clone()),
+                        ],
+                        true,
+                        *count as f64,
+                    )
+                } else {
+                    (Default::default(), false, *count as f64)
+                }
+            }
+            CompiledTriggerConditionKind::Geq {
+                event: reference_event,
+                fulfilled,
+                progress,
+            } => {
+                assert!(!*fulfilled);
+                // Events that cannot be compared to the reference event are ignored, even if they have the same identifier.
+                match event.value_geq(reference_event) {
+                    Some(true) => {
+                        *fulfilled = true;
+                        return (
+                            vec![TriggerConditionUpdate::Unsubscribe(
+                                reference_event.identifier(),
+                            )],
+                            true,
+                            1.0,
+                        );
+                    }
+                    Some(false) => {
+                        if let Some(event_progress) = event.value_geq_progress(reference_event) {
+                            *progress = event_progress;
+                        }
+                    }
+                    None => {}
+                }
+                (vec![], false, *progress)
+            }
+            CompiledTriggerConditionKind::Neq {
+                event: reference_event,
+                fulfilled,
+            } => {
+                assert!(!*fulfilled);
+                // Events that cannot be compared to the reference event are ignored.
+                // There is no meaningful partial progress towards inequality, so the progress stays zero until fulfilled.
+                if let (Some(geq), Some(leq)) = (
+                    event.value_geq(reference_event),
+                    reference_event.value_geq(event),
+                ) {
+                    if !(geq && leq) {
+                        *fulfilled = true;
+                        return (
+                            vec![TriggerConditionUpdate::Unsubscribe(
+                                reference_event.identifier(),
+                            )],
+                            true,
+                            1.0,
+                        );
+                    }
+                }
+                (vec![], false, 0.0)
+            }
+            CompiledTriggerConditionKind::Predicate {
+                identifier,
+                predicate,
+                fulfilled,
+                ..
+            } => {
+                assert!(!*fulfilled);
+                if identifier
+                    .as_ref()
+                    .map_or(true, |identifier| event.identifier() == *identifier)
+                    && predicate
+                        .as_ref()
+                        .map_or(false, |predicate| predicate.evaluate(event, context))
+                {
+                    *fulfilled = true;
+                    return (
+                        identifier
+                            .iter()
+                            .cloned()
+                            .map(TriggerConditionUpdate::Unsubscribe)
+                            .collect(),
+                        true,
+                        1.0,
+                    );
+                }
+                (vec![], false, 0.0)
+            }
+            CompiledTriggerConditionKind::CountWithin {
+                identifier,
+                required,
+                window,
+                events_seen,
+                positions,
+            } => {
+                assert!(positions.len() < *required);
+                *events_seen = context.map_or(*events_seen + 1, |context| context.index);
+                if event.identifier() == *identifier {
+                    positions.push_back(*events_seen);
+                }
+                while positions
+                    .front()
+                    .map_or(false, |position| position + *window <= *events_seen)
+                {
+                    positions.pop_front();
+                }
+
+                if positions.len() >= *required {
+                    (
+                        vec![TriggerConditionUpdate::Unsubscribe(identifier.clone())],
+                        true,
+                        *required as f64,
+                    )
+                } else {
+                    (vec![], false, positions.len() as f64)
+                }
+            }
+            CompiledTriggerConditionKind::StaleFor {
+                target,
+                required,
+                received,
+                fulfilled,
+                ..
+            } => {
+                assert!(!*fulfilled);
+                // The received event itself is not idle yet, since it may still advance the target trigger.
+                let idle = match (context, *target) {
+                    (Some(context), Some(target)) => {
+                        let last_advance = context.last_advances.get(target).copied().unwrap_or(0);
+                        context.index.saturating_sub(last_advance + 1)
+                    }
+                    // In a trigger system, no trigger has the trigger id, so it can never be idle.
+                    (Some(_), None) => return (vec![], false, 0.0),
+                    (None, _) => *received,
+                };
+                *received += 1;
+                if idle >= *required {
+                    *fulfilled = true;
+                    (vec![], true, *required as f64)
+                } else {
+                    (vec![], false, idle as f64)
+                }
+            }
+            CompiledTriggerConditionKind::Accumulate {
+                identifier,
+                extractor,
+                sum,
+                target,
+                ..
+            } => {
+                assert!(*sum < *target);
+                if event.identifier() == *identifier {
+                    if let Some(extractor) = extractor {
+                        *sum = (*sum + extractor.evaluate(event, context)).max(0.0);
+                    }
+                }
+
+                if *sum >= *target {
+                    (
+                        vec![TriggerConditionUpdate::Unsubscribe(identifier.clone())],
+                        true,
+                        *target,
+                    )
+                } else {
+                    (vec![], false, *sum)
+                }
+            }
+            CompiledTriggerConditionKind::DeltaGeq {
+                identifier,
+                extractor,
+                previous,
+                threshold,
+                fulfilled,
+                ..
+            } => {
+                assert!(!*fulfilled);
+                let extractor = match extractor {
+                    Some(extractor) if event.identifier() == *identifier => extractor,
+                    _ => return (vec![], false, 0.0),
+                };
+                let value = extractor.evaluate(event, context);
+                // The baseline moves with each event, so only the change from one event to the next counts.
+                match previous.replace(value).map(|previous| value - previous) {
+                    Some(delta) if delta >= *threshold => {
+                        *fulfilled = true;
+                        (
+                            vec![TriggerConditionUpdate::Unsubscribe(identifier.clone())],
+                            true,
+                            1.0,
+                        )
+                    }
+                    delta => (
+                        vec![],
+                        false,
+                        match delta {
+                            Some(delta) if *threshold > 0.0 => (delta / *threshold).clamp(0.0, 1.0),
+                            _ => 0.0,
+                        },
+                    ),
+                }
+            }
+            CompiledTriggerConditionKind::EqAny {
+                events: reference_events,
+                fulfilled,
+            } => {
+                assert!(!*fulfilled);
+                // Only reference events with the same identifier are compared, and events that cannot be compared are ignored.
+                let identifier = event.identifier();
+                let equal = reference_events.iter().any(|reference_event| {
+                    reference_event.identifier() == identifier
+                        && event.value_geq(reference_event) == Some(true)
+                        && reference_event.value_geq(event) == Some(true)
+                });
+                if equal {
+                    *fulfilled = true;
+                    return (
+                        Self::distinct_identifiers(reference_events)
+                            .into_iter()
+                            .map(TriggerConditionUpdate::Unsubscribe)
+                            .collect(),
+                        true,
+                        1.0,
+                    );
+                }
+                (vec![], false, 0.0)
+            }
+            CompiledTriggerConditionKind::Between {
+                low,
+                high,
+                low_bound,
+                high_bound,
+                fulfilled,
+                progress,
+            } => {
+                assert!(!*fulfilled);
+                // Events that cannot be compared to the bounds are ignored.
+                if let (Some(above_low), Some(below_high)) =
+                    (low_bound.below(low, event), high_bound.below(event, high))
+                {
+                    if above_low && below_high {
+                        *fulfilled = true;
+                        *progress = 1.0;
+                        return (
+                            vec![TriggerConditionUpdate::Unsubscribe(low.identifier())],
+                            true,
+                            1.0,
+                        );
+                    }
+                    // An event equal to an exclusive lower bound does not count as progress, since it would be complete progress.
+                    if event.value_geq(low) == Some(false) {
+                        *progress = progress.max(event.value_geq_progress(low).unwrap_or(0.0));
+                    }
+                }
+                (vec![], false, *progress)
+            }
+            CompiledTriggerConditionKind::HoldBetween {
+                low,
+                high,
+                tick_identifier,
+                in_range,
+                count,
+                required,
+            } => {
+                assert!(count < required);
+                let identifier = event.identifier();
+                if identifier == low.identifier() {
+                    // Events that cannot be compared to the bounds are ignored.
+                    if let (Some(above_low), Some(below_high)) =
+                        (event.value_geq(low), high.value_geq(event))
+                    {
+                        *in_range = above_low && below_high;
+                        if !*in_range {
+                            *count = 0;
+                        }
+                    }
+                } else if identifier == *tick_identifier && *in_range {
                     *count += 1;
                 }
 
-                assert!(count <= required);
                 if count == required {
                     (
-                        vec![TriggerConditionUpdate::Unsubscribe(
-                            counted_identifier.clone(),
-                        )],
+                        vec![
+                            TriggerConditionUpdate::Unsubscribe(low.identifier()),
+                            TriggerConditionUpdate::Unsubscribe(tick_identifier.clone()),
+                        ],
                         true,
                         *count as f64,
                     )
                 } else {
-                    (Default::default(), count >= required, *count as f64)
-                }
-            }
-            CompiledTriggerConditionKind::Geq {
-                event: reference_event,
-                fulfilled,
-            } => {
-                assert!(!*fulfilled);
-                if event.value_geq(reference_event).unwrap() {
-                    *fulfilled = true;
-                    return (
-                        vec![TriggerConditionUpdate::Unsubscribe(
-                            reference_event.identifier(),
-                        )],
-                        true,
-                        1.0,
-                    );
+                    (Default::default(), false, *count as f64)
                 }
-                (
-                    vec![],
-                    false,
-                    event.value_geq_progress(reference_event).unwrap(),
-                )
             }
             CompiledTriggerConditionKind::Sequence {
                 current_index,
@@ -357,7 +3036,7 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
                     .map(|condition| condition.required_progress())
                     .sum();
                 let (mut trigger_condition_update, result, current_progress) =
-                    conditions[*current_index].execute_event(event);
+                    conditions[*current_index].execute_event_in(event, context);
                 if result {
                     let progress_base =
                         progress_base + conditions[*current_index].required_progress();
@@ -386,6 +3065,109 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
                     )
                 }
             }
+            CompiledTriggerConditionKind::StrictSequence {
+                current_index,
+                conditions,
+                identifiers,
+            } => {
+                assert!(*current_index < conditions.len());
+                // The subscriptions of the current condition are replaced as a whole, since a restart may change them arbitrarily.
+                let mut trigger_condition_updates: Vec<_> = conditions[*current_index]
+                    .subscriptions()
+                    .into_iter()
+                    .map(TriggerConditionUpdate::Unsubscribe)
+                    .collect();
+                let receives = |condition: &CompiledTriggerCondition<Event>| {
+                    condition.subscribes_to_all()
+                        || condition.subscriptions().contains(&event.identifier())
+                };
+
+                let condition = &mut conditions[*current_index];
+                let advanced = receives(condition) && {
+                    let progress_before = condition.current_progress();
+                    let (_, result, current_progress) = condition.execute_event_in(event, context);
+                    result || current_progress > progress_before
+                };
+                if !advanced {
+                    conditions
+                        .iter_mut()
+                        .for_each(|condition| condition.reset());
+                    *current_index = conditions
+                        .iter()
+                        .take_while(|condition| condition.completed())
+                        .count();
+                    // The event that interrupted the sequence may start the next attempt.
+                    if let Some(condition) = conditions.get_mut(*current_index) {
+                        if receives(condition) {
+                            condition.execute_event_in(event, context);
+                        }
+                    }
+                }
+                while conditions
+                    .get(*current_index)
+                    .map_or(false, |condition| condition.completed())
+                {
+                    *current_index += 1;
+                }
+
+                let progress_base: f64 = conditions
+                    .iter()
+                    .take(*current_index)
+                    .map(|condition| condition.required_progress())
+                    .sum();
+                if let Some(condition) = conditions.get(*current_index) {
+                    trigger_condition_updates.extend(
+                        condition
+                            .subscriptions()
+                            .into_iter()
+                            .map(TriggerConditionUpdate::Subscribe),
+                    );
+                    (
+                        trigger_condition_updates,
+                        false,
+                        progress_base + condition.current_progress(),
+                    )
+                } else {
+                    trigger_condition_updates.extend(
+                        identifiers
+                            .iter()
+                            .cloned()
+                            .map(TriggerConditionUpdate::Unsubscribe),
+                    );
+                    (trigger_condition_updates, true, progress_base)
+                }
+            }
+            CompiledTriggerConditionKind::Pipeline {
+                current_index,
+                conditions,
+            } => {
+                assert!(*current_index < conditions.len());
+                let mut trigger_condition_updates = Vec::new();
+                for condition in conditions.iter_mut().skip(*current_index) {
+                    if !condition.completed() {
+                        let (mut local_trigger_condition_updates, _, _) =
+                            condition.execute_event_in(event, context);
+                        trigger_condition_updates.append(&mut local_trigger_condition_updates);
+                    }
+                }
+
+                let mut current_progress: f64 = conditions
+                    .iter()
+                    .take(*current_index)
+                    .map(|condition| condition.required_progress())
+                    .sum();
+                while *current_index < conditions.len() && conditions[*current_index].completed() {
+                    current_progress += conditions[*current_index].required_progress();
+                    *current_index += 1;
+                }
+
+                if *current_index < conditions.len() {
+                    current_progress += conditions[*current_index].current_progress();
+                    (trigger_condition_updates, false, current_progress)
+                } else {
+                    (trigger_condition_updates, true, current_progress)
+                }
+            }
             CompiledTriggerConditionKind::And {
                 conditions,
                 fulfilled_conditions,
@@ -401,7 +3183,7 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
                 let mut i = 0;
                 while i < conditions.len() {
                     let (mut local_trigger_condition_updates, result, progress) =
-                        conditions[i].execute_event(event);
+                        conditions[i].execute_event_in(event, context);
                     trigger_condition_updates.append(&mut local_trigger_condition_updates);
                     if result {
                         current_progress += conditions[i].required_progress();
@@ -420,23 +3202,61 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
             CompiledTriggerConditionKind::Or {
                 conditions,
                 fulfilled_conditions,
+                priorities,
+            } => {
+                assert!(fulfilled_conditions.is_empty());
+                let mut trigger_condition_updates = Vec::new();
+                let mut current_progress: f64 = 0.0;
+
+                for condition in conditions.iter_mut() {
+                    let (mut local_trigger_condition_updates, result, progress) =
+                        condition.execute_event_in(event, context);
+                    trigger_condition_updates.append(&mut local_trigger_condition_updates);
+                    current_progress = if result {
+                        1.0
+                    } else {
+                        current_progress.max(condition.relative_progress(progress))
+                    };
+                }
+                Self::partition_or(conditions, fulfilled_conditions, priorities);
+
+                let result = !fulfilled_conditions.is_empty();
+                if result {
+                    trigger_condition_updates.extend(conditions.iter().flat_map(|condition| {
+                        condition
+                            .subscriptions()
+                            .into_iter()
+                            .map(TriggerConditionUpdate::Unsubscribe)
+                    }));
+                }
+
+                (
+                    trigger_condition_updates,
+                    result,
+                    current_progress * self.required_progress(),
+                )
+            }
+            CompiledTriggerConditionKind::PrunedOr {
+                conditions,
+                fulfilled_conditions,
+                pruned_conditions,
+                max_lag,
             } => {
                 assert!(fulfilled_conditions.is_empty());
                 let mut trigger_condition_updates = Vec::new();
                 let mut current_progress: f64 = 0.0;
 
-                // TODO replace with drain_filter once stable
                 let mut i = 0;
                 while i < conditions.len() {
                     let (mut local_trigger_condition_updates, result, progress) =
-                        conditions[i].execute_event(event);
+                        conditions[i].execute_event_in(event, context);
                     trigger_condition_updates.append(&mut local_trigger_condition_updates);
                     if result {
                         current_progress = 1.0;
                         fulfilled_conditions.push(conditions.remove(i));
                     } else {
                         current_progress =
-                            current_progress.max(progress / conditions[i].required_progress());
+                            current_progress.max(conditions[i].relative_progress(progress));
                         i += 1;
                     }
                 }
@@ -449,6 +3269,12 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
                             .into_iter()
                             .map(TriggerConditionUpdate::Unsubscribe)
                     }));
+                } else {
+                    trigger_condition_updates.extend(Self::prune(
+                        conditions,
+                        pruned_conditions,
+                        *max_lag,
+                    ));
                 }
 
                 (
@@ -470,13 +3296,13 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
                 let mut i = 0;
                 while i < conditions.len() {
                     let (mut local_trigger_condition_updates, result, progress) =
-                        conditions[i].execute_event(event);
+                        conditions[i].execute_event_in(event, context);
                     trigger_condition_updates.append(&mut local_trigger_condition_updates);
                     if result {
                         relative_progresses.push(1.0);
                         fulfilled_conditions.push(conditions.remove(i));
                     } else {
-                        relative_progresses.push(progress / conditions[i].required_progress());
+                        relative_progresses.push(conditions[i].relative_progress(progress));
                         i += 1;
                     }
                 }
@@ -497,6 +3323,347 @@ impl<Event: TriggerEvent> CompiledTriggerConditionKind<Event> {
                     * self.required_progress();
                 (trigger_condition_updates, result, current_progress)
             }
+            CompiledTriggerConditionKind::Retry {
+                condition,
+                failure_identifier,
+                max_retries,
+                retries,
+                failed,
+            } => {
+                if event.identifier() != *failure_identifier {
+                    let (mut trigger_condition_updates, result, progress) =
+                        condition.execute_event_in(event, context);
+                    if result {
+                        trigger_condition_updates.push(TriggerConditionUpdate::Unsubscribe(
+                            failure_identifier.clone(),
+                        ));
+                    }
+                    return (trigger_condition_updates, result, progress);
+                }
+
+                *retries += 1;
+                if *retries > *max_retries {
+                    // The remaining subscriptions are removed when the failure is noticed by the surrounding `CompiledTriggerCondition`.
+                    *failed = true;
+                    return (Default::default(), false, condition.current_progress());
+                }
+
+                let mut trigger_condition_updates: Vec<_> = condition
+                    .subscriptions()
+                    .into_iter()
+                    .map(TriggerConditionUpdate::Unsubscribe)
+                    .collect();
+                condition.reset();
+                trigger_condition_updates.extend(
+                    condition
+                        .subscriptions()
+                        .into_iter()
+                        .map(TriggerConditionUpdate::Subscribe),
+                );
+                (
+                    trigger_condition_updates,
+                    false,
+                    condition.current_progress(),
+                )
+            }
+            CompiledTriggerConditionKind::Not {
+                condition,
+                window,
+                events_seen,
+                failed,
+            } => {
+                // Events that are not subscribed by the inner condition are not counted, e.g. if this is part of an `And`.
+                if !condition.subscribes_to_all()
+                    && !condition.subscriptions().contains(&event.identifier())
+                {
+                    return (Default::default(), false, *events_seen as f64);
+                }
+
+                let (mut trigger_condition_updates, result, _) =
+                    condition.execute_event_in(event, context);
+                if result {
+                    *failed = true;
+                    return (trigger_condition_updates, false, *events_seen as f64);
+                }
+
+                *events_seen += 1;
+                let result = events_seen >= window;
+                if result {
+                    trigger_condition_updates.extend(
+                        condition
+                            .subscriptions()
+                            .into_iter()
+                            .map(TriggerConditionUpdate::Unsubscribe),
+                    );
+                }
+                (trigger_condition_updates, result, *events_seen as f64)
+            }
+            CompiledTriggerConditionKind::ExactlyN {
+                conditions,
+                fulfilled_conditions,
+                n,
+                failed,
+            } => {
+                assert!(fulfilled_conditions.len() < *n);
+                let mut trigger_condition_updates = Vec::new();
+                let mut relative_progresses = vec![1.0; fulfilled_conditions.len()];
+
+                let mut i = 0;
+                while i < conditions.len() {
+                    let (mut local_trigger_condition_updates, result, progress) =
+                        conditions[i].execute_event_in(event, context);
+                    trigger_condition_updates.append(&mut local_trigger_condition_updates);
+                    if result {
+                        relative_progresses.push(1.0);
+                        fulfilled_conditions.push(conditions.remove(i));
+                    } else {
+                        relative_progresses.push(conditions[i].relative_progress(progress));
+                        i += 1;
+                    }
+                }
+
+                // Conditions completing on the same event are counted together, so if they exceed `n`, the condition fails.
+                // Events created from actions are separate events, but at that point this condition is either completed or failed already.
+                if fulfilled_conditions.len() > *n {
+                    // The remaining subscriptions are removed when the failure is noticed by the surrounding `CompiledTriggerCondition`.
+                    *failed = true;
+                    return (trigger_condition_updates, false, 0.0);
+                }
+
+                let result = fulfilled_conditions.len() == *n;
+                if result {
+                    trigger_condition_updates.extend(conditions.iter().flat_map(|condition| {
+                        condition
+                            .subscriptions()
+                            .into_iter()
+                            .map(TriggerConditionUpdate::Unsubscribe)
+                    }));
+                }
+
+                relative_progresses.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+                let current_progress = relative_progresses.iter().rev().take(*n).sum::<f64>()
+                    / (*n as f64)
+                    * self.required_progress();
+                (trigger_condition_updates, result, current_progress)
+            }
+        }
+    }
+}
+
+impl BoundKind {
+    /// Returns if the value of `lower` lies below the value of `upper`, or equals it if the bound is inclusive.
+    /// Returns `None` if the events cannot be compared.
+    fn below<Event: TriggerEvent>(self, lower: &Event, upper: &Event) -> Option<bool> {
+        match self {
+            BoundKind::Inclusive => upper.value_geq(lower),
+            BoundKind::Exclusive => lower.value_geq(upper).map(|geq| !geq),
+        }
+    }
+}
+
+impl Default for BoundKind {
+    fn default() -> Self {
+        BoundKind::Inclusive
+    }
+}
+
+impl<Event> EventPredicate<Event> {
+    pub fn new(predicate: impl Fn(&Event) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(predicate), None)
+    }
+
+    /// Shares the results of this predicate with all conditions using it, at the given index in [`SharedEvaluations`].
+    pub(crate) fn with_evaluation(mut self, evaluation: usize) -> Self {
+        self.1 = Some(evaluation);
+        self
+    }
+
+    pub(crate) fn evaluation(&self) -> Option<usize> {
+        self.1
+    }
+
+    /// Returns the result of the predicate for the event, evaluating it only if no other condition did so for this event already.
+    fn evaluate(&self, event: &Event, context: Option<EventContext<'_>>) -> bool {
+        let result = context
+            .zip(self.1)
+            .and_then(|(context, evaluation)| context.predicate_results.get(evaluation));
+        if let Some(value) = result.and_then(Cell::get) {
+            return value;
+        }
+        let value = (self.0)(event);
+        if let Some(result) = result {
+            result.set(Some(value));
+        }
+        value
+    }
+}
+
+impl<Event> Clone for EventPredicate<Event> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1)
+    }
+}
+
+impl<Event> Debug for EventPredicate<Event> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventPredicate")
+    }
+}
+
+impl<Event> EventExtractor<Event> {
+    pub fn new(extractor: impl Fn(&Event) -> f64 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(extractor), None)
+    }
+
+    /// Shares the results of this extractor with all conditions using it, at the given index in [`SharedEvaluations`].
+    pub(crate) fn with_evaluation(mut self, evaluation: usize) -> Self {
+        self.1 = Some(evaluation);
+        self
+    }
+
+    pub(crate) fn evaluation(&self) -> Option<usize> {
+        self.1
+    }
+
+    /// Like [`EventPredicate::evaluate`], returns the value of the event, extracting it only once per event.
+    fn evaluate(&self, event: &Event, context: Option<EventContext<'_>>) -> f64 {
+        let result = context
+            .zip(self.1)
+            .and_then(|(context, evaluation)| context.extractor_results.get(evaluation));
+        if let Some(value) = result.and_then(Cell::get) {
+            return value;
+        }
+        let value = (self.0)(event);
+        if let Some(result) = result {
+            result.set(Some(value));
+        }
+        value
+    }
+}
+
+impl<Event> Clone for EventExtractor<Event> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1)
+    }
+}
+
+impl<Event> Debug for EventExtractor<Event> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventExtractor")
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<Event> SharedCondition<Event> {
+    pub fn new(condition: Arc<TriggerCondition<Event>>) -> Self
+    where
+        Event: Clone,
+    {
+        Self {
+            condition,
+            clone: Some(TriggerCondition::clone),
+        }
+    }
+
+    pub fn condition(&self) -> &TriggerCondition<Event> {
+        &self.condition
+    }
+
+    /// Returns the condition, cloning it if it is still shared.
+    fn resolve(self) -> TriggerCondition<Event> {
+        let clone = self.clone;
+        Arc::try_unwrap(self.condition).unwrap_or_else(|condition| {
+            clone.expect("only conditions created with Event: Clone can be shared")(&condition)
+        })
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<Event: Clone> Clone for SharedCondition<Event> {
+    fn clone(&self) -> Self {
+        Self::new(self.condition.clone())
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<Event: Debug> Debug for SharedCondition<Event> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.condition, f)
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<Event: PartialEq> PartialEq for SharedCondition<Event> {
+    fn eq(&self, other: &Self) -> bool {
+        self.condition == other.condition
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<Event: Eq> Eq for SharedCondition<Event> {}
+
+#[cfg(feature = "shared")]
+impl<Event: PartialOrd> PartialOrd for SharedCondition<Event> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.condition.partial_cmp(&other.condition)
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<Event: Ord> Ord for SharedCondition<Event> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.condition.cmp(&other.condition)
+    }
+}
+
+#[cfg(all(feature = "shared", feature = "serde"))]
+impl<Event: Serialize> Serialize for SharedCondition<Event> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.condition.as_ref().serialize(serializer)
+    }
+}
+
+/// Deserialized conditions are not shared anymore, since sharing is not visible in the serialized form.
+#[cfg(all(feature = "shared", feature = "serde"))]
+impl<'de, Event: Deserialize<'de>> Deserialize<'de> for SharedCondition<Event> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TriggerCondition::deserialize(deserializer).map(|condition| Self {
+            condition: Arc::new(condition),
+            clone: None,
+        })
+    }
+}
+
+impl Display for ConditionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConditionError::EmptyOperator(name) => write!(f, "{name} without sub-conditions"),
+            ConditionError::ThresholdTooHigh { n, available } => write!(
+                f,
+                "threshold {n} exceeds the number of sub-conditions {available}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::CompletedConditionInSequence { index } => write!(
+                f,
+                "sequence contains a completed condition at index {index} after an uncompleted one"
+            ),
+            CompileError::UnknownTriggerId { index } => write!(
+                f,
+                "stale_for condition of the trigger at index {index} refers to an unknown trigger id"
+            ),
+            CompileError::NonFiniteValue => {
+                write!(f, "accumulate target or delta_geq threshold is not finite")
+            }
         }
     }
 }
+
+impl std::error::Error for CompileError {}
@@ -1,5 +1,9 @@
-use crate::TriggerCondition;
+#[cfg(feature = "shared")]
+use crate::conditions::SharedCondition;
+use crate::{BoundKind, OrderedF64, TriggerCondition};
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+#[cfg(feature = "shared")]
+use std::sync::Arc;
 
 pub fn none<Event>() -> TriggerCondition<Event> {
     TriggerCondition::None
@@ -13,10 +17,160 @@ pub fn event_count<Event>(event: Event, required: usize) -> TriggerCondition<Eve
     TriggerCondition::EventCount { event, required }
 }
 
+/// Like [`event_count`], but each `penalty_event` decrements the count, down to zero.
+/// The progress of the resulting condition may decrease.
+pub fn decaying_count<Event>(
+    event: Event,
+    required: usize,
+    penalty_event: Event,
+) -> TriggerCondition<Event> {
+    TriggerCondition::DecayingCount {
+        event,
+        required,
+        penalty_event,
+    }
+}
+
 pub fn geq<Event>(event: Event) -> TriggerCondition<Event> {
     TriggerCondition::Geq { event }
 }
 
+/// Completes on the first comparable event whose value differs from the value of `event`.
+pub fn neq<Event>(event: Event) -> TriggerCondition<Event> {
+    TriggerCondition::Neq { event }
+}
+
+/// Completes on the first event whose value equals the value of any of `events`.
+/// An event is only compared to the reference events with the same identifier.
+pub fn eq_any<Event>(events: Vec<Event>) -> TriggerCondition<Event> {
+    TriggerCondition::EqAny { events }
+}
+
+/// Completes on the first event with the identifier of `event` for which the predicate registered under `name` returns true.
+/// Since functions cannot be serialized, the predicate is registered separately by [`crate::CompiledTriggers::register_predicate`].
+pub fn predicate<Event>(event: Event, name: impl Into<String>) -> TriggerCondition<Event> {
+    TriggerCondition::Predicate {
+        event,
+        name: name.into(),
+    }
+}
+
+/// Like [`predicate`], but receives the events with any identifier.
+/// This allows to match categories of events whose identifiers are not known in advance.
+pub fn predicate_any_event<Event>(name: impl Into<String>) -> TriggerCondition<Event> {
+    TriggerCondition::PredicateAnyEvent { name: name.into() }
+}
+
+/// Sums the values of the events with the identifier of `event` and completes when the sum reaches `target`.
+/// The value of an event is computed by the extractor registered under `name` by [`crate::CompiledTriggers::register_extractor`].
+/// Negative values decrease the sum, down to zero.
+pub fn accumulate<Event>(
+    event: Event,
+    target: f64,
+    name: impl Into<String>,
+) -> TriggerCondition<Event> {
+    TriggerCondition::Accumulate {
+        event,
+        target: OrderedF64(target),
+        name: name.into(),
+    }
+}
+
+/// Completes when the value of an event with the identifier of `event` exceeds the value of the previous such event by at least `threshold`,
+/// e.g. to detect healing a lot in a single tick. The first event only sets the baseline.
+/// The value of an event is computed by the extractor registered under `name` by [`crate::CompiledTriggers::register_extractor`].
+pub fn delta_geq<Event>(
+    event: Event,
+    threshold: f64,
+    name: impl Into<String>,
+) -> TriggerCondition<Event> {
+    TriggerCondition::DeltaGeq {
+        event,
+        threshold: OrderedF64(threshold),
+        name: name.into(),
+    }
+}
+
+/// Completes on the first event whose value is at least the value of `low` and at most the value of `high`.
+/// Both events must have the same identifier.
+///
+/// ```
+/// use event_trigger_action_system::{between, Trigger, TriggerEvent, Triggers};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct HealthChanged(usize);
+///
+/// impl From<()> for HealthChanged {
+///     fn from(_: ()) -> Self {
+///         HealthChanged(0)
+///     }
+/// }
+///
+/// impl TriggerEvent for HealthChanged {
+///     type Action = ();
+///     type Identifier = ();
+///
+///     fn identifier(&self) -> Self::Identifier {}
+///
+///     fn value_geq(&self, other: &Self) -> Option<bool> {
+///         Some(self.0 >= other.0)
+///     }
+///
+///     fn value_geq_progress(&self, other: &Self) -> Option<f64> {
+///         Some((self.0 as f64 / other.0 as f64).min(1.0))
+///     }
+/// }
+///
+/// let mut triggers = Triggers::new(vec![Trigger::new(
+///     "in band".to_string(),
+///     between(HealthChanged(5), HealthChanged(10)),
+///     vec![()],
+/// )])
+/// .compile(&|event| event, &|action| action);
+///
+/// triggers.execute_event(&HealthChanged(3));
+/// triggers.execute_event(&HealthChanged(12));
+/// assert_eq!(triggers.consume_action(), None);
+/// triggers.execute_event(&HealthChanged(7));
+/// assert_eq!(triggers.consume_action(), Some(()));
+/// ```
+pub fn between<Event>(low: Event, high: Event) -> TriggerCondition<Event> {
+    between_bounds(low, BoundKind::Inclusive, high, BoundKind::Inclusive)
+}
+
+/// Like [`between`], but each bound can exclude its own value, e.g. `low_bound` [`BoundKind::Exclusive`] requires a value greater than the value of `low`.
+pub fn between_bounds<Event>(
+    low: Event,
+    low_bound: BoundKind,
+    high: Event,
+    high_bound: BoundKind,
+) -> TriggerCondition<Event> {
+    TriggerCondition::Between {
+        low,
+        high,
+        low_bound,
+        high_bound,
+    }
+}
+
+/// Completes after `required` events with the identifier of `tick` were received while the value was at least the value of `low`
+/// and at most the value of `high`.
+/// The value is given by the latest event with the identifier of `low`, and the count is reset when such an event is out of range.
+/// Before the first value event, the value counts as out of range.
+pub fn hold_between<Event>(
+    low: Event,
+    high: Event,
+    tick: Event,
+    required: usize,
+) -> TriggerCondition<Event> {
+    TriggerCondition::HoldBetween {
+        low,
+        high,
+        tick,
+        required,
+    }
+}
+
 pub fn and<Event>(conditions: Vec<TriggerCondition<Event>>) -> TriggerCondition<Event> {
     TriggerCondition::And { conditions }
 }
@@ -25,14 +179,142 @@ pub fn or<Event>(conditions: Vec<TriggerCondition<Event>>) -> TriggerCondition<E
     TriggerCondition::Or { conditions }
 }
 
+/// Like [`or`], but each branch has a priority.
+/// If several branches complete on the same event, the one with the highest priority is the winner reported by
+/// [`CompiledTriggerCondition::or_winner`](crate::CompiledTriggerCondition::or_winner).
+pub fn or_priority<Event>(
+    conditions: Vec<(i32, TriggerCondition<Event>)>,
+) -> TriggerCondition<Event> {
+    TriggerCondition::PriorityOr { conditions }
+}
+
+/// Like [`or`], but branches that fall far behind are pruned to save work on wide disjunctions.
+/// After each event, every branch whose remaining progress exceeds the smallest remaining progress of any branch by more than `max_lag`
+/// is unsubscribed and does not receive events anymore until the condition is reset.
+/// Pruned branches can therefore not complete the condition.
+pub fn or_pruned<Event>(
+    conditions: Vec<TriggerCondition<Event>>,
+    max_lag: usize,
+) -> TriggerCondition<Event> {
+    TriggerCondition::PrunedOr {
+        conditions,
+        max_lag,
+    }
+}
+
 pub fn sequence<Event>(conditions: Vec<TriggerCondition<Event>>) -> TriggerCondition<Event> {
     TriggerCondition::Sequence { conditions }
 }
 
+/// Like [`sequence`], but restarts from the first condition on each event that does not advance the current condition,
+/// e.g. for input combos. An event advances a condition if it makes progress on it or completes it.
+/// The resulting condition receives the events of all conditions the whole time, so an event meant for a later condition restarts the sequence as well.
+/// An event that restarts the sequence is also passed to the first condition.
+pub fn strict_sequence<Event>(conditions: Vec<TriggerCondition<Event>>) -> TriggerCondition<Event> {
+    TriggerCondition::StrictSequence { conditions }
+}
+
+/// Behaves like `condition`, but shares it with all other conditions created from the same `Arc` instead of cloning it,
+/// e.g. to save memory for templates repeated in many triggers.
+/// Each use is cloned separately when compiled, so the compiled conditions make progress independently.
+#[cfg(feature = "shared")]
+pub fn shared<Event: Clone>(condition: Arc<TriggerCondition<Event>>) -> TriggerCondition<Event> {
+    TriggerCondition::Shared {
+        condition: SharedCondition::new(condition),
+    }
+}
+
+/// Completes on the first event received after the trigger with the id `trigger_id` has not advanced for `events` events,
+/// e.g. to remind the player of a quest they have not worked on for a while.
+/// A trigger advances when an event increases its progress or completes it, and resetting it or replacing its state counts as well.
+/// Like in [`count_within`], the events are counted in the event stream of the trigger system, also before this condition becomes active,
+/// and the trigger containing it receives all events while it is active. The progress is the number of idle events out of `events`.
+/// [`crate::Triggers::try_compile`] rejects trigger ids that do not belong to any of the compiled triggers,
+/// and in a trigger system created otherwise, the condition never completes if its trigger id does not exist.
+pub fn stale_for<Event>(trigger_id: impl Into<String>, events: usize) -> TriggerCondition<Event> {
+    TriggerCondition::StaleFor {
+        trigger_id: trigger_id.into(),
+        events,
+    }
+}
+
+/// Completes when `required` of the last `window` events have the identifier of `event`.
+/// The window counts all events executed by the trigger system, including events created from actions and events executed while the trigger was disabled,
+/// but only events received while this condition is active are counted towards `required`.
+/// Since this condition needs to see all events to move its window, the trigger containing it receives all events while it is active.
+/// The progress is the number of such events in the window at the time of the latest event received by the trigger.
+pub fn count_within<Event>(
+    event: Event,
+    required: usize,
+    window: usize,
+) -> TriggerCondition<Event> {
+    TriggerCondition::CountWithin {
+        event,
+        required,
+        window,
+    }
+}
+
+/// Like [`sequence`], but all conditions make progress simultaneously.
+/// The progress of a condition only counts once all conditions before it are completed.
+pub fn pipeline<Event>(conditions: Vec<TriggerCondition<Event>>) -> TriggerCondition<Event> {
+    TriggerCondition::Pipeline { conditions }
+}
+
 pub fn any_n<Event>(conditions: Vec<TriggerCondition<Event>>, n: usize) -> TriggerCondition<Event> {
     TriggerCondition::AnyN { conditions, n }
 }
 
+/// Completes after `window` events subscribed by `condition` were received without `condition` completing.
+/// If `condition` completes first, the resulting condition can never complete.
+pub fn not<Event>(condition: TriggerCondition<Event>, window: usize) -> TriggerCondition<Event> {
+    TriggerCondition::Not {
+        condition: Box::new(condition),
+        window,
+    }
+}
+
+/// Completes when `condition` completes, but resets `condition` on each event with the identifier of `failure_event`.
+/// After more than `max_retries` failure events, the resulting condition can never complete.
+/// As a step of a [`sequence`], the failure events only count while the step is active.
+pub fn retry<Event>(
+    condition: TriggerCondition<Event>,
+    failure_event: Event,
+    max_retries: usize,
+) -> TriggerCondition<Event> {
+    TriggerCondition::Retry {
+        condition: Box::new(condition),
+        failure_event,
+        max_retries,
+    }
+}
+
+/// Completes when exactly one of `conditions` completes.
+/// If multiple conditions complete on the same event, the resulting condition can never complete.
+pub fn xor<Event>(conditions: Vec<TriggerCondition<Event>>) -> TriggerCondition<Event> {
+    TriggerCondition::Xor { conditions }
+}
+
+/// Completes when exactly `n` of `conditions` complete.
+/// If more than `n` conditions are completed after the same event, the resulting condition can never complete.
+pub fn exactly_n<Event>(
+    conditions: Vec<TriggerCondition<Event>>,
+    n: usize,
+) -> TriggerCondition<Event> {
+    TriggerCondition::ExactlyN { conditions, n }
+}
+
+/// Completes after `window` events subscribed by `conditions` were received while at most `n` of `conditions` completed.
+/// If more than `n` conditions complete first, the resulting condition can never complete.
+/// This is equivalent to `not(any_n(conditions, n + 1), window)`.
+pub fn at_most_n<Event>(
+    conditions: Vec<TriggerCondition<Event>>,
+    n: usize,
+    window: usize,
+) -> TriggerCondition<Event> {
+    not(any_n(conditions, n + 1), window)
+}
+
 impl<Event: Clone> BitAndAssign for TriggerCondition<Event> {
     fn bitand_assign(&mut self, rhs: Self) {
         *self = self.clone() & rhs;
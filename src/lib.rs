@@ -1,10 +1,28 @@
 mod conditions;
 mod constructors;
+mod macros;
+pub mod progress;
 mod triggers;
 
-pub use crate::conditions::{CompiledTriggerCondition, TriggerCondition, TriggerConditionUpdate};
-pub use crate::constructors::{and, any_n, event_count, geq, never, none, or, sequence};
+#[cfg(feature = "shared")]
+pub use crate::conditions::SharedCondition;
+pub use crate::conditions::{
+    BoundKind, CompileError, CompiledTriggerCondition, ConditionError, ConditionKindTag,
+    EventExtractor, EventPredicate, OrderedF64, TriggerCondition, TriggerConditionUpdate,
+};
+#[cfg(feature = "shared")]
+pub use crate::constructors::shared;
+pub use crate::constructors::{
+    accumulate, and, any_n, at_most_n, between, between_bounds, count_within, decaying_count,
+    delta_geq, eq_any, event_count, exactly_n, geq, hold_between, neq, never, none, not, or,
+    or_priority, or_pruned, pipeline, predicate, predicate_any_event, retry, sequence, stale_for,
+    strict_sequence, xor,
+};
+#[cfg(feature = "stats")]
+pub use crate::triggers::Stats;
 pub use crate::triggers::{
-    CompiledTrigger, CompiledTriggers, Trigger, TriggerAction, TriggerEvent, TriggerHandle,
-    TriggerIdentifier, Triggers,
+    BuildError, CascadeMode, CascadeOrder, CompiledTrigger, CompiledTriggers,
+    CompiledTriggersBuilder, MemoryReport, NumericTriggerEvent, QuestSummary, Trigger,
+    TriggerAction, TriggerBuilder, TriggerDiff, TriggerEvent, TriggerHandle, TriggerIdentifier,
+    TriggerSnapshot, TriggerState, TriggerStatus, Triggers,
 };
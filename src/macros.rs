@@ -0,0 +1,29 @@
+/// Creates [`Triggers`](crate::Triggers) from a list of `id => condition => [actions]` entries.
+///
+/// ```
+/// use event_trigger_action_system::{event_count, none, triggers, Trigger, Triggers};
+///
+/// let triggers: Triggers<(), ()> = triggers! {
+///     "start" => none() => [()],
+///     "repeat" => event_count((), 2) => [(), ()],
+/// };
+/// assert_eq!(
+///     triggers,
+///     Triggers::new(vec![
+///         Trigger::new("start".to_string(), none(), vec![()]),
+///         Trigger::new("repeat".to_string(), event_count((), 2), vec![(), ()]),
+///     ])
+/// );
+/// ```
+#[macro_export]
+macro_rules! triggers {
+    ($($id:expr => $condition:expr => [$($action:expr),* $(,)?]),* $(,)?) => {
+        $crate::Triggers::new(::std::vec![$(
+            $crate::Trigger::new(
+                ::std::string::String::from($id),
+                $condition,
+                ::std::vec![$($action),*],
+            )
+        ),*])
+    };
+}
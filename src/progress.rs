@@ -0,0 +1,37 @@
+//! Helpers for implementing [`TriggerEvent::value_geq_progress`](crate::TriggerEvent::value_geq_progress).
+
+use std::cmp::Ordering;
+
+/// Returns the progress of a non-negative `current` value towards a non-negative `target` value, in the range from zero to one.
+///
+/// The condition is completed if `current.partial_cmp(&target)` is `ordering` or `Equal`,
+/// in which case the progress is one. `Ordering::Greater` means that `current` should reach at least `target`,
+/// `Ordering::Less` that it should fall to at most `target`, and `Ordering::Equal` that it should match `target` exactly.
+/// Otherwise, the progress is the ratio of the smaller to the larger of the two values.
+/// It grows monotonically as `current` approaches `target` from the side where the condition is not completed,
+/// which keeps the progress of conditions such as `geq` from jumping back and forth for values moving in one direction.
+///
+/// Returns `None` if one of the values is negative or not a number.
+///
+/// ```
+/// use event_trigger_action_system::progress::linear_progress;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(linear_progress(5.0, 20.0, Ordering::Greater), Some(0.25));
+/// assert_eq!(linear_progress(25.0, 20.0, Ordering::Greater), Some(1.0));
+/// assert_eq!(linear_progress(40.0, 10.0, Ordering::Less), Some(0.25));
+/// assert_eq!(linear_progress(-1.0, 10.0, Ordering::Less), None);
+/// ```
+pub fn linear_progress(current: f64, target: f64, ordering: Ordering) -> Option<f64> {
+    if !(current >= 0.0 && target >= 0.0) {
+        return None;
+    }
+
+    let actual_ordering = current.partial_cmp(&target)?;
+    if actual_ordering == Ordering::Equal || actual_ordering == ordering {
+        Some(1.0)
+    } else {
+        // The values differ, so the larger one is positive.
+        Some(current.min(target) / current.max(target))
+    }
+}
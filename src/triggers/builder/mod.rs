@@ -0,0 +1,180 @@
+use crate::conditions::{CompileError, ConditionError};
+use crate::triggers::{CompiledTriggers, Trigger, TriggerEvent, Triggers};
+use crate::TriggerCondition;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// Collects triggers and the functions to compile them, and builds a [`CompiledTriggers`] instance from them.
+///
+/// The compilers are set with [`Self::event_compiler`] and [`Self::action_compiler`], and [`Self::build`] is only available once both are set.
+pub struct CompiledTriggersBuilder<Event, Action, EventCompiler, ActionCompiler> {
+    triggers: Vec<Trigger<Event, Action>>,
+    validate: bool,
+    event_compiler: EventCompiler,
+    action_compiler: ActionCompiler,
+}
+
+/// Builds a [`Trigger`], see [`Trigger::builder`].
+/// Unless set otherwise, the trigger has an empty id, the condition `none()` and no actions.
+#[derive(Debug, Clone)]
+pub struct TriggerBuilder<Event, Action> {
+    trigger: Trigger<Event, Action>,
+}
+
+/// An error found while building a [`CompiledTriggers`] instance with [`CompiledTriggersBuilder::build`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BuildError {
+    /// The condition of the trigger with the given id is invalid.
+    InvalidCondition {
+        id_str: String,
+        error: ConditionError,
+    },
+    /// Multiple triggers have the given non-empty id.
+    DuplicateId(String),
+    /// The triggers cannot be compiled, see [`Triggers::try_compile`].
+    Compile(CompileError),
+}
+
+impl<Event, Action> CompiledTriggersBuilder<Event, Action, (), ()> {
+    pub fn new() -> Self {
+        Self {
+            triggers: Vec::new(),
+            validate: false,
+            event_compiler: (),
+            action_compiler: (),
+        }
+    }
+}
+
+impl<Event, Action> Default for CompiledTriggersBuilder<Event, Action, (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Event, Action, EventCompiler, ActionCompiler>
+    CompiledTriggersBuilder<Event, Action, EventCompiler, ActionCompiler>
+{
+    pub fn trigger(mut self, trigger: Trigger<Event, Action>) -> Self {
+        self.triggers.push(trigger);
+        self
+    }
+
+    pub fn triggers(mut self, triggers: impl IntoIterator<Item = Trigger<Event, Action>>) -> Self {
+        self.triggers.extend(triggers);
+        self
+    }
+
+    /// Makes [`Self::build`] validate the conditions of all triggers with [`crate::TriggerCondition::validate`],
+    /// and check that no two triggers have the same non-empty id.
+    pub fn validate_all(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    pub fn event_compiler<NewEventCompiler>(
+        self,
+        event_compiler: NewEventCompiler,
+    ) -> CompiledTriggersBuilder<Event, Action, NewEventCompiler, ActionCompiler> {
+        CompiledTriggersBuilder {
+            triggers: self.triggers,
+            validate: self.validate,
+            event_compiler,
+            action_compiler: self.action_compiler,
+        }
+    }
+
+    pub fn action_compiler<NewActionCompiler>(
+        self,
+        action_compiler: NewActionCompiler,
+    ) -> CompiledTriggersBuilder<Event, Action, EventCompiler, NewActionCompiler> {
+        CompiledTriggersBuilder {
+            triggers: self.triggers,
+            validate: self.validate,
+            event_compiler: self.event_compiler,
+            action_compiler,
+        }
+    }
+
+    /// Validates the triggers if requested by [`Self::validate_all`], and compiles them like [`Triggers::try_compile`].
+    pub fn build<CompiledEvent: TriggerEvent>(
+        self,
+    ) -> Result<CompiledTriggers<CompiledEvent>, BuildError>
+    where
+        EventCompiler: Fn(Event) -> CompiledEvent,
+        ActionCompiler: Fn(Action) -> CompiledEvent::Action,
+    {
+        if self.validate {
+            let mut ids = BTreeSet::new();
+            for trigger in &self.triggers {
+                if let Err(error) = trigger.condition.validate() {
+                    return Err(BuildError::InvalidCondition {
+                        id_str: trigger.id_str.clone(),
+                        error,
+                    });
+                }
+                if !trigger.id_str.is_empty() && !ids.insert(trigger.id_str.as_str()) {
+                    return Err(BuildError::DuplicateId(trigger.id_str.clone()));
+                }
+            }
+        }
+
+        Triggers::new(self.triggers)
+            .try_compile(&self.event_compiler, &self.action_compiler)
+            .map_err(BuildError::Compile)
+    }
+}
+
+impl<Event, Action> TriggerBuilder<Event, Action> {
+    pub fn new() -> Self {
+        Self {
+            trigger: Trigger::new(String::new(), TriggerCondition::None, Vec::new()),
+        }
+    }
+
+    pub fn id(mut self, id_str: impl Into<String>) -> Self {
+        self.trigger.id_str = id_str.into();
+        self
+    }
+
+    pub fn condition(mut self, condition: TriggerCondition<Event>) -> Self {
+        self.trigger.condition = condition;
+        self
+    }
+
+    /// Appends an action to the actions of the trigger.
+    pub fn action(mut self, action: Action) -> Self {
+        self.trigger.actions.push(action);
+        self
+    }
+
+    /// Appends the given actions to the actions of the trigger.
+    pub fn actions(mut self, actions: impl IntoIterator<Item = Action>) -> Self {
+        self.trigger.actions.extend(actions);
+        self
+    }
+
+    pub fn build(self) -> Trigger<Event, Action> {
+        self.trigger
+    }
+}
+
+impl<Event, Action> Default for TriggerBuilder<Event, Action> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::InvalidCondition { id_str, error } => {
+                write!(f, "invalid condition of trigger {id_str:?}: {error}")
+            }
+            BuildError::DuplicateId(id_str) => write!(f, "duplicate trigger id {id_str:?}"),
+            BuildError::Compile(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
@@ -1,14 +1,27 @@
-use crate::conditions::{CompiledTriggerCondition, TriggerConditionUpdate};
+use crate::conditions::{
+    CompileError, CompiledTriggerCondition, CompiledTriggerConditionKind, EventContext,
+    EventExtractor, EventPredicate, SharedEvaluations, TriggerConditionUpdate,
+};
+use crate::progress::linear_progress;
 use crate::TriggerCondition;
 use btreemultimap_value_ord::BTreeMultiMap;
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, VecDeque};
+use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt::Debug;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
+mod builder;
 mod std_lib_implementations;
 
-#[derive(Debug, Clone)]
+pub use builder::{BuildError, CompiledTriggersBuilder, TriggerBuilder};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Triggers<Event, Action> {
     triggers: Vec<Trigger<Event, Action>>,
 }
@@ -16,22 +29,220 @@ pub struct Triggers<Event, Action> {
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CompiledTriggers<Event: TriggerEvent> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            deserialize_with = "deserialize_trigger_system",
+            bound(deserialize = "Event: Deserialize<'de>")
+        )
+    )]
     trigger_system: TriggerSystem<Event>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_action_queue")
+    )]
     action_queue: VecDeque<Event::Action>,
+    /// The number of actions at the front of the action queue that the action transformers were applied to already,
+    /// see [`CompiledTriggers::consume_action_if`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    transformed_actions: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    tag_index: BTreeMap<String, Vec<TriggerHandle>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    action_transformers: ActionTransformers<Event::Action>,
+}
+
+/// Decides if two actions are duplicates of each other.
+type ActionComparator<Action> = fn(&Action, &Action) -> bool;
+
+/// Transforms an action when it is consumed, see [`CompiledTriggers::add_action_transformer`].
+type ActionTransformer<Action> = Arc<Mutex<dyn FnMut(Action) -> Action + Send>>;
+
+/// Functions that are applied to each action when it is consumed, in order of registration.
+/// Since the functions cannot be cloned, clones share the same functions.
+struct ActionTransformers<Action> {
+    transformers: Vec<ActionTransformer<Action>>,
+}
+
+#[cfg(feature = "serde")]
+thread_local! {
+    /// True while [`CompiledTriggers::deserialize_lenient`] runs, see [`deserialize_actions`].
+    /// It is only set through [`LenientGuard`].
+    static LENIENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Makes the deserialization on the current thread lenient until it is dropped, see [`LENIENT`].
+/// Dropping restores the previous leniency, also when the deserialization panics.
+#[cfg(feature = "serde")]
+struct LenientGuard {
+    previous: bool,
+}
+
+#[cfg(feature = "serde")]
+impl LenientGuard {
+    fn new() -> Self {
+        Self {
+            previous: LENIENT.with(|lenient| lenient.replace(true)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Drop for LenientGuard {
+    fn drop(&mut self) {
+        LENIENT.with(|lenient| lenient.set(self.previous));
+    }
+}
+
+/// An action that may not be deserializable anymore, e.g. because its enum variant was removed.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LenientAction<Action> {
+    Known(Action),
+    Unknown(IgnoredAny),
 }
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct TriggerSystem<Event: TriggerEvent> {
-    triggers: Vec<CompiledTrigger<Event>>,
+    /// Removed triggers are replaced by `None`, such that the handles of the other triggers stay valid.
+    triggers: Vec<Option<CompiledTrigger<Event>>>,
+    /// The multimap counts how often each trigger is subscribed to an identifier,
+    /// so a sub-condition unsubscribing does not remove the subscription of another sub-condition to the same identifier.
     subscriptions: BTreeMultiMap<Event::Identifier, usize>,
+    /// The indices of the triggers whose condition is not completed.
+    /// This is not serialized, but rebuilt from the conditions, see [`deserialize_trigger_system`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    active: BTreeSet<usize>,
+    /// The indices of the enabled triggers whose condition currently receives all events, see [`CompiledTriggerCondition::subscribes_to_all`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    wildcard_subscriptions: BTreeSet<usize>,
+    /// Scratch buffer for the actions produced while executing an event, to avoid allocating on each event.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    action_buffer: Vec<Event::Action>,
+    /// Scratch buffer for the indices of the triggers subscribed to the event being executed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trigger_index_buffer: Vec<usize>,
+    /// Scratch buffers for the results of the registered predicates and extractors, shared by all conditions receiving the event being executed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    shared_evaluations: SharedEvaluations,
+    /// Actions whose events were not executed yet because their cascade was interrupted or deferred, in order of execution.
+    /// The actions themselves are already in the action queue.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "Vec::new", deserialize_with = "deserialize_actions")
+    )]
+    pending_actions: Vec<Event::Action>,
+    /// The index of the trigger that produced each pending action, if known, such that a repeating trigger cannot feed itself across [`CompiledTriggers::pump`] calls.
+    #[cfg_attr(feature = "serde", serde(default = "Vec::new"))]
+    pending_action_sources: Vec<Option<usize>>,
+    /// The indices of the triggers whose actions caused the events currently being executed, outermost first.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cascade_sources: Vec<usize>,
+    /// The depth of the event currently being executed, where events passed to the trigger system have depth zero.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cascade_depth: usize,
+    /// The number of events executed so far, including events created from actions that no trigger received.
+    /// This is the position of the last event in the event stream, see [`EventContext`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    event_count: usize,
+    /// The position of the last event that advanced each trigger in the event stream, indexed like `triggers`, see [`EventContext`].
+    /// Triggers that did not advance yet have the position at which they were added, or at which their state was last replaced.
+    #[cfg_attr(feature = "serde", serde(default = "Vec::new"))]
+    last_advances: Vec<usize>,
+    /// When the events created from actions are executed, see [`CompiledTriggers::set_cascade_mode`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    cascade_mode: CascadeMode,
+    /// The order in which the events of a cascade are executed, see [`CompiledTriggers::set_cascade_order`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    cascade_order: CascadeOrder,
+    /// Compares actions to remove duplicates produced by the same event, see [`CompiledTriggers::set_coalesce_per_event`].
+    #[cfg_attr(feature = "serde", serde(skip, default = "Default::default"))]
+    coalesce_actions: Option<ActionComparator<Event::Action>>,
+    /// The maximum depth of events created from actions, see [`CompiledTriggers::set_max_cascade`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_cascade: Option<usize>,
+    /// If set, the cascade is interrupted once this many actions were produced, see [`CompiledTriggers::execute_event_limited`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    max_actions: Option<usize>,
+    /// The number of events that were not executed because they exceeded `max_cascade`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dropped_cascade_events: usize,
+    /// The version of the format in which the trigger system was serialized, see [`FORMAT_VERSION`].
+    /// Older versions did not serialize this, which deserializes as version zero.
+    #[cfg(feature = "serde")]
+    #[serde(default)]
+    format_version: u32,
+    /// The predicates registered by [`CompiledTriggers::register_predicate`], applied to triggers added later.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Default::default"))]
+    predicates: BTreeMap<String, EventPredicate<Event>>,
+    /// The extractors registered by [`CompiledTriggers::register_extractor`], applied to triggers added later.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Default::default"))]
+    extractors: BTreeMap<String, EventExtractor<Event>>,
+    /// If set, the indices of the triggers that fire are recorded here, see [`CompiledTriggers::execute_event_completions`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    completions: Option<Vec<usize>>,
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats: Stats,
+    /// The number of events executed on each trigger, indexed like `triggers`, see [`CompiledTriggers::hottest_triggers`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    evaluations: Vec<u64>,
 }
 
-#[derive(Debug, Clone)]
+/// The version of the serialization format of [`CompiledTriggers`], see [`deserialize_trigger_system`].
+/// Version zero did not record if triggers fired.
+#[cfg(feature = "serde")]
+const FORMAT_VERSION: u32 = 1;
+
+/// Statistics about the execution of a trigger system.
+/// Each event counts, including events created from actions during a cascade.
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// The number of events that no trigger was subscribed to.
+    pub events_without_subscribers: usize,
+    /// The number of events that were received by triggers, but did not complete any of them.
+    pub events_progressed_only: usize,
+    /// The number of events that completed at least one trigger.
+    pub events_completing_triggers: usize,
+    /// The number of actions produced by triggers, including failure actions and actions that were not executed as events.
+    pub actions_produced: usize,
+    /// The maximum depth of an executed event, where events passed to the trigger system have depth zero.
+    pub max_cascade_depth: usize,
+    /// The number of times each trigger fired, indexed like the trigger handles.
+    /// Triggers beyond the end of the vector have not fired.
+    pub trigger_fires: Vec<usize>,
+}
+
+#[cfg(feature = "stats")]
+impl Stats {
+    /// Returns the total number of executed events.
+    pub fn events(&self) -> usize {
+        self.events_without_subscribers
+            + self.events_progressed_only
+            + self.events_completing_triggers
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Trigger<Event, Action> {
     pub id_str: String,
     pub condition: TriggerCondition<Event>,
     pub actions: Vec<Action>,
+    // The fields below are private, such that adding more of them does not break code constructing triggers.
+    // They are set with the `with_*` methods.
+    #[cfg_attr(feature = "serde", serde(default))]
+    tags: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    repeat: bool,
+    #[cfg_attr(feature = "serde", serde(default = "Vec::new"))]
+    fail_on: Vec<Event>,
+    #[cfg_attr(feature = "serde", serde(default = "Vec::new"))]
+    failure_actions: Vec<Action>,
 }
 
 #[derive(Debug, Clone)]
@@ -39,13 +250,155 @@ pub struct Trigger<Event, Action> {
 pub struct CompiledTrigger<Event: TriggerEvent> {
     pub id_str: String,
     condition: CompiledTriggerCondition<Event>,
-    actions: Option<Vec<Event::Action>>,
+    /// The actions are retained after firing, such that the trigger can be reset.
+    /// Older versions serialized the actions of fired triggers as `null`, which deserializes as no actions.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_trigger_actions")
+    )]
+    actions: Vec<Event::Action>,
+    /// Older versions did not serialize this, see [`deserialize_trigger_system`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    fired: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    tags: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    repeat: bool,
+    /// If true, the trigger is removed after firing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    once: bool,
+    /// Disabled triggers are not subscribed to any events and do not fire, but keep their state.
+    #[cfg_attr(feature = "serde", serde(default = "enabled_by_default"))]
+    enabled: bool,
+    #[cfg_attr(feature = "serde", serde(default = "Vec::new"))]
+    fail_on: Vec<Event::Identifier>,
+    #[cfg_attr(feature = "serde", serde(default = "Vec::new"))]
+    failure_actions: Vec<Event::Action>,
+    /// If true, the trigger received one of its `fail_on` events.
+    #[cfg_attr(feature = "serde", serde(default))]
+    failed: bool,
 }
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TriggerHandle(usize);
 
+/// The runtime state of a [`CompiledTriggers`] instance, see [`CompiledTriggers::snapshot`].
+/// Ids, tags, actions and registered functions of the triggers are not included.
+/// The statistics and metrics are included, such that restoring also rolls them back.
+#[derive(Debug, Clone)]
+pub struct TriggerSnapshot<Event: TriggerEvent> {
+    /// The state of each trigger, or `None` if it was removed.
+    triggers: Vec<Option<TriggerState<Event>>>,
+    subscriptions: BTreeMultiMap<Event::Identifier, usize>,
+    active: BTreeSet<usize>,
+    wildcard_subscriptions: BTreeSet<usize>,
+    pending_actions: Vec<Event::Action>,
+    pending_action_sources: Vec<Option<usize>>,
+    event_count: usize,
+    last_advances: Vec<usize>,
+    dropped_cascade_events: usize,
+    #[cfg(feature = "stats")]
+    stats: Stats,
+    #[cfg(feature = "metrics")]
+    evaluations: Vec<u64>,
+    action_queue: VecDeque<Event::Action>,
+    transformed_actions: usize,
+}
+
+/// The runtime state of a single trigger, see [`CompiledTriggers::trigger_state`] and [`TriggerSnapshot`].
+/// The condition is stored as a whole, since its state is spread over its structure, e.g. by moving completed sub-conditions.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TriggerState<Event: TriggerEvent> {
+    condition: CompiledTriggerCondition<Event>,
+    fired: bool,
+    enabled: bool,
+    failed: bool,
+}
+
+/// An estimate of the memory used by a [`CompiledTriggers`] instance, in bytes.
+/// Heap allocations made by events, identifiers and actions themselves are not included.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MemoryReport {
+    /// The memory used by the triggers, excluding their conditions.
+    pub triggers: usize,
+    /// The heap memory used by the conditions of the triggers.
+    pub conditions: usize,
+    /// The memory used by the subscriptions of the triggers to event identifiers and to all events.
+    pub subscriptions: usize,
+    /// The memory used by the action queue, the internal action buffer and the actions with deferred events.
+    pub action_queue: usize,
+}
+
+/// The status of a trigger as shown e.g. in a quest log.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TriggerStatus {
+    /// The trigger can still complete.
+    Active,
+    /// The condition of the trigger is completed.
+    Completed,
+    /// The condition of the trigger can never complete.
+    Failed,
+}
+
+/// When the events created from actions are executed, see [`CompiledTriggers::set_cascade_mode`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CascadeMode {
+    /// The whole cascade is executed as part of the event that started it.
+    Immediate,
+    /// The events of the produced actions are deferred, and each call to [`CompiledTriggers::pump`] executes one layer of the cascade.
+    Deferred,
+}
+
+impl Default for CascadeMode {
+    fn default() -> Self {
+        Self::Immediate
+    }
+}
+
+/// The order in which the events of an immediate cascade are executed, see [`CompiledTriggers::set_cascade_order`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CascadeOrder {
+    /// The cascade of each action is completed before the event of the next action is executed.
+    DepthFirst,
+    /// The events of all actions produced by an event are executed before the events of the actions they produce.
+    BreadthFirst,
+}
+
+impl Default for CascadeOrder {
+    fn default() -> Self {
+        Self::DepthFirst
+    }
+}
+
+/// The display data of a single trigger, see [`CompiledTriggers::quest_summary`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuestSummary<Identifier> {
+    pub handle: TriggerHandle,
+    pub id: String,
+    pub status: TriggerStatus,
+    pub current: f64,
+    pub required: f64,
+    /// The identifiers of the events the trigger currently needs to make progress.
+    pub waiting_on: Vec<Identifier>,
+}
+
+/// A trigger that differs between two trigger systems, see [`CompiledTriggers::diff`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TriggerDiff<Identifier> {
+    pub handle: TriggerHandle,
+    /// The trigger in the system `diff` was called on, or `None` if it does not exist there.
+    pub left: Option<QuestSummary<Identifier>>,
+    /// The trigger in the other system, or `None` if it does not exist there.
+    pub right: Option<QuestSummary<Identifier>>,
+}
+
 pub trait TriggerAction: Debug + Clone {}
 
 pub trait TriggerIdentifier: Debug + Ord + Clone {}
@@ -62,6 +415,13 @@ pub trait TriggerEvent: From<Self::Action> {
     /// Returns a number between 0.0 and 1.0 indicating how close the condition `value_geq` is to being fulfilled.
     /// Except if the events are not compatible, then `None` is returned.
     fn value_geq_progress(&self, other: &Self) -> Option<f64>;
+
+    /// Returns the identifier of the event created from the given action, if it is known without converting the action.
+    /// If it is, actions whose identifier has no subscribers are never converted into events.
+    /// The default implementation returns `None`, such that each action is converted exactly once. Override it if converting is expensive.
+    fn action_identifier(_action: &Self::Action) -> Option<Self::Identifier> {
+        None
+    }
 }
 
 #[cfg(feature = "serde")]
@@ -76,6 +436,63 @@ pub trait TriggerEvent: From<Self::Action> {
     /// Returns a number between 0.0 and 1.0 indicating how close the condition `value_geq` is to being fulfilled.
     /// Except if the events are not compatible, then `None` is returned.
     fn value_geq_progress(&self, other: &Self) -> Option<f64>;
+
+    /// Returns the identifier of the event created from the given action, if it is known without converting the action.
+    /// If it is, actions whose identifier has no subscribers are never converted into events.
+    /// The default implementation returns `None`, such that each action is converted exactly once. Override it if converting is expensive.
+    fn action_identifier(_action: &Self::Action) -> Option<Self::Identifier> {
+        None
+    }
+}
+
+/// An event whose value is a number, for which [`TriggerEvent`] is implemented automatically.
+/// The value is compared with [`PartialOrd`], and the progress is computed from [`numeric_value`](Self::numeric_value)
+/// by [`linear_progress`](crate::progress::linear_progress).
+#[cfg(not(feature = "serde"))]
+pub trait NumericTriggerEvent: PartialOrd + From<Self::Action> {
+    type Action: TriggerAction;
+    type Identifier: TriggerIdentifier;
+
+    fn identifier(&self) -> Self::Identifier;
+
+    /// Returns the value of the event, or `None` if the event has no value.
+    fn numeric_value(&self) -> Option<f64>;
+}
+
+/// An event whose value is a number, for which [`TriggerEvent`] is implemented automatically.
+/// The value is compared with [`PartialOrd`], and the progress is computed from [`numeric_value`](Self::numeric_value)
+/// by [`linear_progress`](crate::progress::linear_progress).
+#[cfg(feature = "serde")]
+pub trait NumericTriggerEvent: PartialOrd + From<Self::Action> {
+    type Action: TriggerAction + Serialize + for<'de> Deserialize<'de>;
+    type Identifier: TriggerIdentifier + Serialize + for<'de> Deserialize<'de>;
+
+    fn identifier(&self) -> Self::Identifier;
+
+    /// Returns the value of the event, or `None` if the event has no value.
+    fn numeric_value(&self) -> Option<f64>;
+}
+
+impl<Event: NumericTriggerEvent> TriggerEvent for Event {
+    type Action = <Event as NumericTriggerEvent>::Action;
+    type Identifier = <Event as NumericTriggerEvent>::Identifier;
+
+    fn identifier(&self) -> Self::Identifier {
+        NumericTriggerEvent::identifier(self)
+    }
+
+    fn value_geq(&self, other: &Self) -> Option<bool> {
+        self.partial_cmp(other)
+            .map(|ordering| ordering != std::cmp::Ordering::Less)
+    }
+
+    fn value_geq_progress(&self, other: &Self) -> Option<f64> {
+        linear_progress(
+            self.numeric_value()?,
+            other.numeric_value()?,
+            std::cmp::Ordering::Greater,
+        )
+    }
 }
 
 impl<Event, Action> Triggers<Event, Action> {
@@ -83,6 +500,46 @@ impl<Event, Action> Triggers<Event, Action> {
         Self { triggers }
     }
 
+    /// Appends a trigger, which gets the handle following the handles of the triggers before it when compiled.
+    pub fn push(&mut self, trigger: Trigger<Event, Action>) {
+        self.triggers.push(trigger);
+    }
+
+    pub fn len(&self) -> usize {
+        self.triggers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    /// Multiplies the required counts of all count-based conditions by `factor`.
+    /// See [`TriggerCondition::scale_requirements`].
+    pub fn scale_requirements(&mut self, factor: f64) {
+        self.triggers
+            .iter_mut()
+            .for_each(|trigger| trigger.condition.scale_requirements(factor));
+    }
+
+    /// Returns an iterator over mutable references to the triggers, for editing them before compilation.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Trigger<Event, Action>> {
+        self.triggers.iter_mut()
+    }
+
+    /// Replaces the condition of each trigger with the result of applying `f` to it.
+    pub fn map_conditions<F: FnMut(TriggerCondition<Event>) -> TriggerCondition<Event>>(
+        &mut self,
+        mut f: F,
+    ) {
+        for trigger in &mut self.triggers {
+            let condition = std::mem::replace(&mut trigger.condition, TriggerCondition::None);
+            trigger.condition = f(condition);
+        }
+    }
+
+    /// Compiles the triggers.
+    /// The handles are assigned in the order of the triggers, such that the i-th trigger gets the handle `handle_for_index(i)`.
+    /// This ordering is stable, so handles stored e.g. in save data stay valid for an identical set of triggers.
     pub fn compile<
         EventCompiler: Fn(Event) -> CompiledEvent,
         CompiledEvent: TriggerEvent,
@@ -92,53 +549,260 @@ impl<Event, Action> Triggers<Event, Action> {
         event_compiler: &EventCompiler,
         action_compiler: &ActionCompiler,
     ) -> CompiledTriggers<CompiledEvent> {
-        CompiledTriggers::new(
-            self.triggers
-                .into_iter()
-                .map(|trigger| trigger.compile(event_compiler, action_compiler))
-                .collect(),
-        )
+        self.try_compile(event_compiler, action_compiler)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Compiles the triggers like [`Self::compile`], or returns the error of the first trigger that cannot be compiled.
+    /// Besides invalid conditions, this rejects `stale_for` conditions whose trigger id does not belong to any of the triggers.
+    pub fn try_compile<
+        EventCompiler: Fn(Event) -> CompiledEvent,
+        CompiledEvent: TriggerEvent,
+        ActionCompiler: Fn(Action) -> CompiledEvent::Action,
+    >(
+        self,
+        event_compiler: &EventCompiler,
+        action_compiler: &ActionCompiler,
+    ) -> Result<CompiledTriggers<CompiledEvent>, CompileError> {
+        let mut triggers = self
+            .triggers
+            .into_iter()
+            .map(|trigger| trigger.try_compile(event_compiler, action_compiler))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(index) = resolve_stale_for(&mut triggers) {
+            return Err(CompileError::UnknownTriggerId { index });
+        }
+        Ok(CompiledTriggers::new(triggers))
     }
 }
 
 impl<Event: TriggerEvent> CompiledTriggers<Event> {
+    /// Creates a trigger system from the given triggers.
+    /// The `stale_for` conditions are resolved to the first trigger with their trigger id.
+    /// A `stale_for` condition whose trigger id does not exist never completes, see [`Triggers::try_compile`].
     pub fn new(mut triggers: Vec<CompiledTrigger<Event>>) -> Self {
-        let mut initial_actions = Vec::new();
+        resolve_stale_for(&mut triggers);
+        let mut tag_index: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (id, trigger) in triggers.iter().enumerate() {
+            for tag in &trigger.tags {
+                tag_index
+                    .entry(tag.clone())
+                    .or_default()
+                    .push(TriggerHandle(id));
+            }
+        }
         let subscriptions = triggers
-            .iter_mut()
+            .iter()
             .enumerate()
             .flat_map(|(id, trigger)| {
-                let subscriptions = trigger.subscriptions();
-                if trigger.completed() {
-                    initial_actions.append(&mut trigger.consume_actions());
-                }
-                subscriptions
+                trigger
+                    .active_subscriptions()
                     .into_iter()
-                    .map(move |identifier| (identifier, id))
+                    .map(move |identifier| {
+                        #[cfg(feature = "tracing")]
+                        tracing::trace!(trigger = id, ?identifier, "subscribe");
+                        (identifier, id)
+                    })
             })
             .collect();
-        let mut trigger_system = TriggerSystem {
-            triggers,
-            subscriptions,
+        let active = triggers
+            .iter()
+            .enumerate()
+            .filter(|(_, trigger)| !trigger.completed())
+            .map(|(index, _)| index)
+            .collect();
+        let wildcard_subscriptions = triggers
+            .iter()
+            .enumerate()
+            .filter(|(_, trigger)| trigger.subscribes_to_all())
+            .map(|(index, _)| index)
+            .collect();
+        let last_advances = vec![0; triggers.len()];
+        let mut result = Self {
+            trigger_system: TriggerSystem {
+                triggers: triggers.into_iter().map(Some).collect(),
+                subscriptions,
+                active,
+                wildcard_subscriptions,
+                action_buffer: Vec::new(),
+                trigger_index_buffer: Vec::new(),
+                shared_evaluations: Default::default(),
+                pending_actions: Vec::new(),
+                pending_action_sources: Vec::new(),
+                event_count: 0,
+                last_advances,
+                cascade_sources: Vec::new(),
+                cascade_depth: 0,
+                cascade_mode: CascadeMode::Immediate,
+                cascade_order: CascadeOrder::DepthFirst,
+                coalesce_actions: None,
+                max_cascade: None,
+                max_actions: None,
+                dropped_cascade_events: 0,
+                #[cfg(feature = "serde")]
+                format_version: FORMAT_VERSION,
+                predicates: BTreeMap::new(),
+                extractors: BTreeMap::new(),
+                completions: None,
+                #[cfg(feature = "stats")]
+                stats: Default::default(),
+                #[cfg(feature = "metrics")]
+                evaluations: Vec::new(),
+            },
+            action_queue: Default::default(),
+            transformed_actions: 0,
+            tag_index,
+            action_transformers: Default::default(),
         };
 
-        let mut i = 0;
-        while i < initial_actions.len() {
-            initial_actions.append(
-                &mut trigger_system.execute_event(&Event::from(initial_actions[i].clone())),
-            );
-            i += 1;
-        }
+        let initial_actions = result.flush_ready();
+        result.action_queue.extend(initial_actions);
+        result
+    }
 
-        Self {
-            trigger_system,
-            action_queue: initial_actions.into_iter().collect(),
+    /// Like [`Self::new`], but the initial actions of completed triggers are sorted by descending `priority` before their cascade is executed.
+    /// Actions with equal priority stay in the order of their triggers.
+    pub fn new_with_priority<P: Ord, F: FnMut(&Event::Action) -> P>(
+        triggers: Vec<CompiledTrigger<Event>>,
+        mut priority: F,
+    ) -> Self {
+        // Consume the actions before calling `new`, such that it does not cascade them.
+        let mut triggers = triggers;
+        let mut initial_actions = Vec::new();
+        for (index, trigger) in triggers.iter_mut().enumerate() {
+            if trigger.ready() {
+                initial_actions.extend(
+                    trigger
+                        .consume_actions()
+                        .into_iter()
+                        .map(|action| (action, index)),
+                );
+            }
         }
+        initial_actions.sort_by_key(|(action, _)| Reverse(priority(action)));
+        let (mut initial_actions, action_sources): (Vec<_>, Vec<_>) = initial_actions
+            .into_iter()
+            .enumerate()
+            .map(|(i, (action, index))| (action, (i + 1, Some(index))))
+            .unzip();
+
+        let mut result = Self::new(triggers);
+        result.trigger_system.execute_actions(
+            &mut initial_actions,
+            0,
+            action_sources,
+            &AtomicBool::new(false),
+        );
+        result.action_queue.extend(initial_actions);
+        result
+    }
+
+    /// Fires all triggers that are completed but whose actions have not been produced yet, e.g. triggers with a `none()` condition.
+    /// Returns the actions of these triggers, as well as the actions cascading from them.
+    /// The returned actions are not added to the action queue.
+    pub fn flush_ready(&mut self) -> Vec<Event::Action> {
+        let mut actions = Vec::new();
+        self.trigger_system.flush_ready(&mut actions);
+        actions
+    }
+
+    /// Fires the trigger with the given handle if it is completed but its actions have not been produced yet,
+    /// adding its actions and their cascade to the action queue.
+    fn flush_trigger(&mut self, handle: TriggerHandle) {
+        let mut actions = std::mem::take(&mut self.trigger_system.action_buffer);
+        self.trigger_system.flush_trigger(handle.0, &mut actions);
+        self.action_queue.extend(actions.drain(..));
+        self.trigger_system.action_buffer = actions;
     }
 
     pub fn execute_event(&mut self, event: &Event) {
-        self.action_queue
-            .extend(self.trigger_system.execute_event(event).into_iter());
+        self.execute_event_cancellable(event, &AtomicBool::new(false));
+    }
+
+    /// Executes the given event like [`Self::execute_event`], and returns the handles of the triggers that fired
+    /// as a direct or cascaded result of the event, in order of firing.
+    pub fn execute_event_completions(&mut self, event: &Event) -> Vec<TriggerHandle> {
+        self.trigger_system.completions = Some(Vec::new());
+        self.execute_event(event);
+        let completions = self.trigger_system.completions.take().unwrap_or_default();
+        completions.into_iter().map(TriggerHandle).collect()
+    }
+
+    /// Executes the given events to reconstruct the state of the triggers, e.g. from an event log, without producing any actions.
+    /// The actions of triggers that fire are discarded, and the events of these actions are not executed.
+    /// Triggers that depend on events created from actions therefore do not progress.
+    pub fn replay_without_actions(&mut self, events: &[Event]) {
+        let mut actions = std::mem::take(&mut self.trigger_system.action_buffer);
+        for event in events {
+            self.trigger_system.execute_triggers(event, &mut actions);
+            actions.clear();
+        }
+        self.trigger_system.action_buffer = actions;
+    }
+
+    /// Executes the given event and returns the actions it produced, including its cascade, instead of adding them to the action queue.
+    /// The actions are passed through the action transformers like consumed actions, and the action queue is left untouched.
+    pub fn execute_event_collect(&mut self, event: &Event) -> Vec<Event::Action> {
+        let mut actions = Vec::new();
+        self.trigger_system
+            .execute_event(event, &mut actions, &AtomicBool::new(false));
+        let action_transformers = &mut self.action_transformers;
+        actions
+            .into_iter()
+            .map(|action| action_transformers.apply(action))
+            .collect()
+    }
+
+    /// Executes the given events in order like [`Self::execute_event_collect`], and returns the actions produced by each event at the index of that event.
+    /// Since the cascade of an event is completed before the next event is executed, all actions of a cascade are grouped with the event that started it.
+    /// In [`CascadeMode::Deferred`], only the actions produced directly by each event are returned.
+    pub fn execute_events_grouped(&mut self, events: &[Event]) -> Vec<Vec<Event::Action>> {
+        events
+            .iter()
+            .map(|event| self.execute_event_collect(event))
+            .collect()
+    }
+
+    /// Executes the given event like [`Self::execute_event`], but stops the cascade as soon as `cancel` is set.
+    /// The flag is checked before executing the event of each action produced during the cascade.
+    /// On cancellation, all actions produced so far are queued as usual, and the events of the remaining actions are deferred.
+    /// They are executed in the original order by calling [`Self::pump`].
+    /// Returns true if the cascade was cancelled.
+    pub fn execute_event_cancellable(&mut self, event: &Event, cancel: &AtomicBool) -> bool {
+        let mut actions = std::mem::take(&mut self.trigger_system.action_buffer);
+        let cancelled = self
+            .trigger_system
+            .execute_event(event, &mut actions, cancel);
+        self.action_queue.extend(actions.drain(..));
+        self.trigger_system.action_buffer = actions;
+        cancelled
+    }
+
+    /// Executes the given event like [`Self::execute_event`], but interrupts the cascade once at least `max_actions` actions were produced.
+    /// The limit is checked before executing the event of each action, so a trigger producing several actions at once may exceed it.
+    /// All produced actions are queued as usual, and the events of the remaining actions are deferred until [`Self::pump`] is called.
+    /// Returns true if the limit interrupted the cascade.
+    pub fn execute_event_limited(&mut self, event: &Event, max_actions: usize) -> bool {
+        self.trigger_system.max_actions = Some(max_actions);
+        let limited = self.execute_event_cancellable(event, &AtomicBool::new(false));
+        self.trigger_system.max_actions = None;
+        limited
+    }
+
+    /// Executes the events of all actions whose cascade was deferred, e.g. by a cancelled [`Self::execute_event_cancellable`].
+    /// In [`CascadeMode::Deferred`], only the events of the currently deferred actions are executed,
+    /// and the actions they produce are deferred again for the next call.
+    pub fn pump(&mut self) {
+        let mut actions = std::mem::take(&mut self.trigger_system.action_buffer);
+        self.trigger_system
+            .execute_pending_actions(&mut actions, &AtomicBool::new(false));
+        self.action_queue.extend(actions.drain(..));
+        self.trigger_system.action_buffer = actions;
+    }
+
+    /// Returns true if there are actions whose events were deferred and can be executed by [`Self::pump`].
+    pub fn has_pending_events(&self) -> bool {
+        !self.trigger_system.pending_actions.is_empty()
     }
 
     pub fn execute_events<'events>(&mut self, events: impl IntoIterator<Item = &'events Event>)
@@ -157,139 +821,2069 @@ impl<Event: TriggerEvent> CompiledTriggers<Event> {
     }
 
     pub fn consume_action(&mut self) -> Option<Event::Action> {
-        self.action_queue.pop_front()
+        let action = self.action_queue.pop_front()?;
+        if self.transformed_actions > 0 {
+            self.transformed_actions -= 1;
+            Some(action)
+        } else {
+            Some(self.action_transformers.apply(action))
+        }
     }
 
-    pub fn consume_all_actions(&mut self) -> impl '_ + Iterator<Item = Event::Action> {
-        self.action_queue.drain(0..self.action_queue.len())
+    /// Returns the number of pending actions.
+    pub fn action_queue_len(&self) -> usize {
+        self.action_queue.len()
     }
 
-    pub fn progress(&self, handle: TriggerHandle) -> Option<(f64, f64)> {
-        self.trigger_system
-            .triggers
-            .get(handle.0)
-            .map(|trigger| trigger.progress())
+    /// Returns true if there are pending actions.
+    pub fn has_pending_actions(&self) -> bool {
+        !self.action_queue.is_empty()
     }
-}
 
-impl<Event: TriggerEvent> TriggerSystem<Event> {
-    fn execute_event(&mut self, event: &Event) -> Vec<Event::Action> {
-        let mut all_actions = Vec::new();
-        let identifier = event.identifier();
-        let trigger_indices: Vec<_> = self
-            .subscriptions
-            .get(&identifier)
-            .unwrap_or(&BTreeMap::new())
-            .keys()
-            .copied()
-            .collect();
-        for trigger_index in trigger_indices {
-            let trigger = &mut self.triggers[trigger_index];
-            let (mut actions, trigger_condition_updates) = trigger.execute_event(event);
-            all_actions.append(&mut actions);
-
-            for trigger_condition_update in trigger_condition_updates {
-                match trigger_condition_update {
-                    TriggerConditionUpdate::Subscribe(identifier) => {
-                        self.subscriptions.insert(identifier.clone(), trigger_index);
-                    }
-                    TriggerConditionUpdate::Unsubscribe(identifier) => {
-                        self.subscriptions
-                            .remove_key_value(&identifier, &trigger_index);
-                    }
-                }
-            }
-        }
+    /// Returns the next pending action without consuming it.
+    /// Action transformers are applied when an action is consumed or passed to the predicate of e.g. [`Self::consume_action_if`],
+    /// so the returned action is untransformed unless such a predicate received it already.
+    pub fn peek_action(&self) -> Option<&Event::Action> {
+        self.action_queue.front()
+    }
 
-        let mut i = 0;
-        while i < all_actions.len() {
-            all_actions.append(&mut self.execute_event(&Event::from(all_actions[i].clone())));
-            i += 1;
-        }
+    /// Returns an iterator over all pending actions in order, without consuming them.
+    pub fn peek_actions(&self) -> impl '_ + Iterator<Item = &Event::Action> {
+        self.action_queue.iter()
+    }
 
-        all_actions
+    pub fn consume_all_actions(&mut self) -> impl '_ + Iterator<Item = Event::Action> {
+        let action_transformers = &mut self.action_transformers;
+        let transformed_actions = std::mem::take(&mut self.transformed_actions);
+        self.action_queue
+            .drain(0..self.action_queue.len())
+            .enumerate()
+            .map(move |(index, action)| {
+                if index < transformed_actions {
+                    action
+                } else {
+                    action_transformers.apply(action)
+                }
+            })
     }
-}
 
-impl<Event, Action> Trigger<Event, Action> {
-    pub fn new(id_str: String, condition: TriggerCondition<Event>, actions: Vec<Action>) -> Self {
-        Self {
-            id_str,
-            condition,
-            actions,
-        }
+    /// Consumes all queued actions in order and appends them to `out`, such that its allocation can be reused.
+    /// Unlike [`Self::consume_all_actions`], no actions are lost if the result is not fully iterated.
+    pub fn drain_actions_into(&mut self, out: &mut Vec<Event::Action>) {
+        out.reserve(self.action_queue.len());
+        out.extend(self.consume_all_actions());
     }
 
-    pub fn compile<
-        EventCompiler: Fn(Event) -> CompiledEvent,
-        CompiledEvent: TriggerEvent,
-        ActionCompiler: Fn(Action) -> CompiledEvent::Action,
-    >(
-        self,
-        event_compiler: &EventCompiler,
-        action_compiler: &ActionCompiler,
-    ) -> CompiledTrigger<CompiledEvent> {
-        CompiledTrigger {
-            id_str: self.id_str,
-            condition: self.condition.compile(event_compiler),
-            actions: Some(self.actions.into_iter().map(action_compiler).collect()),
+    /// Consumes actions from the front of the action queue for as long as `predicate` returns `true`.
+    /// The first action for which `predicate` returns `false` stays in the queue.
+    ///
+    /// The predicate receives the actions as they would be consumed, i.e. after the action transformers were applied.
+    /// Each action is transformed only once, the action that stays in the queue keeps its transformed value.
+    pub fn drain_actions_while<F: FnMut(&Event::Action) -> bool>(
+        &mut self,
+        mut predicate: F,
+    ) -> Vec<Event::Action> {
+        let mut result = Vec::new();
+        while self.transform_front(1) && predicate(&self.action_queue[0]) {
+            result.extend(self.consume_action());
         }
+        result
     }
-}
 
-impl<Event: TriggerEvent> CompiledTrigger<Event> {
-    pub fn new(
-        id_str: String,
-        condition: CompiledTriggerCondition<Event>,
-        actions: Vec<Event::Action>,
-    ) -> Self {
-        Self {
-            id_str,
-            condition,
-            actions: Some(actions),
+    /// Consumes the next action if `predicate` returns `true` for it.
+    /// Otherwise, the action queue is left untouched and `None` is returned.
+    ///
+    /// Like in [`Self::drain_actions_while`], the predicate receives the action after the action transformers were applied.
+    pub fn consume_action_if<F: FnOnce(&Event::Action) -> bool>(
+        &mut self,
+        predicate: F,
+    ) -> Option<Event::Action> {
+        if self.transform_front(1) && predicate(&self.action_queue[0]) {
+            self.consume_action()
+        } else {
+            None
         }
     }
 
-    pub fn subscriptions(&self) -> Vec<Event::Identifier> {
-        self.condition.subscriptions()
+    /// Keeps only the queued actions for which `predicate` returns `true`, and drops the others without consuming them.
+    /// The order of the remaining actions is preserved.
+    ///
+    /// Like in [`Self::drain_actions_while`], the predicate receives the actions after the action transformers were applied.
+    /// The remaining actions keep their transformed values.
+    pub fn retain_actions<F: FnMut(&Event::Action) -> bool>(&mut self, predicate: F) {
+        self.transform_front(self.action_queue.len());
+        self.action_queue.retain(predicate);
+        self.transformed_actions = self.action_queue.len();
     }
 
-    pub fn execute_event(
+    /// Applies the action transformers to the first `count` queued actions that were not transformed yet.
+    /// Returns false if there are fewer than `count` queued actions.
+    fn transform_front(&mut self, count: usize) -> bool {
+        if self.action_queue.len() < count {
+            return false;
+        }
+        while self.transformed_actions < count {
+            let action = &mut self.action_queue[self.transformed_actions];
+            // The transformers take the action by value, so it is cloned out of the queue.
+            *action = self.action_transformers.apply(action.clone());
+            self.transformed_actions += 1;
+        }
+        true
+    }
+
+    /// Registers a function that is applied to each action when it is consumed.
+    /// Multiple transformers are applied in the order in which they were registered.
+    ///
+    /// Transformers are not serialized, and clones of this trigger system share the same transformers.
+    pub fn add_action_transformer<F: FnMut(Event::Action) -> Event::Action + Send + 'static>(
         &mut self,
-        event: &Event,
-    ) -> (
-        Vec<Event::Action>,
-        Vec<TriggerConditionUpdate<Event::Identifier>>,
+        transformer: F,
     ) {
-        let (trigger_condition_updates, result, _) = self.condition.execute_event(event);
-        if result {
-            (self.actions.take().unwrap(), trigger_condition_updates)
-        } else {
-            (Default::default(), trigger_condition_updates)
-        }
+        self.action_transformers
+            .transformers
+            .push(Arc::new(Mutex::new(transformer)));
     }
 
-    pub fn progress(&self) -> (f64, f64) {
-        (
-            self.condition.current_progress(),
-            self.condition.required_progress(),
-        )
+    /// Counts the queued actions grouped by the given key function, without consuming them.
+    pub fn action_histogram<K: Ord, F: Fn(&Event::Action) -> K>(
+        &self,
+        key: F,
+    ) -> BTreeMap<K, usize> {
+        let mut histogram = BTreeMap::new();
+        for action in &self.action_queue {
+            *histogram.entry(key(action)).or_insert(0) += 1;
+        }
+        histogram
     }
 
-    pub fn condition(&self) -> &CompiledTriggerCondition<Event> {
-        &self.condition
+    /// Moves all pending actions of `other` to the end of the action queue of `self`.
+    /// The triggers of `other` are left untouched.
+    ///
+    /// The moved actions are transformed by the action transformers of `self` when they are consumed,
+    /// except for those that `other` has transformed already while all actions of `self` are transformed.
+    pub fn absorb_actions(&mut self, other: &mut CompiledTriggers<Event>) {
+        if self.transformed_actions == self.action_queue.len() {
+            self.transformed_actions += other.transformed_actions;
+        }
+        other.transformed_actions = 0;
+        self.action_queue.append(&mut other.action_queue);
     }
 
-    pub fn actions(&self) -> &[Event::Action] {
-        self.actions.as_deref().unwrap_or(&[])
+    /// Adds a trigger that fires the given actions on the next event with the given identifier.
+    /// The trigger removes itself after firing, after which its handle becomes invalid.
+    pub fn once(
+        &mut self,
+        identifier: Event::Identifier,
+        actions: Vec<Event::Action>,
+    ) -> TriggerHandle {
+        let condition = CompiledTriggerCondition::new(CompiledTriggerConditionKind::EventCount {
+            identifier,
+            count: 0,
+            required: 1,
+        });
+        let trigger = CompiledTrigger {
+            once: true,
+            ..CompiledTrigger::new(String::new(), condition, actions)
+        };
+        TriggerHandle(self.trigger_system.insert_trigger(trigger))
+    }
+
+    /// Returns the handle of the trigger at the given index in the vector of triggers this trigger system was created from.
+    /// Triggers added later get the following indices in order of addition.
+    /// Returns `None` if there is no trigger with the given index, or if it was removed.
+    pub fn handle_for_index(&self, index: usize) -> Option<TriggerHandle> {
+        self.trigger_system
+            .trigger(index)
+            .is_some()
+            .then(|| TriggerHandle(index))
+    }
+
+    /// Adds a trigger to this trigger system and returns its handle.
+    /// Like in [`Self::new`], if the trigger is completed already, its actions and their cascade are added to the action queue immediately.
+    pub fn add_trigger(&mut self, trigger: CompiledTrigger<Event>) -> TriggerHandle {
+        let handle = TriggerHandle(self.trigger_system.insert_trigger(trigger));
+        for tag in self.trigger_system.triggers[handle.0]
+            .as_ref()
+            .unwrap()
+            .tags()
+        {
+            self.tag_index.entry(tag.clone()).or_default().push(handle);
+        }
+
+        self.flush_trigger(handle);
+        handle
+    }
+
+    /// Resets the trigger with the given handle to its initial state, such that it can fire again.
+    /// Like in [`Self::add_trigger`], if the condition is completed from the start, the actions and their cascade are added to the action queue immediately.
+    /// Does nothing if the handle is invalid or the trigger was removed.
+    pub fn reset_trigger(&mut self, handle: TriggerHandle) {
+        let trigger = match self.trigger_system.triggers.get_mut(handle.0) {
+            Some(Some(trigger)) => trigger,
+            _ => return,
+        };
+        let trigger_condition_updates = trigger.reset();
+        if trigger.enabled {
+            self.trigger_system
+                .apply_trigger_condition_updates(handle.0, trigger_condition_updates);
+        }
+        self.trigger_system.update_active(handle.0);
+        self.trigger_system.mark_advanced(handle.0);
+
+        self.flush_trigger(handle);
+    }
+
+    /// Enables or disables the trigger with the given handle.
+    /// A disabled trigger keeps its state, but is unsubscribed from its events and does not fire.
+    /// When it is enabled again, it is subscribed again, and if its condition completed in the meantime, e.g. by [`Self::reset_trigger`],
+    /// its actions and their cascade are added to the action queue immediately.
+    /// Does nothing if the handle is invalid or the trigger was removed.
+    pub fn set_enabled(&mut self, handle: TriggerHandle, enabled: bool) {
+        let trigger = match self.trigger_system.triggers.get_mut(handle.0) {
+            Some(Some(trigger)) => trigger,
+            _ => return,
+        };
+        if trigger.enabled == enabled {
+            return;
+        }
+        trigger.enabled = enabled;
+        let trigger_condition_updates = trigger
+            .trigger_subscriptions()
+            .into_iter()
+            .map(if enabled {
+                TriggerConditionUpdate::Subscribe
+            } else {
+                TriggerConditionUpdate::Unsubscribe
+            })
+            .collect();
+        self.trigger_system
+            .apply_trigger_condition_updates(handle.0, trigger_condition_updates);
+
+        if enabled {
+            self.flush_trigger(handle);
+        }
+    }
+
+    /// Limits the depth of cascades, where the events created from the actions produced by an event have a depth one higher than that event.
+    /// Events passed to the trigger system have depth zero, so with a limit of zero, actions are not executed as events at all.
+    /// Actions whose events would exceed the limit are still added to the action queue, but their events are dropped,
+    /// which is counted by [`Self::dropped_cascade_events`]. `None` removes the limit, which is the default.
+    pub fn set_max_cascade(&mut self, limit: Option<usize>) {
+        self.trigger_system.max_cascade = limit;
+    }
+
+    /// Sets when the events created from actions are executed, which is [`CascadeMode::Immediate`] by default.
+    /// In [`CascadeMode::Deferred`], actions are still added to the action queue right away,
+    /// but their events are only executed by [`Self::pump`], one layer of the cascade per call.
+    /// The limit set by [`Self::set_max_cascade`] only applies to immediate cascades.
+    pub fn set_cascade_mode(&mut self, mode: CascadeMode) {
+        self.trigger_system.cascade_mode = mode;
+    }
+
+    pub fn cascade_mode(&self) -> CascadeMode {
+        self.trigger_system.cascade_mode
+    }
+
+    /// Sets the order in which the events of an immediate cascade are executed, which is [`CascadeOrder::DepthFirst`] by default.
+    /// Actions are queued in the order in which they are produced, so the order affects the action queue as well.
+    /// If a trigger produces the actions `a` and `b`, whose events produce `c` and `d`, and the event of `c` produces `e`,
+    /// then the actions are queued in the order `a`, `b`, `c`, `e`, `d` depth-first, and `a`, `b`, `c`, `d`, `e` breadth-first.
+    pub fn set_cascade_order(&mut self, order: CascadeOrder) {
+        self.trigger_system.cascade_order = order;
+    }
+
+    pub fn cascade_order(&self) -> CascadeOrder {
+        self.trigger_system.cascade_order
+    }
+
+    /// If set, an action that equals an action produced earlier by the same event is dropped, so it is neither queued nor executed as an event.
+    /// This applies to each event separately, including the events created from actions during a cascade.
+    /// The setting is not serialized.
+    pub fn set_coalesce_per_event(&mut self, coalesce: bool)
+    where
+        Event::Action: PartialEq,
+    {
+        self.trigger_system.coalesce_actions = if coalesce {
+            Some(<Event::Action as PartialEq>::eq)
+        } else {
+            None
+        };
+    }
+
+    pub fn coalesce_per_event(&self) -> bool {
+        self.trigger_system.coalesce_actions.is_some()
+    }
+
+    /// Returns the number of events that were dropped because they exceeded the limit set by [`Self::set_max_cascade`].
+    pub fn dropped_cascade_events(&self) -> usize {
+        self.trigger_system.dropped_cascade_events
+    }
+
+    /// Removes the trigger with the given handle, such that it does not receive any events anymore.
+    /// The handles of all other triggers stay valid, and the handle of the removed trigger is never reused.
+    /// Returns `None` if the handle is invalid or the trigger was removed already.
+    pub fn remove_trigger(&mut self, handle: TriggerHandle) -> Option<CompiledTrigger<Event>> {
+        let trigger = self.trigger_system.remove_trigger(handle.0)?;
+        for tag in &trigger.tags {
+            if let Some(handles) = self.tag_index.get_mut(tag) {
+                handles.retain(|other| *other != handle);
+                if handles.is_empty() {
+                    self.tag_index.remove(tag);
+                }
+            }
+        }
+        Some(trigger)
+    }
+
+    pub fn progress(&self, handle: TriggerHandle) -> Option<(f64, f64)> {
+        self.trigger_system
+            .trigger(handle.0)
+            .map(|trigger| trigger.progress())
+    }
+
+    /// Returns the number of completed and required sub-conditions of the given trigger, if its condition is an `any_n` condition.
+    /// Returns `None` if the condition is of a different kind, the handle is invalid or the trigger was removed.
+    pub fn any_n_progress(&self, handle: TriggerHandle) -> Option<(usize, usize)> {
+        self.trigger_system
+            .trigger(handle.0)
+            .and_then(|trigger| trigger.condition.any_n_progress())
+    }
+
+    /// Sets the number of sub-conditions required by the given trigger, if its condition is an `any_n` condition,
+    /// see [`CompiledTriggerCondition::set_any_n_threshold`].
+    /// The trigger is resubscribed according to its recomputed condition, and if the condition completed,
+    /// the actions and their cascade are added to the action queue immediately.
+    /// Returns false if the condition is of a different kind, the handle is invalid or the trigger was removed.
+    pub fn set_any_n_threshold(&mut self, handle: TriggerHandle, threshold: usize) -> bool {
+        let trigger_system = &mut self.trigger_system;
+        let trigger = match trigger_system.triggers.get_mut(handle.0) {
+            Some(Some(trigger)) if trigger.condition.any_n_progress().is_some() => trigger,
+            _ => return false,
+        };
+        let mut trigger_condition_updates: Vec<_> = trigger
+            .active_subscriptions()
+            .into_iter()
+            .map(TriggerConditionUpdate::Unsubscribe)
+            .collect();
+        trigger.condition.set_any_n_threshold(threshold);
+        trigger_condition_updates.extend(
+            trigger
+                .active_subscriptions()
+                .into_iter()
+                .map(TriggerConditionUpdate::Subscribe),
+        );
+        trigger_system.apply_trigger_condition_updates(handle.0, trigger_condition_updates);
+        trigger_system.update_active(handle.0);
+        trigger_system.mark_advanced(handle.0);
+
+        self.flush_trigger(handle);
+        true
+    }
+
+    /// Returns the actions the given trigger produces when it fires, e.g. to preview the rewards of a quest.
+    /// Returns `None` if the trigger fired already and does not repeat, the handle is invalid or the trigger was removed.
+    pub fn actions(&self, handle: TriggerHandle) -> Option<&[Event::Action]> {
+        self.trigger_system
+            .trigger(handle.0)
+            .filter(|trigger| !trigger.fired)
+            .map(|trigger| trigger.actions.as_slice())
+    }
+
+    /// Returns true if the condition of the given trigger is completed, or `None` if the handle is invalid or the trigger was removed.
+    pub fn is_completed(&self, handle: TriggerHandle) -> Option<bool> {
+        self.trigger_system
+            .trigger(handle.0)
+            .map(|trigger| trigger.condition.completed())
+    }
+
+    /// Returns the sums of the current and the required progress of all triggers.
+    /// Completed triggers contribute their required progress to both sums.
+    /// Triggers with a required progress of zero, such as `none()`, contribute nothing,
+    /// so the required progress is only zero if all triggers are trivially completed.
+    pub fn total_progress(&self) -> (f64, f64) {
+        self.iter_triggers()
+            .fold((0.0, 0.0), |(current, required), (_, trigger)| {
+                let (trigger_current, trigger_required) = trigger.progress();
+                let trigger_current = if trigger.completed() {
+                    trigger_required
+                } else {
+                    trigger_current
+                };
+                (current + trigger_current, required + trigger_required)
+            })
+    }
+
+    /// Returns the handle of the first trigger with the given `id_str`, or `None` if there is no such trigger.
+    /// Removed triggers are skipped.
+    pub fn handle_by_id(&self, id_str: &str) -> Option<TriggerHandle> {
+        self.trigger_system
+            .triggers
+            .iter()
+            .position(|trigger| {
+                trigger
+                    .as_ref()
+                    .map_or(false, |trigger| trigger.id_str == id_str)
+            })
+            .map(TriggerHandle)
+    }
+
+    /// Registers the function used by all `predicate` conditions with the given name, replacing any previously registered one.
+    /// This applies to the existing triggers as well as to triggers added later.
+    /// Registered predicates are not serialized, so they need to be registered again after deserializing.
+    ///
+    /// The conditions using the predicate are identical in what they compute from an event, so they share its evaluation:
+    /// the predicate is called at most once per event, and its result is passed to all of them.
+    pub fn register_predicate(
+        &mut self,
+        name: impl Into<String>,
+        predicate: impl Fn(&Event) -> bool + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        let evaluation = self
+            .trigger_system
+            .predicates
+            .get(&name)
+            .and_then(EventPredicate::evaluation)
+            .unwrap_or(self.trigger_system.predicates.len());
+        let predicate = EventPredicate::new(predicate).with_evaluation(evaluation);
+        for trigger in self.trigger_system.triggers.iter_mut().flatten() {
+            trigger.condition.register_predicate(&name, &predicate);
+        }
+        self.trigger_system.predicates.insert(name, predicate);
+    }
+
+    /// Registers the function used by all `accumulate` and `delta_geq` conditions with the given name, replacing any previously registered one.
+    /// Like for [`Self::register_predicate`], this applies to triggers added later, but not to deserialized triggers,
+    /// and the extractor is called at most once per event.
+    pub fn register_extractor(
+        &mut self,
+        name: impl Into<String>,
+        extractor: impl Fn(&Event) -> f64 + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        let evaluation = self
+            .trigger_system
+            .extractors
+            .get(&name)
+            .and_then(EventExtractor::evaluation)
+            .unwrap_or(self.trigger_system.extractors.len());
+        let extractor = EventExtractor::new(extractor).with_evaluation(evaluation);
+        for trigger in self.trigger_system.triggers.iter_mut().flatten() {
+            trigger.condition.register_extractor(&name, &extractor);
+        }
+        self.trigger_system.extractors.insert(name, extractor);
+    }
+
+    /// Changes the `id_str` of the trigger with the given handle.
+    /// Returns false and changes nothing if the handle is invalid, the trigger was removed, or another trigger already has the new id.
+    pub fn set_trigger_id(&mut self, handle: TriggerHandle, new_id: impl Into<String>) -> bool {
+        let new_id = new_id.into();
+        if self
+            .handle_by_id(&new_id)
+            .map_or(false, |other| other != handle)
+        {
+            return false;
+        }
+        match self.trigger_system.triggers.get_mut(handle.0) {
+            Some(Some(trigger)) => {
+                trigger.id_str = new_id;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the progress of the first trigger with the given `id_str`, see [`Self::handle_by_id`].
+    pub fn progress_by_id(&self, id_str: &str) -> Option<(f64, f64)> {
+        self.progress(self.handle_by_id(id_str)?)
+    }
+
+    /// Iterates over all triggers with their handles, in order of their handles.
+    /// Removed triggers are skipped.
+    ///
+    /// ```
+    /// use event_trigger_action_system::{event_count, none, Trigger, TriggerEvent, Triggers};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct Killed;
+    ///
+    /// impl From<()> for Killed {
+    ///     fn from(_: ()) -> Self {
+    ///         Killed
+    ///     }
+    /// }
+    ///
+    /// impl TriggerEvent for Killed {
+    ///     type Action = ();
+    ///     type Identifier = ();
+    ///
+    ///     fn identifier(&self) -> Self::Identifier {}
+    ///
+    ///     fn value_geq(&self, _other: &Self) -> Option<bool> {
+    ///         None
+    ///     }
+    ///
+    ///     fn value_geq_progress(&self, _other: &Self) -> Option<f64> {
+    ///         None
+    ///     }
+    /// }
+    ///
+    /// let mut triggers = Triggers::new(vec![
+    ///     Trigger::new("intro".to_string(), none(), vec![]),
+    ///     Trigger::new("wolves".to_string(), event_count(Killed, 3), vec![()]),
+    /// ])
+    /// .compile(&|event| event, &|action| action);
+    /// triggers.execute_event(&Killed);
+    ///
+    /// for (handle, trigger) in triggers.iter_triggers() {
+    ///     let (current, required) = trigger.progress();
+    ///     println!("{handle:?} {}: {current}/{required}", trigger.id_str());
+    /// }
+    /// let progress: Vec<_> = triggers
+    ///     .iter_triggers()
+    ///     .map(|(_, trigger)| trigger.progress())
+    ///     .collect();
+    /// assert_eq!(progress, vec![(0.0, 0.0), (1.0, 3.0)]);
+    /// ```
+    pub fn iter_triggers(
+        &self,
+    ) -> impl '_ + Iterator<Item = (TriggerHandle, &CompiledTrigger<Event>)> {
+        self.trigger_system
+            .triggers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, trigger)| Some((TriggerHandle(index), trigger.as_ref()?)))
+    }
+
+    /// Iterates over the triggers whose condition is not completed, in order of their handles.
+    /// Unlike filtering [`Self::iter_triggers`], this only visits the active triggers.
+    /// Failed triggers count as active, and repeating triggers stay active when they fire.
+    pub fn iter_active_triggers(
+        &self,
+    ) -> impl '_ + Iterator<Item = (TriggerHandle, &CompiledTrigger<Event>)> {
+        self.trigger_system.active.iter().map(move |&index| {
+            (
+                TriggerHandle(index),
+                self.trigger_system.trigger(index).unwrap(),
+            )
+        })
+    }
+
+    /// Returns the number of triggers that can still make progress, i.e. that are enabled and neither completed nor failed.
+    pub fn active_trigger_count(&self) -> usize {
+        self.iter_active_triggers()
+            .filter(|(_, trigger)| trigger.enabled() && trigger.status() == TriggerStatus::Active)
+            .count()
+    }
+
+    /// Returns the status, progress and subscriptions of all triggers in order of their handles.
+    /// Removed triggers are skipped.
+    pub fn quest_summary(&self) -> Vec<QuestSummary<Event::Identifier>> {
+        self.iter_triggers()
+            .map(|(handle, trigger)| trigger.summary(handle))
+            .collect()
+    }
+
+    /// Compares the triggers of this and the other system by handle, returning the triggers whose
+    /// status, progress or subscriptions differ, or that exist in only one of the systems.
+    /// This is meant for systems compiled from the same definitions, e.g. to find where a saved and a replayed game diverged.
+    /// Subscriptions are compared regardless of their order.
+    pub fn diff(&self, other: &Self) -> Vec<TriggerDiff<Event::Identifier>> {
+        let summary = |triggers: &Self, index| {
+            triggers.trigger_system.trigger(index).map(|trigger| {
+                let mut summary = trigger.summary(TriggerHandle(index));
+                summary.waiting_on.sort();
+                summary
+            })
+        };
+
+        (0..self
+            .trigger_system
+            .triggers
+            .len()
+            .max(other.trigger_system.triggers.len()))
+            .filter_map(|index| {
+                let left = summary(self, index);
+                let right = summary(other, index);
+                if left == right {
+                    None
+                } else {
+                    Some(TriggerDiff {
+                        handle: TriggerHandle(index),
+                        left,
+                        right,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Renders the progress of all triggers as CSV with the columns `id_str,status,current,required,completion_fraction`, e.g. for balancing spreadsheets.
+    /// There is a header row, followed by one row per trigger in order of their handles. Removed triggers are skipped.
+    /// The completion fraction of a trigger with a required progress of zero is one.
+    pub fn progress_csv(&self) -> String {
+        let mut csv = "id_str,status,current,required,completion_fraction\n".to_string();
+        for (_, trigger) in self.iter_triggers() {
+            let id_str = if trigger.id_str.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", trigger.id_str.replace('"', "\"\""))
+            } else {
+                trigger.id_str.clone()
+            };
+            let status = match trigger.status() {
+                TriggerStatus::Active => "active",
+                TriggerStatus::Completed => "completed",
+                TriggerStatus::Failed => "failed",
+            };
+            let (current, required) = trigger.progress();
+            let completion_fraction = if required > 0.0 {
+                current / required
+            } else {
+                1.0
+            };
+            csv.push_str(&format!(
+                "{id_str},{status},{current},{required},{completion_fraction}\n"
+            ));
+        }
+        csv
+    }
+
+    /// Renders the dependencies between the triggers as a Graphviz DOT graph.
+    /// The nodes are the triggers, labelled by their `id_str`, and there is an edge from one trigger to another
+    /// if the event of one of the actions of the first trigger has an identifier that one of the leaf conditions
+    /// or `fail_on` events of the second trigger has, regardless of the current state of the triggers.
+    /// Conditions that receive all events, like [`crate::predicate_any_event`], do not have incoming edges.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph triggers {\n".to_string();
+        for (handle, trigger) in self.iter_triggers() {
+            let label = trigger.id_str.replace('\\', "\\\\").replace('"', "\\\"");
+            dot.push_str(&format!("    t{} [label=\"{label}\"];\n", handle.0));
+        }
+
+        let mut subscribers = BTreeMap::<_, BTreeSet<_>>::new();
+        for (handle, trigger) in self.iter_triggers() {
+            for identifier in trigger
+                .condition
+                .leaf_identifiers()
+                .into_iter()
+                .chain(trigger.fail_on.iter().cloned())
+            {
+                subscribers.entry(identifier).or_default().insert(handle.0);
+            }
+        }
+        let edges: BTreeSet<_> = self
+            .iter_triggers()
+            .flat_map(|(handle, trigger)| {
+                let subscribers = &subscribers;
+                trigger.actions.iter().flat_map(move |action| {
+                    let identifier = Event::action_identifier(action)
+                        .unwrap_or_else(|| Event::from(action.clone()).identifier());
+                    subscribers
+                        .get(&identifier)
+                        .into_iter()
+                        .flatten()
+                        .map(move |subscriber| (handle.0, *subscriber))
+                })
+            })
+            .collect();
+        for (source, target) in edges {
+            dot.push_str(&format!("    t{source} -> t{target};\n"));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns the handles of all triggers with the given tag.
+    pub fn handles_by_tag(&self, tag: &str) -> Vec<TriggerHandle> {
+        self.tag_index.get(tag).cloned().unwrap_or_default()
+    }
+
+    /// Returns the tags of the given trigger, or `None` if the handle is invalid.
+    pub fn tags_of(&self, handle: TriggerHandle) -> Option<&[String]> {
+        self.trigger_system
+            .trigger(handle.0)
+            .map(|trigger| trigger.tags())
+    }
+
+    /// Returns the statistics collected since the creation of this trigger system.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> &Stats {
+        &self.trigger_system.stats
+    }
+
+    /// Resets all statistics to zero.
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.trigger_system.stats = Stats::default();
+    }
+
+    /// Captures the runtime state of this trigger system, i.e. the state of the triggers, their subscriptions and the action queue.
+    /// Unlike cloning the whole system, this does not clone the ids, tags and actions of the triggers.
+    pub fn snapshot(&self) -> TriggerSnapshot<Event>
+    where
+        Event: Clone,
+    {
+        let trigger_system = &self.trigger_system;
+        TriggerSnapshot {
+            triggers: trigger_system
+                .triggers
+                .iter()
+                .map(|trigger| trigger.as_ref().map(CompiledTrigger::state))
+                .collect(),
+            subscriptions: trigger_system.subscriptions.clone(),
+            active: trigger_system.active.clone(),
+            wildcard_subscriptions: trigger_system.wildcard_subscriptions.clone(),
+            pending_actions: trigger_system.pending_actions.clone(),
+            pending_action_sources: trigger_system.pending_action_sources.clone(),
+            event_count: trigger_system.event_count,
+            last_advances: trigger_system.last_advances.clone(),
+            dropped_cascade_events: trigger_system.dropped_cascade_events,
+            #[cfg(feature = "stats")]
+            stats: trigger_system.stats.clone(),
+            #[cfg(feature = "metrics")]
+            evaluations: trigger_system.evaluations.clone(),
+            action_queue: self.action_queue.clone(),
+            transformed_actions: self.transformed_actions,
+        }
+    }
+
+    /// Restores the runtime state captured by [`Self::snapshot`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if triggers were added or removed since the snapshot was taken.
+    pub fn restore(&mut self, snapshot: TriggerSnapshot<Event>) {
+        let trigger_system = &mut self.trigger_system;
+        assert_eq!(
+            trigger_system.triggers.len(),
+            snapshot.triggers.len(),
+            "triggers were added since the snapshot was taken"
+        );
+        for (trigger, state) in trigger_system.triggers.iter_mut().zip(snapshot.triggers) {
+            match (trigger, state) {
+                (Some(trigger), Some(state)) => trigger.set_state(state),
+                (None, None) => {}
+                _ => panic!("triggers were removed since the snapshot was taken"),
+            }
+        }
+        trigger_system.subscriptions = snapshot.subscriptions;
+        trigger_system.active = snapshot.active;
+        trigger_system.wildcard_subscriptions = snapshot.wildcard_subscriptions;
+        trigger_system.pending_actions = snapshot.pending_actions;
+        trigger_system.pending_action_sources = snapshot.pending_action_sources;
+        trigger_system.event_count = snapshot.event_count;
+        trigger_system.last_advances = snapshot.last_advances;
+        trigger_system.dropped_cascade_events = snapshot.dropped_cascade_events;
+        #[cfg(feature = "stats")]
+        {
+            trigger_system.stats = snapshot.stats;
+        }
+        #[cfg(feature = "metrics")]
+        {
+            trigger_system.evaluations = snapshot.evaluations;
+        }
+        self.action_queue = snapshot.action_queue;
+        self.transformed_actions = snapshot.transformed_actions;
+    }
+
+    /// Returns the runtime state of the trigger with the given handle, e.g. to synchronize a single trigger over the network.
+    /// Returns `None` if the handle is invalid or the trigger was removed.
+    pub fn trigger_state(&self, handle: TriggerHandle) -> Option<TriggerState<Event>>
+    where
+        Event: Clone,
+    {
+        self.trigger_system
+            .trigger(handle.0)
+            .map(CompiledTrigger::state)
+    }
+
+    /// Installs the runtime state captured by [`Self::trigger_state`], e.g. from another trigger system with the same triggers.
+    /// The trigger is resubscribed according to its new state, and the registered predicates and extractors are applied to its condition.
+    /// Like in [`Self::reset_trigger`], if the condition is completed but the trigger did not fire yet,
+    /// the actions and their cascade are added to the action queue immediately.
+    /// Does nothing if the handle is invalid or the trigger was removed.
+    pub fn apply_trigger_state(&mut self, handle: TriggerHandle, state: TriggerState<Event>) {
+        let trigger_system = &mut self.trigger_system;
+        let trigger = match trigger_system.triggers.get_mut(handle.0) {
+            Some(Some(trigger)) => trigger,
+            _ => return,
+        };
+        let mut trigger_condition_updates: Vec<_> = trigger
+            .active_subscriptions()
+            .into_iter()
+            .map(TriggerConditionUpdate::Unsubscribe)
+            .collect();
+        trigger.set_state(state);
+        for (name, predicate) in &trigger_system.predicates {
+            trigger.condition.register_predicate(name, predicate);
+        }
+        for (name, extractor) in &trigger_system.extractors {
+            trigger.condition.register_extractor(name, extractor);
+        }
+        trigger_condition_updates.extend(
+            trigger
+                .active_subscriptions()
+                .into_iter()
+                .map(TriggerConditionUpdate::Subscribe),
+        );
+        trigger_system.apply_trigger_condition_updates(handle.0, trigger_condition_updates);
+        trigger_system.update_active(handle.0);
+        trigger_system.mark_advanced(handle.0);
+
+        self.flush_trigger(handle);
+    }
+
+    /// Returns the `top_n` triggers that received the most events since the creation of this trigger system, with their number of events.
+    /// Triggers with more events come first, and triggers with the same number of events are ordered by their handles.
+    /// Removed triggers and triggers that never received an event are skipped.
+    #[cfg(feature = "metrics")]
+    pub fn hottest_triggers(&self, top_n: usize) -> Vec<(TriggerHandle, u64)> {
+        let mut evaluations: Vec<_> = self
+            .trigger_system
+            .evaluations
+            .iter()
+            .enumerate()
+            .filter(|(index, evaluations)| {
+                **evaluations > 0 && self.trigger_system.trigger(*index).is_some()
+            })
+            .map(|(index, evaluations)| (TriggerHandle(index), *evaluations))
+            .collect();
+        evaluations.sort_by_key(|(handle, evaluations)| (Reverse(*evaluations), *handle));
+        evaluations.truncate(top_n);
+        evaluations
+    }
+
+    /// Returns an estimate of the memory used by the subscription maps, i.e. a key per identifier and a trigger index and count per subscribed trigger,
+    /// as well as the trigger indices of the triggers receiving all events.
+    fn subscriptions_size(&self) -> usize {
+        let trigger_system = &self.trigger_system;
+        // The multimap cannot be iterated, but identifiers without subscribers are removed from it, see [`TriggerSystem::unsubscribe`].
+        // Hence its keys are exactly the identifiers that the triggers are subscribed to, and the entries are counted in the multimap itself.
+        let identifiers: BTreeSet<_> = trigger_system
+            .triggers
+            .iter()
+            .flatten()
+            .flat_map(|trigger| trigger.active_subscriptions())
+            .collect();
+        identifiers
+            .iter()
+            .filter_map(|identifier| trigger_system.subscriptions.get(identifier))
+            .map(|subscribers| {
+                size_of::<Event::Identifier>() + subscribers.len() * 2 * size_of::<usize>()
+            })
+            .sum::<usize>()
+            + trigger_system.wildcard_subscriptions.len() * size_of::<usize>()
+    }
+
+    /// Returns an estimate of the memory used by this trigger system.
+    pub fn memory_report(&self) -> MemoryReport {
+        let triggers = &self.trigger_system.triggers;
+        MemoryReport {
+            triggers: size_of::<Self>()
+                + triggers.capacity() * size_of::<Option<CompiledTrigger<Event>>>()
+                + self.trigger_system.active.len() * size_of::<usize>()
+                + triggers
+                    .iter()
+                    .flatten()
+                    .map(|trigger| {
+                        trigger.id_str.capacity()
+                            + trigger.tags.capacity() * size_of::<String>()
+                            + trigger.tags.iter().map(String::capacity).sum::<usize>()
+                            + trigger.actions.capacity() * size_of::<Event::Action>()
+                            + trigger.fail_on.capacity() * size_of::<Event::Identifier>()
+                            + trigger.failure_actions.capacity() * size_of::<Event::Action>()
+                    })
+                    .sum::<usize>(),
+            conditions: triggers
+                .iter()
+                .flatten()
+                .map(|trigger| trigger.condition.heap_size())
+                .sum(),
+            subscriptions: self.subscriptions_size(),
+            action_queue: (self.action_queue.capacity()
+                + self.trigger_system.action_buffer.capacity()
+                + self.trigger_system.pending_actions.capacity())
+                * size_of::<Event::Action>(),
+        }
+    }
+}
+
+/// Deserializes a trigger system and rebuilds the state that is derived from its triggers.
+#[cfg(feature = "serde")]
+fn deserialize_trigger_system<'de, D: Deserializer<'de>, Event: TriggerEvent>(
+    deserializer: D,
+) -> Result<TriggerSystem<Event>, D::Error>
+where
+    TriggerSystem<Event>: Deserialize<'de>,
+{
+    let mut trigger_system = TriggerSystem::deserialize(deserializer)?;
+    if trigger_system.format_version == 0 {
+        // Back then, a trigger fired exactly when its condition completed.
+        for trigger in trigger_system.triggers.iter_mut().flatten() {
+            trigger.fired = trigger.completed();
+        }
+    }
+    trigger_system.format_version = FORMAT_VERSION;
+    trigger_system
+        .last_advances
+        .resize(trigger_system.triggers.len(), trigger_system.event_count);
+    for index in 0..trigger_system.triggers.len() {
+        trigger_system.update_active(index);
+    }
+    if trigger_system.pending_action_sources.len() != trigger_system.pending_actions.len() {
+        // Some pending actions were dropped, so the remaining ones cannot be matched to their sources anymore.
+        trigger_system.pending_action_sources = vec![None; trigger_system.pending_actions.len()];
+    }
+    Ok(trigger_system)
+}
+
+/// Deserializes a list of actions.
+/// While [`CompiledTriggers::deserialize_lenient`] runs, actions that cannot be deserialized are dropped.
+#[cfg(feature = "serde")]
+fn deserialize_actions<'de, D: Deserializer<'de>, Action: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Vec<Action>, D::Error> {
+    if LENIENT.with(Cell::get) {
+        Ok(Vec::<LenientAction<Action>>::deserialize(deserializer)?
+            .into_iter()
+            .filter_map(|action| match action {
+                LenientAction::Known(action) => Some(action),
+                LenientAction::Unknown(_) => None,
+            })
+            .collect())
+    } else {
+        Vec::deserialize(deserializer)
+    }
+}
+
+/// Deserializes the actions of a [`CompiledTrigger`], where `null` is the representation of fired triggers in older versions.
+#[cfg(feature = "serde")]
+fn deserialize_trigger_actions<'de, D: Deserializer<'de>, Action: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Vec<Action>, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// The default of [`CompiledTrigger::enabled`] for triggers serialized by older versions.
+#[cfg(feature = "serde")]
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Deserializes the action queue of [`CompiledTriggers`], see [`deserialize_actions`].
+#[cfg(feature = "serde")]
+fn deserialize_action_queue<'de, D: Deserializer<'de>, Action: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<VecDeque<Action>, D::Error> {
+    deserialize_actions(deserializer).map(VecDeque::from)
+}
+
+#[cfg(feature = "serde")]
+impl<Event: TriggerEvent> CompiledTriggers<Event> {
+    /// Deserializes a trigger system like its `Deserialize` implementation, but drops queued and pending actions that cannot be deserialized,
+    /// e.g. because their enum variant was removed or renamed since the trigger system was serialized.
+    /// This requires a self-describing format such as JSON or RON.
+    /// Unknown actions in the definitions of triggers still cause an error.
+    pub fn deserialize_lenient<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    where
+        Event: Deserialize<'de>,
+    {
+        let _lenient = LenientGuard::new();
+        Self::deserialize(deserializer)
+    }
+}
+
+impl<Event: TriggerEvent> TriggerSystem<Event> {
+    /// Returns the trigger with the given index, or `None` if it does not exist or was removed.
+    fn trigger(&self, index: usize) -> Option<&CompiledTrigger<Event>> {
+        self.triggers.get(index).and_then(Option::as_ref)
+    }
+
+    /// Adds a trigger and subscribes it to its events, returning its index.
+    /// If the trigger is completed already, its actions are not produced.
+    fn insert_trigger(&mut self, mut trigger: CompiledTrigger<Event>) -> usize {
+        for (name, predicate) in &self.predicates {
+            trigger.condition.register_predicate(name, predicate);
+        }
+        for (name, extractor) in &self.extractors {
+            trigger.condition.register_extractor(name, extractor);
+        }
+        let index = self.triggers.len();
+        let triggers = &self.triggers;
+        trigger.condition.resolve_stale_for(&|trigger_id| {
+            triggers
+                .iter()
+                .position(|other| {
+                    other
+                        .as_ref()
+                        .map_or(false, |other| other.id_str == trigger_id)
+                })
+                .or_else(|| (trigger_id == trigger.id_str).then(|| index))
+        });
+        for identifier in trigger.active_subscriptions() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(trigger = index, ?identifier, "subscribe");
+            self.subscriptions.insert(identifier, index);
+        }
+        self.triggers.push(Some(trigger));
+        self.mark_advanced(index);
+        self.update_active(index);
+        self.update_wildcard_subscription(index);
+        index
+    }
+
+    /// Removes the trigger with the given index and unsubscribes it from its events.
+    fn remove_trigger(&mut self, index: usize) -> Option<CompiledTrigger<Event>> {
+        let trigger = self.triggers.get_mut(index)?.take()?;
+        for identifier in trigger.active_subscriptions() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(trigger = index, ?identifier, "unsubscribe");
+            self.unsubscribe(&identifier, index);
+        }
+        self.active.remove(&index);
+        self.wildcard_subscriptions.remove(&index);
+        Some(trigger)
+    }
+
+    /// Removes one subscription of the trigger with the given index to the given identifier.
+    /// If no trigger is subscribed to the identifier anymore, it is removed from the subscriptions, such that they do not grow with every identifier ever subscribed to.
+    fn unsubscribe(&mut self, identifier: &Event::Identifier, index: usize) {
+        self.subscriptions.remove_key_value(identifier, &index);
+        if self
+            .subscriptions
+            .get(identifier)
+            .map_or(false, BTreeMap::is_empty)
+        {
+            self.subscriptions.remove(identifier);
+        }
+    }
+
+    /// Records that the trigger with the given index advanced on the last executed event, or that its state was replaced.
+    fn mark_advanced(&mut self, index: usize) {
+        if self.last_advances.len() <= index {
+            self.last_advances.resize(index + 1, 0);
+        }
+        self.last_advances[index] = self.event_count;
+    }
+
+    /// Adds the trigger with the given index to the active triggers if its condition is not completed, and removes it otherwise.
+    fn update_active(&mut self, index: usize) {
+        if self
+            .trigger(index)
+            .map_or(false, |trigger| !trigger.completed())
+        {
+            self.active.insert(index);
+        } else {
+            self.active.remove(&index);
+        }
+    }
+
+    /// Adds the trigger with the given index to the wildcard subscriptions if it is enabled and its condition needs all events, and removes it otherwise.
+    fn update_wildcard_subscription(&mut self, index: usize) {
+        if self
+            .trigger(index)
+            .map_or(false, |trigger| trigger.subscribes_to_all())
+        {
+            self.wildcard_subscriptions.insert(index);
+        } else {
+            self.wildcard_subscriptions.remove(&index);
+        }
+    }
+
+    /// Consumes the actions of all completed triggers that still hold their actions, appending them and their cascade to `all_actions`.
+    fn flush_ready(&mut self, all_actions: &mut Vec<Event::Action>) {
+        let first_action = all_actions.len();
+        let mut action_sources = Vec::new();
+        for (index, trigger) in self.triggers.iter_mut().enumerate() {
+            if let Some(trigger) = trigger.as_mut().filter(|trigger| trigger.ready()) {
+                all_actions.append(&mut trigger.consume_actions());
+                action_sources.push((all_actions.len(), Some(index)));
+            }
+        }
+        self.execute_actions(
+            all_actions,
+            first_action,
+            action_sources,
+            &AtomicBool::new(false),
+        );
+    }
+
+    /// Like [`Self::flush_ready`], but only consumes the actions of the trigger with the given index, if it is completed and still holds its actions.
+    fn flush_trigger(&mut self, index: usize, all_actions: &mut Vec<Event::Action>) {
+        let trigger = match self.triggers.get_mut(index) {
+            Some(Some(trigger)) if trigger.ready() => trigger,
+            _ => return,
+        };
+        let first_action = all_actions.len();
+        all_actions.append(&mut trigger.consume_actions());
+        let action_sources = vec![(all_actions.len(), Some(index))];
+        self.execute_actions(
+            all_actions,
+            first_action,
+            action_sources,
+            &AtomicBool::new(false),
+        );
+    }
+
+    /// Updates the subscriptions of the trigger with the given index.
+    /// Since the wildcard subscription is not expressed as an update, it is re-derived from the state of the trigger.
+    fn apply_trigger_condition_updates(
+        &mut self,
+        trigger_index: usize,
+        trigger_condition_updates: Vec<TriggerConditionUpdate<Event::Identifier>>,
+    ) {
+        for trigger_condition_update in trigger_condition_updates {
+            match trigger_condition_update {
+                TriggerConditionUpdate::Subscribe(identifier) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(trigger = trigger_index, ?identifier, "subscribe");
+                    self.subscriptions.insert(identifier, trigger_index);
+                }
+                TriggerConditionUpdate::Unsubscribe(identifier) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(trigger = trigger_index, ?identifier, "unsubscribe");
+                    self.unsubscribe(&identifier, trigger_index);
+                }
+            }
+        }
+        self.update_wildcard_subscription(trigger_index);
+    }
+
+    /// Executes the events of the pending actions, appending all resulting actions to `all_actions`.
+    /// The pending actions themselves are not appended.
+    /// Returns true if the cascade was cancelled.
+    fn execute_pending_actions(
+        &mut self,
+        all_actions: &mut Vec<Event::Action>,
+        cancel: &AtomicBool,
+    ) -> bool {
+        if self.cascade_mode == CascadeMode::Deferred {
+            let first_action = all_actions.len();
+            let mut action_sources = Vec::new();
+            let pending_actions = std::mem::take(&mut self.pending_actions);
+            let pending_action_sources = std::mem::take(&mut self.pending_action_sources);
+            let mut cancelled = false;
+            for (i, action) in pending_actions.iter().enumerate() {
+                if cancel.load(Ordering::Relaxed) {
+                    // The remaining events are executed before the events of the actions produced so far.
+                    self.pending_actions
+                        .extend(pending_actions[i..].iter().cloned());
+                    self.pending_action_sources.extend(
+                        (i..pending_actions.len())
+                            .map(|i| pending_action_sources.get(i).copied().flatten()),
+                    );
+                    cancelled = true;
+                    break;
+                }
+                let event = match self.receive_action(action) {
+                    Some(event) => event,
+                    None => continue,
+                };
+
+                let source = pending_action_sources.get(i).copied().flatten();
+                self.cascade_sources.extend(source);
+                action_sources.extend(self.execute_triggers(&event, all_actions));
+                if source.is_some() {
+                    self.cascade_sources.pop();
+                }
+            }
+            return self.execute_actions(all_actions, first_action, action_sources, cancel)
+                || cancelled;
+        }
+
+        let first_action = all_actions.len();
+        let pending_count = self.pending_actions.len();
+        all_actions.append(&mut self.pending_actions);
+        let pending_action_sources = std::mem::take(&mut self.pending_action_sources);
+        let action_sources = (0..pending_count)
+            .map(|i| {
+                (
+                    first_action + i + 1,
+                    pending_action_sources.get(i).copied().flatten(),
+                )
+            })
+            .collect();
+        let cancelled = self.execute_actions(all_actions, first_action, action_sources, cancel);
+        all_actions.drain(first_action..first_action + pending_count);
+        cancelled
+    }
+
+    /// Executes the given event, appending all resulting actions to `all_actions`.
+    /// This includes the actions resulting from the cascade of the events created from the actions.
+    /// Returns true if the cascade was cancelled, in which case the remaining actions are stored as pending.
+    fn execute_event(
+        &mut self,
+        event: &Event,
+        all_actions: &mut Vec<Event::Action>,
+        cancel: &AtomicBool,
+    ) -> bool {
+        let first_action = all_actions.len();
+        let action_sources = self.execute_triggers(event, all_actions);
+        self.execute_actions(all_actions, first_action, action_sources, cancel)
+    }
+
+    /// Executes the given event on the subscribed triggers, appending the actions they produce to `all_actions`, without cascading.
+    /// Returns the end of the range of actions produced by each trigger, together with the index of that trigger.
+    fn execute_triggers(
+        &mut self,
+        event: &Event,
+        all_actions: &mut Vec<Event::Action>,
+    ) -> Vec<(usize, Option<usize>)> {
+        self.event_count += 1;
+        let identifier = event.identifier();
+        let subscribers = self.subscriptions.get(&identifier);
+        #[cfg(feature = "stats")]
+        {
+            self.stats.max_cascade_depth = self.stats.max_cascade_depth.max(self.cascade_depth);
+        }
+        if subscribers.is_none() && self.wildcard_subscriptions.is_empty() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(?identifier, subscribers = 0, "execute event");
+            #[cfg(feature = "stats")]
+            {
+                self.stats.events_without_subscribers += 1;
+            }
+            return Vec::new();
+        }
+        // A trigger may be subscribed multiple times to the same identifier, but it receives each event only once.
+        // Its condition forwards the event to all its sub-conditions.
+        let mut trigger_indices = std::mem::take(&mut self.trigger_index_buffer);
+        if let Some(subscribers) = subscribers {
+            trigger_indices.extend(subscribers.keys().copied());
+        }
+        if !self.wildcard_subscriptions.is_empty() {
+            // The same holds for triggers that are subscribed to the identifier as well as to all events.
+            trigger_indices.extend(self.wildcard_subscriptions.iter().copied());
+            trigger_indices.sort_unstable();
+            trigger_indices.dedup();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            ?identifier,
+            subscribers = trigger_indices.len(),
+            "execute event"
+        );
+        #[cfg(feature = "stats")]
+        let mut any_completed = false;
+        #[cfg(feature = "stats")]
+        if trigger_indices.is_empty() {
+            self.stats.events_without_subscribers += 1;
+        }
+
+        // Each registered predicate and extractor is evaluated at most once for the event, regardless of how many conditions use it.
+        let mut shared_evaluations = std::mem::take(&mut self.shared_evaluations);
+        shared_evaluations.predicate_results.clear();
+        shared_evaluations
+            .predicate_results
+            .resize(self.predicates.len(), None);
+        shared_evaluations.extractor_results.clear();
+        shared_evaluations
+            .extractor_results
+            .resize(self.extractors.len(), None);
+        let predicate_results =
+            Cell::from_mut(shared_evaluations.predicate_results.as_mut_slice()).as_slice_of_cells();
+        let extractor_results =
+            Cell::from_mut(shared_evaluations.extractor_results.as_mut_slice()).as_slice_of_cells();
+
+        // The end of the range of actions produced by each trigger, to know the source of each action in the cascade.
+        let mut action_sources = Vec::new();
+        let first_action = all_actions.len();
+        for trigger_index in trigger_indices.iter().copied() {
+            #[cfg(feature = "metrics")]
+            {
+                if self.evaluations.len() <= trigger_index {
+                    self.evaluations.resize(trigger_index + 1, 0);
+                }
+                self.evaluations[trigger_index] += 1;
+            }
+            let trigger = self.triggers[trigger_index].as_mut().unwrap();
+            let actions_before = all_actions.len();
+            let progress_before = trigger.condition.current_progress();
+            let context = EventContext {
+                index: self.event_count,
+                last_advances: &self.last_advances,
+                predicate_results,
+                extractor_results,
+            };
+            let (trigger_condition_updates, completed) =
+                trigger.execute_event_into(event, all_actions, Some(context));
+            let advanced = completed || trigger.condition.current_progress() > progress_before;
+            #[cfg(feature = "tracing")]
+            {
+                let (current, required) = trigger.progress();
+                if completed {
+                    tracing::debug!(
+                        trigger = trigger_index,
+                        id_str = trigger.id_str(),
+                        actions = all_actions.len() - actions_before,
+                        "trigger completed"
+                    );
+                } else {
+                    tracing::trace!(
+                        trigger = trigger_index,
+                        id_str = trigger.id_str(),
+                        current,
+                        required,
+                        "trigger progressed"
+                    );
+                }
+            }
+            if completed && trigger.repeat && self.cascade_sources.contains(&trigger_index) {
+                // The event descends from the actions of this trigger, so firing again could loop forever.
+                all_actions.truncate(actions_before);
+            } else if completed {
+                if let Some(completions) = &mut self.completions {
+                    completions.push(trigger_index);
+                }
+                #[cfg(feature = "stats")]
+                {
+                    let trigger_fires = &mut self.stats.trigger_fires;
+                    if trigger_fires.len() <= trigger_index {
+                        trigger_fires.resize(trigger_index + 1, 0);
+                    }
+                    trigger_fires[trigger_index] += 1;
+                }
+            }
+            if let Some(eq) = self.coalesce_actions {
+                let mut i = actions_before;
+                while i < all_actions.len() {
+                    if all_actions[first_action..i]
+                        .iter()
+                        .any(|action| eq(action, &all_actions[i]))
+                    {
+                        all_actions.remove(i);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            if all_actions.len() > actions_before {
+                action_sources.push((all_actions.len(), Some(trigger_index)));
+            }
+            #[cfg(feature = "stats")]
+            {
+                any_completed |= completed;
+            }
+            let remove = trigger.once && completed;
+            if advanced {
+                self.mark_advanced(trigger_index);
+            }
+
+            self.apply_trigger_condition_updates(trigger_index, trigger_condition_updates);
+
+            if remove {
+                self.remove_trigger(trigger_index);
+            } else if completed {
+                self.update_active(trigger_index);
+            }
+        }
+
+        #[cfg(feature = "stats")]
+        {
+            self.stats.actions_produced += all_actions.len() - first_action;
+        }
+        #[cfg(feature = "stats")]
+        if any_completed {
+            self.stats.events_completing_triggers += 1;
+        } else if !trigger_indices.is_empty() {
+            self.stats.events_progressed_only += 1;
+        }
+
+        trigger_indices.clear();
+        self.trigger_index_buffer = trigger_indices;
+        self.shared_evaluations = shared_evaluations;
+        action_sources
+    }
+
+    /// Returns the event of the given action, or `None` if it is not received by any trigger, i.e. if no trigger is subscribed to its identifier or to all events.
+    /// The cascade skips such events, but they still count in the stats and in the event stream.
+    /// If [`TriggerEvent::action_identifier`] is overridden, the actions of skipped events are not converted.
+    fn receive_action(&mut self, action: &Event::Action) -> Option<Event> {
+        if !self.wildcard_subscriptions.is_empty() {
+            return Some(Event::from(action.clone()));
+        }
+        let (identifier, event) = match Event::action_identifier(action) {
+            Some(identifier) => (identifier, None),
+            None => {
+                let event = Event::from(action.clone());
+                (event.identifier(), Some(event))
+            }
+        };
+        if self.subscriptions.get(&identifier).is_some() {
+            return Some(event.unwrap_or_else(|| Event::from(action.clone())));
+        }
+        self.event_count += 1;
+        #[cfg(feature = "stats")]
+        {
+            self.stats.events_without_subscribers += 1;
+        }
+        None
+    }
+
+    /// Stores the actions of the given frame whose events were not executed yet as pending, together with their sources.
+    fn defer_actions(&mut self, all_actions: &[Event::Action], frame: &CascadeFrame) {
+        let actions = frame.next_action..frame.last_action;
+        self.pending_actions
+            .extend(all_actions[actions.clone()].iter().cloned());
+        self.pending_action_sources
+            .extend(actions.map(|i| frame.action_source(i)));
+    }
+
+    /// Executes the events created from `all_actions[first_action..]`, appending all resulting actions to `all_actions`.
+    /// Each action is fully cascaded before the event of the next action is executed, unless the cascade order is [`CascadeOrder::BreadthFirst`].
+    /// Returns true if the cascade was cancelled or reached `max_actions`, in which case the remaining actions are stored as pending.
+    /// Since the deepest level of the cascade is stored first, executing the pending actions in order continues the cascade in the original order.
+    /// `action_sources` maps the ends of ranges of actions to the triggers that produced them, if known.
+    ///
+    /// The cascade is executed with an explicit stack instead of recursion, such that long chains of actions cannot overflow the call stack.
+    fn execute_actions(
+        &mut self,
+        all_actions: &mut Vec<Event::Action>,
+        first_action: usize,
+        action_sources: Vec<(usize, Option<usize>)>,
+        cancel: &AtomicBool,
+    ) -> bool {
+        if self.cascade_mode == CascadeMode::Deferred {
+            self.defer_actions(
+                all_actions,
+                &CascadeFrame {
+                    next_action: first_action,
+                    last_action: all_actions.len(),
+                    action_sources,
+                    source: None,
+                },
+            );
+            return false;
+        }
+        if self.cascade_order == CascadeOrder::BreadthFirst {
+            return self.execute_actions_breadth_first(
+                all_actions,
+                first_action,
+                action_sources,
+                cancel,
+            );
+        }
+
+        let mut frames = vec![CascadeFrame {
+            next_action: first_action,
+            last_action: all_actions.len(),
+            action_sources,
+            source: None,
+        }];
+
+        while let Some(frame) = frames.last_mut() {
+            if frame.next_action >= frame.last_action {
+                let frame = frames.pop().unwrap();
+                if !frames.is_empty() {
+                    self.leave_cascade_frame(&frame);
+                }
+                continue;
+            }
+
+            let limit_reached = self
+                .max_actions
+                .map_or(false, |max_actions| all_actions.len() >= max_actions);
+            if limit_reached || cancel.load(Ordering::Relaxed) {
+                for (depth, frame) in frames.iter().enumerate().rev() {
+                    self.defer_actions(all_actions, frame);
+                    if depth > 0 {
+                        self.leave_cascade_frame(frame);
+                    }
+                }
+                return true;
+            }
+
+            let i = frame.next_action;
+            frame.next_action += 1;
+            let event = match self.receive_action(&all_actions[i]) {
+                Some(event) => event,
+                None => continue,
+            };
+            if self
+                .max_cascade
+                .map_or(false, |max_cascade| self.cascade_depth >= max_cascade)
+            {
+                self.dropped_cascade_events += 1;
+                continue;
+            }
+
+            let source = frame.action_source(i);
+            if let Some(source) = source {
+                self.cascade_sources.push(source);
+            }
+            self.cascade_depth += 1;
+
+            let first_action = all_actions.len();
+            let action_sources = self.execute_triggers(&event, all_actions);
+            frames.push(CascadeFrame {
+                next_action: first_action,
+                last_action: all_actions.len(),
+                action_sources,
+                source,
+            });
+        }
+        false
+    }
+
+    /// Like [`Self::execute_actions`], but executes the cascade layer by layer.
+    /// The events of all actions produced by an event are executed before the events of the actions they produce.
+    /// If the cascade is cancelled, the remaining actions are stored as pending in the order in which their events would have been executed.
+    fn execute_actions_breadth_first(
+        &mut self,
+        all_actions: &mut Vec<Event::Action>,
+        first_action: usize,
+        action_sources: Vec<(usize, Option<usize>)>,
+        cancel: &AtomicBool,
+    ) -> bool {
+        let cascade_sources = std::mem::take(&mut self.cascade_sources);
+        let cascade_depth = self.cascade_depth;
+        let mut layers = VecDeque::from([BreadthFirstFrame {
+            frame: CascadeFrame {
+                next_action: first_action,
+                last_action: all_actions.len(),
+                action_sources,
+                source: None,
+            },
+            cascade_sources: cascade_sources.clone(),
+            cascade_depth,
+        }]);
+        let mut cancelled = false;
+
+        'layers: while let Some(mut layer) = layers.pop_front() {
+            let frame = &mut layer.frame;
+            while frame.next_action < frame.last_action {
+                let limit_reached = self
+                    .max_actions
+                    .map_or(false, |max_actions| all_actions.len() >= max_actions);
+                if limit_reached || cancel.load(Ordering::Relaxed) {
+                    for frame in
+                        std::iter::once(&layer.frame).chain(layers.iter().map(|layer| &layer.frame))
+                    {
+                        self.defer_actions(all_actions, frame);
+                    }
+                    cancelled = true;
+                    break 'layers;
+                }
+
+                let i = frame.next_action;
+                frame.next_action += 1;
+                let event = match self.receive_action(&all_actions[i]) {
+                    Some(event) => event,
+                    None => continue,
+                };
+                if self
+                    .max_cascade
+                    .map_or(false, |max_cascade| layer.cascade_depth >= max_cascade)
+                {
+                    self.dropped_cascade_events += 1;
+                    continue;
+                }
+
+                let source = frame.action_source(i);
+                self.cascade_sources.clone_from(&layer.cascade_sources);
+                self.cascade_sources.extend(source);
+                self.cascade_depth = layer.cascade_depth + 1;
+
+                let first_action = all_actions.len();
+                let action_sources = self.execute_triggers(&event, all_actions);
+                layers.push_back(BreadthFirstFrame {
+                    frame: CascadeFrame {
+                        next_action: first_action,
+                        last_action: all_actions.len(),
+                        action_sources,
+                        source,
+                    },
+                    cascade_sources: self.cascade_sources.clone(),
+                    cascade_depth: self.cascade_depth,
+                });
+            }
+        }
+
+        self.cascade_sources = cascade_sources;
+        self.cascade_depth = cascade_depth;
+        cancelled
+    }
+
+    /// Restores the cascade state from before the event that produced the actions of `frame` was executed.
+    fn leave_cascade_frame(&mut self, frame: &CascadeFrame) {
+        self.cascade_depth -= 1;
+        if frame.source.is_some() {
+            self.cascade_sources.pop();
+        }
+    }
+}
+
+/// The actions produced by a single event during a cascade, see [`TriggerSystem::execute_actions`].
+struct CascadeFrame {
+    /// The index of the next action whose event is executed.
+    next_action: usize,
+    /// The end of the range of actions produced by the event.
+    last_action: usize,
+    /// The end of the range of actions produced by each trigger, together with the index of that trigger, if known.
+    action_sources: Vec<(usize, Option<usize>)>,
+    /// The trigger whose action created the event, if known.
+    source: Option<usize>,
+}
+
+impl CascadeFrame {
+    /// Returns the index of the trigger that produced the action with the given index, if known.
+    fn action_source(&self, action: usize) -> Option<usize> {
+        self.action_sources
+            .get(
+                self.action_sources
+                    .partition_point(|(end, _)| *end <= action),
+            )
+            .and_then(|(_, trigger_index)| *trigger_index)
+    }
+}
+
+/// A [`CascadeFrame`] of a breadth-first cascade, together with the cascade state of the event that produced its actions.
+struct BreadthFirstFrame {
+    frame: CascadeFrame,
+    /// The indices of the triggers whose actions caused the event, outermost first.
+    cascade_sources: Vec<usize>,
+    /// The depth of the event.
+    cascade_depth: usize,
+}
+
+impl<Event, Action> Trigger<Event, Action> {
+    pub fn new(id_str: String, condition: TriggerCondition<Event>, actions: Vec<Action>) -> Self {
+        Self {
+            id_str,
+            condition,
+            actions,
+            tags: Vec::new(),
+            repeat: false,
+            fail_on: Vec::new(),
+            failure_actions: Vec::new(),
+        }
+    }
+
+    /// Sets free-form tags for grouping triggers, e.g. "main quest" or "side quest".
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// If `repeat` is true, the trigger is reset after firing, such that it fires again each time its condition completes.
+    /// Triggers whose condition is completed from the start still fire only once.
+    /// A repeating trigger does not fire again on events that result from its own actions, as that could loop forever.
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn repeat(&self) -> bool {
+        self.repeat
+    }
+
+    /// Makes the trigger fail permanently on events with the identifiers of `fail_on`, regardless of its condition.
+    /// Failing produces `failure_actions`.
+    /// A failed trigger is unsubscribed from all events and does not fire until it is reset.
+    pub fn with_fail_on(mut self, fail_on: Vec<Event>, failure_actions: Vec<Action>) -> Self {
+        self.fail_on = fail_on;
+        self.failure_actions = failure_actions;
+        self
+    }
+
+    pub fn fail_on(&self) -> &[Event] {
+        &self.fail_on
+    }
+
+    pub fn failure_actions(&self) -> &[Action] {
+        &self.failure_actions
+    }
+
+    /// Returns a builder for a trigger, as an alternative to [`Self::new`].
+    ///
+    /// ```
+    /// use event_trigger_action_system::{event_count, Trigger};
+    ///
+    /// let trigger = Trigger::builder()
+    ///     .id("hunter")
+    ///     .condition(event_count("kill", 2))
+    ///     .action("reward")
+    ///     .action("next quest")
+    ///     .build();
+    /// let expected = Trigger::new(
+    ///     "hunter".to_string(),
+    ///     event_count("kill", 2),
+    ///     vec!["reward", "next quest"],
+    /// );
+    /// assert_eq!(trigger, expected);
+    /// ```
+    pub fn builder() -> TriggerBuilder<Event, Action> {
+        TriggerBuilder::new()
+    }
+
+    pub fn compile<
+        EventCompiler: Fn(Event) -> CompiledEvent,
+        CompiledEvent: TriggerEvent,
+        ActionCompiler: Fn(Action) -> CompiledEvent::Action,
+    >(
+        self,
+        event_compiler: &EventCompiler,
+        action_compiler: &ActionCompiler,
+    ) -> CompiledTrigger<CompiledEvent> {
+        self.try_compile(event_compiler, action_compiler)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Compiles the trigger like [`Self::compile`], or returns an error if its condition cannot be compiled.
+    pub fn try_compile<
+        EventCompiler: Fn(Event) -> CompiledEvent,
+        CompiledEvent: TriggerEvent,
+        ActionCompiler: Fn(Action) -> CompiledEvent::Action,
+    >(
+        self,
+        event_compiler: &EventCompiler,
+        action_compiler: &ActionCompiler,
+    ) -> Result<CompiledTrigger<CompiledEvent>, CompileError> {
+        Ok(CompiledTrigger {
+            id_str: self.id_str,
+            condition: self.condition.try_compile(event_compiler)?,
+            actions: self.actions.into_iter().map(action_compiler).collect(),
+            fired: false,
+            tags: self.tags,
+            repeat: self.repeat,
+            once: false,
+            enabled: true,
+            fail_on: self
+                .fail_on
+                .into_iter()
+                .map(|event| event_compiler(event).identifier())
+                .collect(),
+            failure_actions: self
+                .failure_actions
+                .into_iter()
+                .map(action_compiler)
+                .collect(),
+            failed: false,
+        })
+    }
+}
+
+/// Resolves the `stale_for` conditions of the given triggers to the first trigger with their trigger id, see [`CompiledTriggerCondition::resolve_stale_for`].
+/// Returns the index of the first trigger with a `stale_for` condition whose trigger id does not exist.
+fn resolve_stale_for<Event: TriggerEvent>(
+    triggers: &mut [CompiledTrigger<Event>],
+) -> Option<usize> {
+    let mut ids = BTreeMap::new();
+    for (index, trigger) in triggers.iter().enumerate() {
+        ids.entry(trigger.id_str.clone()).or_insert(index);
+    }
+    let mut unresolved = None;
+    for (index, trigger) in triggers.iter_mut().enumerate() {
+        if !trigger
+            .condition
+            .resolve_stale_for(&|trigger_id| ids.get(trigger_id).copied())
+        {
+            unresolved = unresolved.or(Some(index));
+        }
+    }
+    unresolved
+}
+
+impl<Event: TriggerEvent> CompiledTrigger<Event> {
+    pub fn new(
+        id_str: String,
+        condition: CompiledTriggerCondition<Event>,
+        actions: Vec<Event::Action>,
+    ) -> Self {
+        Self {
+            id_str,
+            condition,
+            actions,
+            fired: false,
+            tags: Vec::new(),
+            repeat: false,
+            once: false,
+            enabled: true,
+            fail_on: Vec::new(),
+            failure_actions: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Makes this trigger fire again each time its condition completes, see [`Trigger::with_repeat`].
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Disables this trigger initially, see [`CompiledTriggers::set_enabled`].
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Makes this trigger fail on events with the given identifiers, producing `failure_actions`, see [`Trigger::with_fail_on`].
+    pub fn with_fail_on(
+        mut self,
+        fail_on: Vec<Event::Identifier>,
+        failure_actions: Vec<Event::Action>,
+    ) -> Self {
+        self.fail_on = fail_on;
+        self.failure_actions = failure_actions;
+        self
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn subscriptions(&self) -> Vec<Event::Identifier> {
+        self.condition.subscriptions()
+    }
+
+    /// Returns true if this trigger is enabled, did not fail, and its condition currently needs to receive all events.
+    fn subscribes_to_all(&self) -> bool {
+        self.enabled && !self.failed && self.condition.subscribes_to_all()
+    }
+
+    /// Returns the identifiers this trigger needs to receive if it is enabled.
+    /// These are the subscriptions of its condition and, while it can still fire, its `fail_on` identifiers.
+    /// A failed trigger needs no events.
+    fn trigger_subscriptions(&self) -> Vec<Event::Identifier> {
+        if self.failed {
+            return Vec::new();
+        }
+        let mut subscriptions = self.subscriptions();
+        if !self.completed() {
+            subscriptions.extend(self.fail_on.iter().cloned());
+        }
+        subscriptions
+    }
+
+    fn state(&self) -> TriggerState<Event>
+    where
+        Event: Clone,
+    {
+        TriggerState {
+            condition: self.condition.clone(),
+            fired: self.fired,
+            enabled: self.enabled,
+            failed: self.failed,
+        }
+    }
+
+    fn set_state(&mut self, state: TriggerState<Event>) {
+        self.condition = state.condition;
+        self.fired = state.fired;
+        self.enabled = state.enabled;
+        self.failed = state.failed;
+    }
+
+    /// Returns the identifiers this trigger is subscribed to in the trigger system, which are none if it is disabled.
+    fn active_subscriptions(&self) -> Vec<Event::Identifier> {
+        if self.enabled {
+            self.trigger_subscriptions()
+        } else {
+            Default::default()
+        }
+    }
+
+    pub fn execute_event(
+        &mut self,
+        event: &Event,
+    ) -> (
+        Vec<Event::Action>,
+        Vec<TriggerConditionUpdate<Event::Identifier>>,
+    ) {
+        let mut actions = Vec::new();
+        let (trigger_condition_updates, _) = self.execute_event_into(event, &mut actions, None);
+        (actions, trigger_condition_updates)
+    }
+
+    /// Like [`Self::execute_event`], but appends the actions to `all_actions` and additionally returns whether the condition of the trigger completed.
+    /// `context` is the position of the event in the event stream of the trigger system, if any.
+    fn execute_event_into(
+        &mut self,
+        event: &Event,
+        all_actions: &mut Vec<Event::Action>,
+        context: Option<EventContext>,
+    ) -> (Vec<TriggerConditionUpdate<Event::Identifier>>, bool) {
+        if self.failed {
+            return (Vec::new(), false);
+        }
+        if self.fail_on.contains(&event.identifier()) {
+            let trigger_condition_updates = self
+                .trigger_subscriptions()
+                .into_iter()
+                .map(TriggerConditionUpdate::Unsubscribe)
+                .collect();
+            self.failed = true;
+            all_actions.extend(self.failure_actions.iter().cloned());
+            return (trigger_condition_updates, false);
+        }
+
+        let (mut trigger_condition_updates, result, _) =
+            self.condition.execute_event_in(event, context);
+        if result && self.repeat {
+            // Re-arm the trigger by resetting its condition and subscribing to its initial events again.
+            self.condition.reset();
+            trigger_condition_updates.extend(
+                self.condition
+                    .subscriptions()
+                    .into_iter()
+                    .map(TriggerConditionUpdate::Subscribe),
+            );
+            all_actions.extend(self.actions.iter().cloned());
+        } else if result {
+            assert!(!self.fired);
+            self.fired = true;
+            all_actions.extend(self.actions.iter().cloned());
+            trigger_condition_updates.extend(
+                self.fail_on
+                    .iter()
+                    .cloned()
+                    .map(TriggerConditionUpdate::Unsubscribe),
+            );
+        }
+        (trigger_condition_updates, result)
+    }
+
+    pub fn id_str(&self) -> &str {
+        &self.id_str
+    }
+
+    pub fn progress(&self) -> (f64, f64) {
+        (
+            self.condition.current_progress(),
+            self.condition.required_progress(),
+        )
+    }
+
+    pub fn condition(&self) -> &CompiledTriggerCondition<Event> {
+        &self.condition
+    }
+
+    pub fn actions(&self) -> &[Event::Action] {
+        if self.fired {
+            &[]
+        } else {
+            &self.actions
+        }
     }
 
     pub fn completed(&self) -> bool {
         self.condition.completed()
     }
 
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn status(&self) -> TriggerStatus {
+        if self.completed() {
+            TriggerStatus::Completed
+        } else if self.failed || self.condition.failed() {
+            TriggerStatus::Failed
+        } else {
+            TriggerStatus::Active
+        }
+    }
+
+    pub fn repeat(&self) -> bool {
+        self.repeat
+    }
+
+    fn summary(&self, handle: TriggerHandle) -> QuestSummary<Event::Identifier> {
+        let (current, required) = self.progress();
+        QuestSummary {
+            handle,
+            id: self.id_str.clone(),
+            status: self.status(),
+            current,
+            required,
+            waiting_on: self.subscriptions(),
+        }
+    }
+
+    /// Returns true if the trigger is completed, but its actions were not consumed yet.
+    fn ready(&self) -> bool {
+        self.enabled && self.completed() && !self.fired
+    }
+
     fn consume_actions(&mut self) -> Vec<Event::Action> {
-        self.actions.take().unwrap()
+        assert!(!self.fired);
+        self.fired = true;
+        self.actions.clone()
+    }
+
+    /// Resets the condition of the trigger and allows it to fire again, also if it failed by one of its `fail_on` events.
+    /// Returns the resulting subscription updates.
+    fn reset(&mut self) -> Vec<TriggerConditionUpdate<Event::Identifier>> {
+        let mut trigger_condition_updates: Vec<_> = self
+            .trigger_subscriptions()
+            .into_iter()
+            .map(TriggerConditionUpdate::Unsubscribe)
+            .collect();
+        self.condition.reset();
+        self.fired = false;
+        self.failed = false;
+        trigger_condition_updates.extend(
+            self.trigger_subscriptions()
+                .into_iter()
+                .map(TriggerConditionUpdate::Subscribe),
+        );
+        trigger_condition_updates
+    }
+}
+
+impl<Action> ActionTransformers<Action> {
+    fn apply(&mut self, action: Action) -> Action {
+        self.transformers
+            .iter()
+            .fold(action, |action, transformer| {
+                (transformer.lock().unwrap())(action)
+            })
+    }
+}
+
+impl<Action> Default for ActionTransformers<Action> {
+    fn default() -> Self {
+        Self {
+            transformers: Vec::new(),
+        }
+    }
+}
+
+impl<Action> Clone for ActionTransformers<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            transformers: self.transformers.clone(),
+        }
+    }
+}
+
+impl<Action> Debug for ActionTransformers<Action> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionTransformers")
+            .field("len", &self.transformers.len())
+            .finish()
+    }
+}
+
+impl MemoryReport {
+    /// Returns the total estimated memory in bytes.
+    pub fn total(&self) -> usize {
+        self.triggers + self.conditions + self.subscriptions + self.action_queue
+    }
+}
+
+impl<Event, Action> FromIterator<Trigger<Event, Action>> for Triggers<Event, Action> {
+    fn from_iter<T: IntoIterator<Item = Trigger<Event, Action>>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<Event, Action> Extend<Trigger<Event, Action>> for Triggers<Event, Action> {
+    fn extend<T: IntoIterator<Item = Trigger<Event, Action>>>(&mut self, iter: T) {
+        self.triggers.extend(iter);
     }
 }
 
@@ -1,4 +1,4 @@
-use crate::triggers::TriggerIdentifier;
+use crate::triggers::{NumericTriggerEvent, TriggerIdentifier};
 use crate::{TriggerAction, TriggerEvent};
 
 impl TriggerAction for () {}
@@ -19,3 +19,24 @@ impl TriggerEvent for () {
         Some(1.0)
     }
 }
+
+/// Numbers are events that all share the identifier `()`, and that are their own actions.
+/// The progress is linear, and therefore `None` for negative numbers.
+macro_rules! impl_numeric_trigger_event {
+    ($($number:ty),*) => {$(
+        impl TriggerAction for $number {}
+
+        impl NumericTriggerEvent for $number {
+            type Action = $number;
+            type Identifier = ();
+
+            fn identifier(&self) -> Self::Identifier {}
+
+            fn numeric_value(&self) -> Option<f64> {
+                Some(*self as f64)
+            }
+        }
+    )*};
+}
+
+impl_numeric_trigger_event!(i64, u64, f64);
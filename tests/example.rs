@@ -1,9 +1,17 @@
 use event_trigger_action_system::{
-    event_count, geq, none, sequence, Trigger, TriggerAction, TriggerConditionUpdate, TriggerEvent,
-    TriggerIdentifier, Triggers,
+    accumulate, and, any_n, at_most_n, between, between_bounds, count_within, decaying_count,
+    delta_geq, eq_any, event_count, exactly_n, geq, hold_between, neq, never, none, not, or,
+    or_priority, or_pruned, pipeline, predicate, predicate_any_event, retry, sequence, stale_for,
+    strict_sequence, triggers, xor, BoundKind, BuildError, CascadeMode, CascadeOrder, CompileError,
+    CompiledTriggers, CompiledTriggersBuilder, ConditionError, ConditionKindTag,
+    NumericTriggerEvent, Trigger, TriggerAction, TriggerCondition, TriggerConditionUpdate,
+    TriggerDiff, TriggerEvent, TriggerHandle, TriggerIdentifier, TriggerSnapshot, TriggerState,
+    TriggerStatus, Triggers,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -15,7 +23,7 @@ enum GameAction {
     DeactivateMonster { id: MonsterHandle },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 enum GameEvent {
     Action(GameAction),
@@ -23,6 +31,7 @@ enum GameEvent {
     FailedMonster { id: MonsterHandle },
     HealthChanged { health: usize },
     MonsterHealthChanged { id: MonsterHandle, health: usize },
+    GoldDropped { amount: usize },
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -33,6 +42,7 @@ enum GameEventIdentifier {
     FailedMonster { id: MonsterHandle },
     HealthChanged,
     MonsterHealthChanged { id: MonsterHandle },
+    GoldDropped,
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
@@ -59,6 +69,7 @@ impl TriggerEvent for GameEvent {
             GameEvent::MonsterHealthChanged { id, .. } => {
                 GameEventIdentifier::MonsterHealthChanged { id: *id }
             }
+            GameEvent::GoldDropped { .. } => GameEventIdentifier::GoldDropped,
         }
     }
 
@@ -201,6 +212,58 @@ fn test_repeated_action() {
     assert!(trigger.condition().completed());
 }
 
+#[test]
+fn test_cascade_executed_once() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(0) }),
+                1,
+            ),
+            vec![GameAction::ActivateMonster {
+                id: MonsterHandle(1),
+            }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateMonster {
+                    id: MonsterHandle(1),
+                }),
+                2,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The event of an action produced deeper in the cascade is executed once.
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(triggers.progress(2.into()), Some((1.0, 2.0)));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(1)
+            },
+        ]
+    );
+}
+
 #[test]
 fn test_composed_none() {
     let trigger = Trigger::<(), ()>::new(
@@ -226,6 +289,260 @@ fn test_composed_none_panic() {
     trigger.execute_event(&());
 }
 
+#[test]
+fn test_between() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "low health".to_string(),
+        between(health(5), health(10)),
+        vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&health(3));
+    triggers.execute_event(&health(12));
+    assert_eq!(triggers.consume_action(), None);
+    let (current, required) = triggers.progress_by_id("low health").unwrap();
+    assert!(current < required);
+
+    // Both bounds are inclusive.
+    triggers.execute_event(&health(10));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(0) })
+    );
+    let (current, required) = triggers.progress_by_id("low health").unwrap();
+    assert_eq!(current, required);
+
+    let mut condition = between(health(5), health(10)).compile(&|x| x);
+    condition.execute_event(&health(5));
+    assert!(condition.completed());
+}
+
+#[test]
+fn test_between_bounds() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let completes = |low_bound, high_bound, value| {
+        let mut condition =
+            between_bounds(health(5), low_bound, health(10), high_bound).compile(&|x| x);
+        condition.execute_event(&health(value));
+        condition.completed()
+    };
+
+    for (low_bound, high_bound, low_included, high_included) in [
+        (BoundKind::Inclusive, BoundKind::Inclusive, true, true),
+        (BoundKind::Inclusive, BoundKind::Exclusive, true, false),
+        (BoundKind::Exclusive, BoundKind::Inclusive, false, true),
+        (BoundKind::Exclusive, BoundKind::Exclusive, false, false),
+    ] {
+        assert!(!completes(low_bound, high_bound, 4));
+        assert_eq!(completes(low_bound, high_bound, 5), low_included);
+        assert!(completes(low_bound, high_bound, 7));
+        assert_eq!(completes(low_bound, high_bound, 10), high_included);
+        assert!(!completes(low_bound, high_bound, 11));
+    }
+
+    // A value equal to an exclusive lower bound is not complete progress.
+    let mut condition = between_bounds(
+        health(5),
+        BoundKind::Exclusive,
+        health(10),
+        BoundKind::Inclusive,
+    )
+    .compile(&|x| x);
+    condition.execute_event(&health(4));
+    let progress = condition.current_progress();
+    assert!(progress > 0.0 && progress < 1.0);
+    condition.execute_event(&health(5));
+    assert_eq!(condition.current_progress(), progress);
+
+    assert_eq!(
+        between_bounds(
+            health(5),
+            BoundKind::Exclusive,
+            health(10),
+            BoundKind::Inclusive
+        )
+        .to_infix_string(&|event| format!("{event:?}")),
+        "between_bounds(HealthChanged { health: 5 }, Exclusive, HealthChanged { health: 10 }, Inclusive)"
+    );
+}
+
+#[test]
+fn test_or_priority() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    // Both branches complete on the same event, and only the `any_n` branch has `any_n` progress.
+    let condition = |event_count_priority, any_n_priority| {
+        or_priority(vec![
+            (event_count_priority, event_count(kill.clone(), 1)),
+            (
+                any_n_priority,
+                any_n(vec![event_count(kill.clone(), 1), never()], 1),
+            ),
+        ])
+    };
+
+    for (event_count_priority, any_n_priority, any_n_wins) in
+        [(1, 0, false), (0, 1, true), (-1, 1, true), (0, 0, false)]
+    {
+        let mut compiled = condition(event_count_priority, any_n_priority).compile(&|x| x);
+        assert!(compiled.or_winner().is_none());
+        compiled.execute_event(&kill);
+        assert!(compiled.completed());
+        let winner = compiled.or_winner().unwrap();
+        assert_eq!(winner.any_n_progress().is_some(), any_n_wins);
+
+        // The priorities still decide after a reset.
+        compiled.reset();
+        compiled.execute_event(&kill);
+        let winner = compiled.or_winner().unwrap();
+        assert_eq!(winner.any_n_progress().is_some(), any_n_wins);
+    }
+
+    assert_eq!(
+        condition(2, 1).to_infix_string(&|_| "kill".to_string()),
+        "or_priority([(2, event_count(kill, 1)), (1, any_n([event_count(kill, 1), never], 1))])"
+    );
+}
+
+#[test]
+fn test_or_zero_required_progress() {
+    // Not simplified, so the `none` branch is compiled as a sub-condition requiring no progress.
+    let trigger = Trigger::<GameEvent, GameAction>::new(
+        "".to_string(),
+        or(vec![
+            none(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                2,
+            ),
+        ]),
+        vec![],
+    )
+    .compile(&|x| x, &|x| x);
+    assert!(trigger.condition().completed());
+    assert_eq!(trigger.progress(), (0.0, 0.0));
+
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        or(vec![
+            or(vec![
+                none(),
+                event_count(
+                    GameEvent::KilledMonster {
+                        id: MonsterHandle(0),
+                    },
+                    1,
+                ),
+            ]),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(1),
+                },
+                2,
+            ),
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.progress(0.into()).unwrap(), (0.0, 0.0));
+}
+
+#[test]
+fn test_triggers_macro() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let triggers = triggers! {
+        "" => none() => [GameAction::ActivateQuest { id: QuestHandle(0) }],
+        "" => event_count(kill(0), 2) => [GameAction::CompleteQuest { id: QuestHandle(0) }],
+        "" => event_count(kill(0), 1) => [GameAction::ActivateQuest { id: QuestHandle(1) }],
+        "" => event_count(
+            GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+            1,
+        ) => [GameAction::FailQuest { id: QuestHandle(2) }],
+        "" => none() => [GameAction::ActivateMonster { id: MonsterHandle(0) }],
+        "" => sequence(vec![
+            event_count(GameEvent::FailedMonster { id: MonsterHandle(3) }, 1),
+            event_count(kill(3), 1),
+        ]) => [GameAction::DeactivateMonster { id: MonsterHandle(3) }],
+    };
+    let expected = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                2,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateMonster {
+                id: MonsterHandle(0),
+            }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::FailedMonster {
+                        id: MonsterHandle(3),
+                    },
+                    1,
+                ),
+                event_count(
+                    GameEvent::KilledMonster {
+                        id: MonsterHandle(3),
+                    },
+                    1,
+                ),
+            ]),
+            vec![GameAction::DeactivateMonster {
+                id: MonsterHandle(3),
+            }],
+        ),
+    ]);
+    assert_eq!(triggers, expected);
+
+    let empty: Triggers<GameEvent, GameAction> = triggers! {};
+    assert!(empty.is_empty());
+}
+
 #[test]
 fn test_complex() {
     let mut triggers = Triggers::new(vec![
@@ -346,6 +663,104 @@ fn test_complex() {
     assert_eq!(triggers.consume_action(), None);
 }
 
+#[test]
+fn test_to_dot() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "start".to_string(),
+            event_count(kill(0), 1),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "fail".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+        Trigger::new(
+            "boss".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::FailedMonster {
+                        id: MonsterHandle(3),
+                    },
+                    1,
+                ),
+                event_count(kill(3), 1),
+            ]),
+            vec![GameAction::DeactivateMonster {
+                id: MonsterHandle(3),
+            }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    let dot = "digraph triggers {
+    t0 [label=\"start\"];
+    t1 [label=\"fail\"];
+    t2 [label=\"boss\"];
+    t0 -> t1;
+}
+";
+    assert_eq!(triggers.to_dot(), dot);
+
+    // The graph does not depend on the state of the triggers.
+    triggers.execute_event(&GameEvent::FailedMonster {
+        id: MonsterHandle(3),
+    });
+    assert_eq!(triggers.to_dot(), dot);
+}
+
+#[test]
+fn test_execute_event_completions() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 1),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The completions include the triggers completed by the cascade, in order of completion.
+    assert_eq!(
+        triggers.execute_event_completions(&kill(0)),
+        vec![TriggerHandle::from(1), TriggerHandle::from(2)]
+    );
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::FailQuest { id: QuestHandle(2) },
+        ]
+    );
+    assert_eq!(
+        triggers.execute_event_completions(&kill(0)),
+        vec![TriggerHandle::from(0)]
+    );
+    assert_eq!(triggers.execute_event_completions(&kill(0)), vec![]);
+}
+
 #[test]
 fn test_geq() {
     let mut triggers = Triggers::new(vec![
@@ -414,3 +829,4806 @@ fn test_geq() {
     );
     assert_eq!(triggers.consume_action(), None);
 }
+
+#[test]
+fn test_absorb_actions() {
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        none(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    let mut other_triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(
+            GameEvent::KilledMonster {
+                id: MonsterHandle(0),
+            },
+            1,
+        ),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+        ],
+    )])
+    .compile(&|x| x, &|x| x);
+    other_triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+
+    triggers.absorb_actions(&mut other_triggers);
+    assert_eq!(other_triggers.consume_action(), None);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(1) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_sequence_leading_none() {
+    let mut trigger = Trigger::new(
+        "".to_string(),
+        sequence(vec![
+            none(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        trigger.subscriptions(),
+        vec![GameEventIdentifier::KilledMonster {
+            id: MonsterHandle(0)
+        }]
+    );
+    assert!(!trigger.completed());
+    assert_eq!(trigger.progress(), (0.0, 1.0));
+
+    assert_eq!(
+        trigger.execute_event(&GameEvent::KilledMonster {
+            id: MonsterHandle(0)
+        }),
+        (
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+            vec![TriggerConditionUpdate::Unsubscribe(
+                GameEventIdentifier::KilledMonster {
+                    id: MonsterHandle(0)
+                }
+            )]
+        )
+    );
+    assert!(trigger.completed());
+    assert_eq!(trigger.progress(), (1.0, 1.0));
+}
+
+#[test]
+#[should_panic]
+fn test_sequence_inner_none_panic() {
+    Trigger::<GameEvent, GameAction>::new(
+        "".to_string(),
+        sequence(vec![
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            none(),
+        ]),
+        vec![],
+    )
+    .compile(&|x| x, &|x| x);
+}
+
+#[test]
+fn test_sequence_inner_none_try_compile() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let condition = sequence(vec![
+        event_count(kill.clone(), 1),
+        event_count(kill.clone(), 1),
+        none(),
+    ]);
+    assert_eq!(
+        condition.clone().try_compile(&|x| x).unwrap_err(),
+        CompileError::CompletedConditionInSequence { index: 2 }
+    );
+    assert_eq!(
+        Triggers::<GameEvent, GameAction>::new(vec![
+            Trigger::new("".to_string(), event_count(kill.clone(), 1), vec![]),
+            Trigger::new("".to_string(), condition, vec![]),
+        ])
+        .try_compile(&|x| x, &|x| x)
+        .unwrap_err(),
+        CompileError::CompletedConditionInSequence { index: 2 }
+    );
+    assert!(
+        Trigger::<GameEvent, GameAction>::new("".to_string(), event_count(kill, 1), vec![])
+            .try_compile(&|x| x, &|x| x)
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_drain_actions_into() {
+    let quest = |id| GameAction::ActivateQuest {
+        id: QuestHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![quest(0), quest(1), quest(2)],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    let mut buffer = vec![quest(3)];
+    triggers.drain_actions_into(&mut buffer);
+    assert_eq!(buffer, vec![quest(3), quest(0), quest(1), quest(2)]);
+    assert!(!triggers.has_pending_actions());
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_consume_action_if() {
+    let quest = |id| GameAction::ActivateQuest {
+        id: QuestHandle(id),
+    };
+    let monster = |id| GameAction::ActivateMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![monster(0), quest(0), monster(1), quest(1)],
+    )])
+    .compile(&|x| x, &|x| x);
+    let is_quest = |action: &GameAction| matches!(action, GameAction::ActivateQuest { .. });
+
+    assert_eq!(triggers.consume_action_if(is_quest), None);
+    assert_eq!(triggers.peek_action(), Some(&monster(0)));
+    assert_eq!(triggers.action_queue_len(), 4);
+
+    triggers.retain_actions(is_quest);
+    assert_eq!(triggers.consume_action_if(is_quest), Some(quest(0)));
+    assert_eq!(triggers.consume_action_if(is_quest), Some(quest(1)));
+    assert_eq!(triggers.consume_action_if(is_quest), None);
+}
+
+#[test]
+fn test_drain_actions_while() {
+    let mut triggers = Triggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::ActivateQuest { id: QuestHandle(2) },
+        ],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    let mut budget = 2;
+    assert_eq!(
+        triggers.drain_actions_while(|_| {
+            if budget > 0 {
+                budget -= 1;
+                true
+            } else {
+                false
+            }
+        }),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+        ]
+    );
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(2) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.drain_actions_while(|_| true), vec![]);
+}
+
+#[test]
+fn test_cascade_repeated_dispatch() {
+    let triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(0) }),
+                1,
+            ),
+            vec![GameAction::ActivateMonster {
+                id: MonsterHandle(1),
+            }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateMonster {
+                    id: MonsterHandle(1),
+                }),
+                2,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(1),
+                },
+                2,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    let events = [
+        GameEvent::KilledMonster {
+            id: MonsterHandle(1),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(1),
+        },
+    ];
+    let mut results = Vec::new();
+    for _ in 0..3 {
+        let mut triggers = triggers.clone();
+        let mut actions = Vec::new();
+        for event in &events {
+            triggers.execute_event(event);
+            actions.push(triggers.consume_all_actions().collect::<Vec<_>>());
+        }
+        // The cascaded action event must be counted exactly once.
+        assert_eq!(triggers.progress(2.into()), Some((1.0, 2.0)));
+        results.push(actions);
+    }
+
+    assert_eq!(
+        results[0],
+        vec![
+            vec![],
+            vec![
+                GameAction::ActivateQuest { id: QuestHandle(0) },
+                GameAction::ActivateMonster {
+                    id: MonsterHandle(1)
+                },
+            ],
+            vec![],
+            vec![GameAction::FailQuest { id: QuestHandle(1) }],
+        ]
+    );
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[0], results[2]);
+}
+
+#[test]
+fn test_canonicalize() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let a = event_count(kill(0), 1);
+    let b = event_count(kill(1), 2);
+    let c = geq(GameEvent::HealthChanged { health: 10 });
+    let d = event_count(kill(3), 1);
+
+    let condition1 =
+        a.clone() & (b.clone() | c.clone()) & any_n(vec![a.clone(), b.clone(), c.clone()], 2);
+    let condition2 = and(vec![
+        any_n(vec![c.clone(), a.clone(), none(), b.clone()], 3),
+        or(vec![c.clone(), never(), b.clone()]),
+        a.clone() & none(),
+    ]);
+    assert_ne!(condition1, condition2);
+    assert_eq!(condition1.clone().canonicalize(), condition2.canonicalize());
+
+    let condition3 =
+        a.clone() & (b.clone() | d.clone()) & any_n(vec![a.clone(), b.clone(), c.clone()], 2);
+    assert_ne!(condition1.canonicalize(), condition3.canonicalize());
+
+    assert_eq!(
+        sequence(vec![
+            none(),
+            a.clone(),
+            sequence(vec![b.clone(), c.clone()])
+        ])
+        .simplify(),
+        sequence(vec![a.clone(), b.clone(), c.clone()])
+    );
+    assert_eq!(or(vec![a.clone(), none()]).simplify(), none());
+    assert_eq!(or(vec![never::<GameEvent>(), never()]).simplify(), never());
+    assert_eq!(and(vec![a.clone(), never()]).simplify(), never());
+    assert_eq!(
+        any_n(vec![a.clone(), b.clone()], 1).simplify(),
+        a.clone() | b.clone()
+    );
+    assert_eq!(any_n(vec![a.clone(), b.clone()], 3).simplify(), never());
+    assert_eq!(event_count(kill(0), 0).simplify(), none::<GameEvent>());
+}
+
+#[test]
+fn test_flush_ready() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(0) }),
+                1,
+            ),
+            vec![GameAction::ActivateMonster {
+                id: MonsterHandle(0),
+            }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            none() & none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // Construction flushes the ready triggers, including their cascade.
+    assert_eq!(triggers.flush_ready(), vec![]);
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(0)
+            },
+        ]
+    );
+    assert_eq!(triggers.flush_ready(), vec![]);
+    assert_eq!(triggers.consume_action(), None);
+
+    // Deserializing does not fire ready triggers, e.g. if a save was edited to enable a trigger whose condition is completed.
+    #[cfg(feature = "serde")]
+    {
+        let triggers = CompiledTriggers::new(vec![Trigger::<GameEvent, _>::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        )
+        .compile(&|x| x, &|x| x)
+        .with_enabled(false)]);
+        let mut save = serde_json::to_value(&triggers).unwrap();
+        save["trigger_system"]["triggers"][0]["enabled"] = serde_json::json!(true);
+        let mut triggers: CompiledTriggers<GameEvent> = serde_json::from_value(save).unwrap();
+        assert_eq!(
+            triggers.flush_ready(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }]
+        );
+        assert_eq!(triggers.flush_ready(), vec![]);
+        assert_eq!(triggers.consume_action(), None);
+    }
+}
+
+#[test]
+fn test_pipeline() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            pipeline(vec![event_count(kill(0), 1), event_count(kill(1), 2)]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            pipeline(vec![event_count(kill(2), 1), event_count(kill(3), 2)]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The second step completes before the first one.
+    triggers.execute_event(&kill(1));
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+    triggers.execute_event(&kill(1));
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill(0));
+    assert_eq!(triggers.progress(0.into()), Some((3.0, 3.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+
+    // The progress of the second step is retained while the first step is in progress.
+    triggers.execute_event(&kill(3));
+    assert_eq!(triggers.progress(1.into()), Some((0.0, 3.0)));
+    triggers.execute_event(&kill(2));
+    assert_eq!(triggers.progress(1.into()), Some((2.0, 3.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill(3));
+    assert_eq!(triggers.progress(1.into()), Some((3.0, 3.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(1) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_memory_report() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let trigger = |id| {
+        Trigger::new(
+            format!("quest {id}"),
+            event_count(kill(id), 1)
+                & (event_count(kill(id + 10), 2) | event_count(kill(id + 20), 1)),
+            vec![GameAction::CompleteQuest {
+                id: QuestHandle(id),
+            }],
+        )
+    };
+
+    let small = Triggers::new(vec![trigger(0)]).compile(&|x| x, &|x| x);
+    let mut large = Triggers::new((0..3).map(trigger).collect()).compile(&|x| x, &|x| x);
+    let small_report = small.memory_report();
+    let large_report = large.memory_report();
+    assert!(large_report.triggers > small_report.triggers);
+    assert!(large_report.conditions > small_report.conditions);
+    assert!(large_report.subscriptions > small_report.subscriptions);
+    assert!(large_report.total() > small_report.total());
+
+    for id in 0..3 {
+        large.execute_event(&kill(id));
+        large.execute_event(&kill(id + 20));
+    }
+    let completed_report = large.memory_report();
+    assert_eq!(completed_report.subscriptions, 0);
+    assert!(completed_report.action_queue > large_report.action_queue);
+
+    // A trigger subscribed twice to the same identifier has a single entry, and triggers receiving all events count as well.
+    let usize_size = std::mem::size_of::<usize>();
+    let subscriptions = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 1) & event_count(kill(0), 2),
+            vec![],
+        ),
+        Trigger::new("".to_string(), predicate_any_event("any"), vec![]),
+    ])
+    .compile(&|x| x, &|x| x)
+    .memory_report()
+    .subscriptions;
+    assert_eq!(
+        subscriptions,
+        std::mem::size_of::<GameEventIdentifier>() + 2 * usize_size + usize_size
+    );
+}
+
+#[test]
+fn test_action_transformers() {
+    let mut triggers = Triggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::ActivateQuest { id: QuestHandle(2) },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(3),
+            },
+        ],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    triggers.add_action_transformer(|action| match action {
+        GameAction::ActivateQuest { id } => GameAction::ActivateQuest {
+            id: QuestHandle(id.0 * 10),
+        },
+        action => action,
+    });
+    triggers.add_action_transformer(|action| match action {
+        GameAction::ActivateQuest { id } => GameAction::CompleteQuest {
+            id: QuestHandle(id.0 + 1),
+        },
+        action => action,
+    });
+    // Clones share the transformers.
+    let transformed = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&transformed);
+    triggers.add_action_transformer(move |action| {
+        counter.fetch_add(1, atomic::Ordering::SeqCst);
+        action
+    });
+    let mut copy = triggers.clone();
+    let mut retained = triggers.clone();
+
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest {
+            id: QuestHandle(11)
+        })
+    );
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest {
+                id: QuestHandle(21)
+            },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(3)
+            },
+        ]
+    );
+    assert_eq!(transformed.load(atomic::Ordering::SeqCst), 3);
+
+    // The predicate receives the transformed actions, and each action is transformed only once.
+    assert_eq!(
+        copy.drain_actions_while(|action| matches!(action, GameAction::CompleteQuest { .. })),
+        vec![
+            GameAction::CompleteQuest {
+                id: QuestHandle(11)
+            },
+            GameAction::CompleteQuest {
+                id: QuestHandle(21)
+            },
+        ]
+    );
+    assert_eq!(copy.action_queue_len(), 1);
+    assert_eq!(transformed.load(atomic::Ordering::SeqCst), 6);
+    assert_eq!(
+        copy.consume_action_if(|action| matches!(action, GameAction::CompleteQuest { .. })),
+        None
+    );
+    assert_eq!(
+        copy.consume_action_if(|action| matches!(action, GameAction::ActivateMonster { .. })),
+        Some(GameAction::ActivateMonster {
+            id: MonsterHandle(3)
+        })
+    );
+    assert_eq!(transformed.load(atomic::Ordering::SeqCst), 6);
+
+    // Like the other predicates, the predicate of `retain_actions` receives the transformed actions.
+    retained.retain_actions(|action| {
+        action
+            != &GameAction::CompleteQuest {
+                id: QuestHandle(11),
+            }
+    });
+    assert_eq!(transformed.load(atomic::Ordering::SeqCst), 9);
+    assert_eq!(
+        retained.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest {
+                id: QuestHandle(21)
+            },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(3)
+            },
+        ]
+    );
+    assert_eq!(transformed.load(atomic::Ordering::SeqCst), 9);
+}
+
+#[test]
+fn test_duplicate_subscription_single_dispatch() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            and(vec![
+                event_count(kill.clone(), 1),
+                event_count(kill.clone(), 1),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            and(vec![
+                event_count(kill.clone(), 2),
+                event_count(kill.clone(), 3),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 2.0)));
+    assert_eq!(triggers.progress(1.into()), Some((2.0, 5.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_scale_requirements() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            sequence(vec![event_count(kill(1), 1), event_count(kill(2), 3)])
+                | geq(GameEvent::HealthChanged { health: 10 }),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ]);
+    triggers.scale_requirements(2.0);
+    let triggers = triggers.compile(&|x| x, &|x| x);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 4.0)));
+    // The geq branch is the cheapest branch of the or, so check the sequence on its own as well.
+    assert_eq!(triggers.progress(1.into()), Some((0.0, 1.0)));
+
+    let mut condition = sequence(vec![event_count(kill(1), 1), event_count(kill(2), 3)]);
+    condition.scale_requirements(2.0);
+    assert_eq!(
+        condition,
+        sequence(vec![event_count(kill(1), 2), event_count(kill(2), 6)])
+    );
+    condition.scale_requirements(0.1);
+    assert_eq!(
+        condition,
+        sequence(vec![event_count(kill(1), 1), event_count(kill(2), 1)])
+    );
+}
+
+#[test]
+fn test_tags() {
+    let quest = |id, tags: &[&str]| {
+        Trigger::new(
+            format!("quest {id}"),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(id),
+                },
+                1,
+            ),
+            vec![GameAction::CompleteQuest {
+                id: QuestHandle(id),
+            }],
+        )
+        .with_tags(tags.iter().map(|tag| tag.to_string()).collect())
+    };
+    let triggers = Triggers::new(vec![
+        quest(0, &["main"]),
+        quest(1, &["side", "hunt"]),
+        quest(2, &[]),
+        quest(3, &["main", "hunt"]),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    assert_eq!(triggers.handles_by_tag("main"), vec![0.into(), 3.into()]);
+    assert_eq!(triggers.handles_by_tag("hunt"), vec![1.into(), 3.into()]);
+    assert_eq!(triggers.handles_by_tag("side"), vec![1.into()]);
+    assert_eq!(triggers.handles_by_tag("unknown"), vec![]);
+    assert_eq!(
+        triggers.tags_of(1.into()),
+        Some(&["side".to_string(), "hunt".to_string()][..])
+    );
+    assert_eq!(triggers.tags_of(2.into()), Some(&[][..]));
+    assert_eq!(triggers.tags_of(4.into()), None);
+}
+
+static COUNTED_CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct CountedAction(usize);
+
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct CountedIdentifier(usize);
+
+#[derive(Debug)]
+struct CountedEvent(usize);
+
+impl TriggerAction for CountedAction {}
+
+impl TriggerIdentifier for CountedIdentifier {}
+
+impl TriggerEvent for CountedEvent {
+    type Action = CountedAction;
+    type Identifier = CountedIdentifier;
+
+    fn identifier(&self) -> Self::Identifier {
+        CountedIdentifier(self.0)
+    }
+
+    fn value_geq(&self, other: &Self) -> Option<bool> {
+        Some(self.0 >= other.0)
+    }
+
+    fn value_geq_progress(&self, other: &Self) -> Option<f64> {
+        Some((self.0 as f64 / other.0 as f64).clamp(0.0, 1.0))
+    }
+
+    fn action_identifier(action: &Self::Action) -> Option<Self::Identifier> {
+        Some(CountedIdentifier(action.0))
+    }
+}
+
+impl From<CountedAction> for CountedEvent {
+    fn from(action: CountedAction) -> Self {
+        COUNTED_CONVERSIONS.fetch_add(1, atomic::Ordering::SeqCst);
+        Self(action.0)
+    }
+}
+
+#[test]
+fn test_unsubscribed_actions_are_not_converted() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(CountedEvent(0), 1),
+            vec![CountedAction(1), CountedAction(2)],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(CountedEvent(1), 1),
+            vec![CountedAction(3)],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&CountedEvent(0));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![CountedAction(1), CountedAction(2), CountedAction(3)]
+    );
+    assert_eq!(COUNTED_CONVERSIONS.load(atomic::Ordering::SeqCst), 1);
+}
+
+static CONVERTED_CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Like [`CountedEvent`], but without overriding [`TriggerEvent::action_identifier`].
+#[derive(Debug)]
+struct ConvertedEvent(usize);
+
+impl TriggerEvent for ConvertedEvent {
+    type Action = CountedAction;
+    type Identifier = CountedIdentifier;
+
+    fn identifier(&self) -> Self::Identifier {
+        CountedIdentifier(self.0)
+    }
+
+    fn value_geq(&self, other: &Self) -> Option<bool> {
+        Some(self.0 >= other.0)
+    }
+
+    fn value_geq_progress(&self, other: &Self) -> Option<f64> {
+        Some((self.0 as f64 / other.0 as f64).clamp(0.0, 1.0))
+    }
+}
+
+impl From<CountedAction> for ConvertedEvent {
+    fn from(action: CountedAction) -> Self {
+        CONVERTED_CONVERSIONS.fetch_add(1, atomic::Ordering::SeqCst);
+        Self(action.0)
+    }
+}
+
+#[test]
+fn test_actions_are_converted_once() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(ConvertedEvent(0), 1),
+            vec![CountedAction(1), CountedAction(2)],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(ConvertedEvent(1), 1),
+            vec![CountedAction(3)],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&ConvertedEvent(0));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![CountedAction(1), CountedAction(2), CountedAction(3)]
+    );
+    assert_eq!(CONVERTED_CONVERSIONS.load(atomic::Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_not() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            not(geq(health(50)), 3),
+            vec![GameAction::FailQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            not(geq(health(20)), 3),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+    triggers.execute_event(&health(10));
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 3.0)));
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 3.0)));
+    triggers.execute_event(&health(30));
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 3.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&health(40));
+    assert_eq!(triggers.progress(0.into()), Some((3.0, 3.0)));
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 3.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::FailQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+
+    // The second trigger failed when its inner condition completed, so it is not affected by further events.
+    triggers.execute_event(&health(10));
+    triggers.execute_event(&health(10));
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 3.0)));
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_simplify_not() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    assert_eq!(not(none::<GameEvent>(), 2).simplify(), never());
+    assert_eq!(not(never::<GameEvent>(), 0).simplify(), none());
+    assert_eq!(not(never::<GameEvent>(), 2).simplify(), never());
+    assert_eq!(not(event_count(kill(0), 1), 0).simplify(), none());
+    assert_eq!(
+        not(event_count(kill(0), 1), 2).simplify(),
+        not(event_count(kill(0), 1), 2)
+    );
+
+    // The simplified conditions behave like the original ones.
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            not(never(), 0),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 3) & not(never(), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            not(event_count(kill(0), 1), 0),
+            vec![GameAction::CompleteQuest { id: QuestHandle(2) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(2) },
+        ]
+    );
+    triggers.execute_events(&[kill(0), kill(0), kill(0)]);
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_action_histogram() {
+    let mut triggers = Triggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(0),
+            },
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::ActivateQuest { id: QuestHandle(2) },
+        ],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    let histogram = triggers.action_histogram(|action| match action {
+        GameAction::ActivateQuest { .. } => "activate quest",
+        GameAction::CompleteQuest { .. } => "complete quest",
+        GameAction::FailQuest { .. } => "fail quest",
+        GameAction::ActivateMonster { .. } | GameAction::DeactivateMonster { .. } => "monster",
+    });
+    assert_eq!(
+        histogram.into_iter().collect::<Vec<_>>(),
+        vec![("activate quest", 3), ("complete quest", 1), ("monster", 1)]
+    );
+    assert_eq!(triggers.consume_all_actions().count(), 5);
+}
+
+#[test]
+fn test_xor() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 1),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            xor(vec![
+                event_count(
+                    GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(0) }),
+                    1,
+                ),
+                event_count(kill(1), 2),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            xor(vec![
+                event_count(kill(2), 1),
+                event_count(kill(2), 1) & event_count(kill(3), 1),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            xor(vec![event_count(kill(4), 1), event_count(kill(4), 1)]),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The first branch of the second trigger completes through the cascade, which unsubscribes the other branch.
+    triggers.execute_event(&kill(1));
+    assert_eq!(triggers.progress(1.into()), Some((0.5, 1.0)));
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+        ]
+    );
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 1.0)));
+
+    // Only the first branch completes, the second one would complete with the same event if it had already seen the other.
+    triggers.execute_event(&kill(2));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(1) })
+    );
+    triggers.execute_event(&kill(3));
+    assert_eq!(triggers.consume_action(), None);
+
+    // Both branches complete on the same event, so the trigger fails.
+    triggers.execute_event(&kill(4));
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(3.into()), Some((0.0, 1.0)));
+    let mut failed_trigger = Trigger::new(
+        "".to_string(),
+        xor(vec![event_count(kill(4), 1), event_count(kill(4), 1)]),
+        vec![GameAction::FailQuest { id: QuestHandle(2) }],
+    )
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        failed_trigger.execute_event(&kill(4)),
+        (
+            vec![],
+            vec![
+                TriggerConditionUpdate::Unsubscribe(GameEventIdentifier::KilledMonster {
+                    id: MonsterHandle(4)
+                }),
+                TriggerConditionUpdate::Unsubscribe(GameEventIdentifier::KilledMonster {
+                    id: MonsterHandle(4)
+                }),
+            ]
+        )
+    );
+    assert!(failed_trigger.condition().failed());
+    assert!(!failed_trigger.completed());
+    assert_eq!(failed_trigger.subscriptions(), vec![]);
+    assert_eq!(failed_trigger.execute_event(&kill(4)), (vec![], vec![]));
+}
+
+#[test]
+fn test_any_n_reset() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut condition = any_n(
+        vec![
+            event_count(kill(0), 1),
+            event_count(kill(1), 1),
+            event_count(kill(2), 2),
+            event_count(kill(3), 1),
+        ],
+        3,
+    )
+    .compile(&|x| x);
+    let initial_subscriptions = condition.subscriptions();
+
+    // Fulfill two branches and partially progress a third one.
+    for event in [kill(0), kill(1), kill(2)] {
+        assert!(!condition.execute_event(&event).1);
+    }
+    assert_eq!(condition.current_progress(), 2.5);
+    assert_eq!(condition.subscriptions().len(), 2);
+
+    condition.reset();
+    assert_eq!(condition.current_progress(), 0.0);
+    assert_eq!(condition.required_progress(), 3.0);
+    assert!(!condition.completed());
+    let mut subscriptions = condition.subscriptions();
+    subscriptions.sort();
+    assert_eq!(subscriptions, initial_subscriptions);
+
+    // All three branches are required again, and the partial progress was discarded.
+    for event in [kill(0), kill(1), kill(2)] {
+        assert!(!condition.execute_event(&event).1);
+    }
+    assert!(condition.execute_event(&kill(2)).1);
+    assert!(condition.completed());
+}
+
+#[test]
+fn test_repeat() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let reward = GameAction::ActivateQuest { id: QuestHandle(0) };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "every third kill".to_string(),
+            event_count(kill.clone(), 3),
+            vec![reward.clone()],
+        )
+        .with_repeat(true),
+        Trigger::new(
+            "third reward".to_string(),
+            event_count(GameEvent::Action(reward.clone()), 3),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        )
+        .with_repeat(true),
+        Trigger::<GameEvent, _>::new(
+            "instant".to_string(),
+            none(),
+            vec![GameAction::FailQuest { id: QuestHandle(0) }],
+        )
+        .with_repeat(true),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // Instantly completed triggers fire only once.
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::FailQuest { id: QuestHandle(0) }]
+    );
+
+    let mut fired = Vec::new();
+    for i in 1..=9 {
+        triggers.execute_event(&kill);
+        if triggers.consume_action() == Some(reward.clone()) {
+            fired.push(i);
+        }
+        assert_eq!(triggers.progress(0.into()), Some(((i % 3) as f64, 3.0)));
+    }
+    assert_eq!(fired, vec![3, 6, 9]);
+    // The cascade re-arms the second trigger as well.
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }]
+    );
+    assert_eq!(triggers.progress(1.into()), Some((0.0, 3.0)));
+}
+
+#[test]
+fn test_once() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill(0), 2),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    let handle = triggers.once(
+        kill(0).identifier(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+    );
+    assert_eq!(handle, 1.into());
+    assert_eq!(triggers.progress(handle), Some((0.0, 1.0)));
+
+    triggers.execute_event(&kill(1));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(1) }]
+    );
+
+    // The ephemeral trigger is gone, while the other trigger is unaffected.
+    assert_eq!(triggers.progress(handle), None);
+    assert_eq!(triggers.tags_of(handle), None);
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }]
+    );
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+}
+
+#[test]
+fn test_neq() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            neq(health(10)),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            neq(health(10)),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&health(10));
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 1.0)));
+
+    triggers.execute_event(&health(5));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(1) },
+        ]
+    );
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 1.0)));
+
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        neq(health(10)),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&health(15));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_display() {
+    let condition: TriggerCondition<GameEvent> = none() & never() | none();
+    assert_eq!(condition.to_string(), "none & never | none");
+    let condition: TriggerCondition<GameEvent> = none() & (never() | none());
+    assert_eq!(condition.to_string(), "none & (never | none)");
+    let condition: TriggerCondition<GameEvent> = or(vec![or(vec![none(), never()]), none()]);
+    assert_eq!(condition.to_string(), "(none | never) | none");
+    let condition: TriggerCondition<GameEvent> = and(vec![and(vec![none(), never()]), none()]);
+    assert_eq!(condition.to_string(), "(none & never) & none");
+    assert_eq!(and::<GameEvent>(vec![none()]).to_string(), "and([none])");
+
+    let condition = sequence(vec![
+        event_count(GameEvent::HealthChanged { health: 5 }, 2)
+            | geq(GameEvent::HealthChanged { health: 10 }),
+        not(never(), 3),
+    ]);
+    assert_eq!(
+        condition.to_string(),
+        "sequence([event_count(HealthChanged { health: 5 }, 2) | geq(HealthChanged { health: 10 }), not(never, 3)])"
+    );
+    assert_eq!(
+        condition.to_infix_string(&|event| format!("{:?}", event.identifier())),
+        "sequence([event_count(HealthChanged, 2) | geq(HealthChanged), not(never, 3)])"
+    );
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_hottest_triggers() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(
+        (0..4)
+            .map(|id| {
+                Trigger::new(
+                    format!("quest {id}"),
+                    event_count(kill(id), 1000),
+                    vec![GameAction::CompleteQuest {
+                        id: QuestHandle(id),
+                    }],
+                )
+            })
+            .collect(),
+    )
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.hottest_triggers(3), vec![]);
+
+    for i in 0..100 {
+        triggers.execute_event(&kill(match i % 10 {
+            0..=5 => 2,
+            6..=8 => 0,
+            _ => 3,
+        }));
+    }
+    assert_eq!(
+        triggers.hottest_triggers(2),
+        vec![(TriggerHandle::from(2), 60), (TriggerHandle::from(0), 30)]
+    );
+    assert_eq!(
+        triggers.hottest_triggers(10),
+        vec![
+            (TriggerHandle::from(2), 60),
+            (TriggerHandle::from(0), 30),
+            (TriggerHandle::from(3), 10),
+        ]
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_stats_event_outcomes() {
+    use event_trigger_action_system::Stats;
+
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill(0), 2),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.stats(), &Stats::default());
+
+    triggers.execute_event(&kill(1));
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.stats(),
+        &Stats {
+            events_without_subscribers: 1,
+            events_progressed_only: 1,
+            events_completing_triggers: 0,
+            actions_produced: 0,
+            max_cascade_depth: 0,
+            trigger_fires: vec![],
+        }
+    );
+
+    // The event of the produced action counts as well, even though no trigger is subscribed to it.
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.stats(),
+        &Stats {
+            events_without_subscribers: 2,
+            events_progressed_only: 1,
+            events_completing_triggers: 1,
+            actions_produced: 1,
+            max_cascade_depth: 0,
+            trigger_fires: vec![1],
+        }
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_stats_after_unsubscribing() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new("".to_string(), event_count(kill(0), 1), vec![]),
+        Trigger::new("".to_string(), event_count(kill(0), 2), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&kill(0));
+    triggers.execute_event(&kill(0));
+    assert_eq!(triggers.stats().events_completing_triggers, 2);
+
+    // No trigger is subscribed to the identifier anymore.
+    triggers.execute_event(&kill(0));
+    assert_eq!(triggers.stats().events_without_subscribers, 1);
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_stats_cascade() {
+    use event_trigger_action_system::Stats;
+
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 1),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    // The events of the actions produced when compiling count, but these actions do not count as produced.
+    assert_eq!(
+        triggers.stats(),
+        &Stats {
+            events_without_subscribers: 1,
+            ..Stats::default()
+        }
+    );
+
+    // The event of the last action is not received by any trigger, so it does not deepen the cascade.
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.stats(),
+        &Stats {
+            events_without_subscribers: 2,
+            events_progressed_only: 0,
+            events_completing_triggers: 2,
+            actions_produced: 2,
+            max_cascade_depth: 1,
+            trigger_fires: vec![0, 1, 1],
+        }
+    );
+    assert_eq!(triggers.stats().events(), 4);
+
+    triggers.reset_stats();
+    assert_eq!(triggers.stats(), &Stats::default());
+}
+
+#[test]
+fn test_exactly_n() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let branches = || {
+        vec![
+            event_count(kill(0), 1),
+            event_count(kill(1), 1),
+            event_count(kill(2), 1),
+            event_count(kill(2), 1),
+        ]
+    };
+
+    // The n-th completion completes the condition and unsubscribes the remaining branches.
+    let mut condition = exactly_n(branches(), 2).compile(&|x| x);
+    assert!(!condition.execute_event(&kill(0)).1);
+    assert_eq!(condition.current_progress(), 1.0);
+    let (updates, result, progress) = condition.execute_event(&kill(1));
+    assert!(result);
+    assert_eq!(progress, 2.0);
+    assert_eq!(updates.len(), 3);
+    assert_eq!(condition.subscriptions(), vec![]);
+
+    // Two branches completing on the same event both count, which may exceed n.
+    let mut condition = exactly_n(branches(), 2).compile(&|x| x);
+    assert!(!condition.execute_event(&kill(0)).1);
+    assert!(!condition.execute_event(&kill(2)).1);
+    assert!(condition.failed());
+    assert!(!condition.completed());
+    assert_eq!(condition.current_progress(), 1.0);
+    assert_eq!(condition.subscriptions(), vec![]);
+    assert_eq!(condition.execute_event(&kill(1)), (vec![], false, 1.0));
+
+    // If they reach n exactly, the condition completes.
+    let mut condition = exactly_n(branches(), 2).compile(&|x| x);
+    assert!(condition.execute_event(&kill(2)).1);
+
+    assert_eq!(exactly_n(branches(), 4).simplify(), and(branches()));
+    assert_eq!(
+        exactly_n(vec![none(), none(), event_count(kill(0), 1)], 1).simplify(),
+        never()
+    );
+    assert_eq!(
+        exactly_n(
+            vec![none(), event_count(kill(0), 1), event_count(kill(1), 1)],
+            2
+        )
+        .simplify(),
+        xor(vec![event_count(kill(0), 1), event_count(kill(1), 1)])
+    );
+}
+
+#[test]
+fn test_at_most_n() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let branches = || {
+        vec![
+            event_count(kill(0), 1),
+            event_count(kill(1), 1),
+            event_count(kill(2), 3),
+        ]
+    };
+
+    let mut condition = at_most_n(branches(), 1, 3).compile(&|x| x);
+    assert!(!condition.execute_event(&kill(0)).1);
+    assert!(!condition.execute_event(&kill(3)).1);
+    assert!(!condition.execute_event(&kill(2)).1);
+    assert!(condition.execute_event(&kill(2)).1);
+
+    let mut condition = at_most_n(branches(), 1, 3).compile(&|x| x);
+    assert!(!condition.execute_event(&kill(0)).1);
+    assert!(!condition.execute_event(&kill(1)).1);
+    assert!(condition.failed());
+    assert!(!condition.execute_event(&kill(2)).1);
+    assert!(!condition.completed());
+}
+
+#[test]
+fn test_iter_mut() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "first".to_string(),
+            event_count(kill(0), 1),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "second".to_string(),
+            event_count(kill(1), 1),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ]);
+    for trigger in triggers.iter_mut() {
+        trigger.id_str = format!("quest: {}", trigger.id_str);
+    }
+    triggers.map_conditions(|condition| condition & event_count(kill(2), 1));
+
+    let mut triggers = triggers.compile(&|x| x, &|x| x);
+    assert!(triggers.handle_by_id("quest: first").is_some());
+    assert!(triggers.handle_by_id("quest: second").is_some());
+    assert_eq!(triggers.handle_by_id("first"), None);
+    triggers.execute_event(&kill(0));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill(2));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_peek_action() {
+    let mut triggers = Triggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+        ],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    assert_eq!(
+        triggers.peek_action(),
+        Some(&GameAction::ActivateQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(
+        triggers.peek_actions().cloned().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+        ]
+    );
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(
+        triggers.peek_action(),
+        Some(&GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.peek_action(), None);
+    assert_eq!(triggers.peek_actions().count(), 0);
+}
+
+#[test]
+fn test_handle_order() {
+    let quest = |id: usize| {
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(id),
+                },
+                id + 1,
+            ),
+            vec![GameAction::CompleteQuest {
+                id: QuestHandle(id),
+            }],
+        )
+        .with_tags(vec![format!("quest {id}")])
+    };
+    let mut triggers = Triggers::new((0..5).map(quest).collect()).compile(&|x| x, &|x| x);
+
+    // Handles are assigned in the order of the authored triggers, changing this breaks stored handles.
+    for id in 0..5 {
+        let handle = triggers.handle_for_index(id).unwrap();
+        assert_eq!(handle, TriggerHandle::from(id));
+        assert_eq!(triggers.tags_of(handle), Some(&[format!("quest {id}")][..]));
+        assert_eq!(triggers.progress(handle), Some((0.0, (id + 1) as f64)));
+    }
+    let handle = triggers.once(
+        GameEventIdentifier::HealthChanged,
+        vec![GameAction::FailQuest { id: QuestHandle(0) }],
+    );
+    assert_eq!(Some(handle), triggers.handle_for_index(5));
+}
+
+#[test]
+fn test_handle_for_index_out_of_range() {
+    let triggers = Triggers::<GameEvent, GameAction>::new(Vec::new()).compile(&|x| x, &|x| x);
+    assert_eq!(triggers.handle_for_index(0), None);
+}
+
+#[test]
+fn test_handle_for_index_removed() {
+    let mut triggers = Triggers::<GameEvent, GameAction>::new(vec![
+        Trigger::new("".to_string(), never(), vec![]),
+        Trigger::new("".to_string(), never(), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+    let handle = triggers.handle_for_index(0).unwrap();
+    assert!(triggers.remove_trigger(handle).is_some());
+    assert_eq!(triggers.handle_for_index(0), None);
+    assert_eq!(triggers.handle_for_index(1), Some(TriggerHandle::from(1)));
+}
+
+#[test]
+fn test_action_queue_len() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![
+                GameAction::ActivateQuest { id: QuestHandle(0) },
+                GameAction::ActivateQuest { id: QuestHandle(1) },
+            ],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill.clone(), 1),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The initial actions are queued by the constructor.
+    assert_eq!(triggers.action_queue_len(), 2);
+    assert!(triggers.has_pending_actions());
+
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.action_queue_len(), 3);
+    assert_eq!(triggers.consume_all_actions().count(), 3);
+    assert_eq!(triggers.action_queue_len(), 0);
+    assert!(!triggers.has_pending_actions());
+}
+
+static CANCEL: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug)]
+struct CancellingEvent(usize);
+
+impl TriggerEvent for CancellingEvent {
+    type Action = CountedAction;
+    type Identifier = CountedIdentifier;
+
+    fn identifier(&self) -> Self::Identifier {
+        CountedIdentifier(self.0)
+    }
+
+    fn value_geq(&self, other: &Self) -> Option<bool> {
+        Some(self.0 >= other.0)
+    }
+
+    fn value_geq_progress(&self, other: &Self) -> Option<f64> {
+        Some((self.0 as f64 / other.0 as f64).clamp(0.0, 1.0))
+    }
+}
+
+impl From<CountedAction> for CancellingEvent {
+    /// Converting the first action cancels the cascade.
+    fn from(action: CountedAction) -> Self {
+        if action.0 == 1 {
+            CANCEL.store(true, atomic::Ordering::SeqCst);
+        }
+        Self(action.0)
+    }
+}
+
+#[test]
+fn test_execute_event_cancellable() {
+    let trigger = |event, actions| Trigger::new("".to_string(), event_count(event, 1), actions);
+    let mut triggers = Triggers::new(vec![
+        trigger(CancellingEvent(0), vec![CountedAction(1), CountedAction(2)]),
+        trigger(CancellingEvent(1), vec![CountedAction(3)]),
+        trigger(CancellingEvent(3), vec![CountedAction(5)]),
+        trigger(CancellingEvent(2), vec![CountedAction(4)]),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The first cascade step sets the flag, so the cascade stops before executing the event of action 3.
+    assert!(triggers.execute_event_cancellable(&CancellingEvent(0), &CANCEL));
+    assert!(triggers.has_pending_events());
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![CountedAction(1), CountedAction(2), CountedAction(3)]
+    );
+
+    // The remaining cascade continues in the original order.
+    triggers.pump();
+    assert!(!triggers.has_pending_events());
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![CountedAction(5), CountedAction(4)]
+    );
+}
+
+#[test]
+fn test_remaining_counts() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let kill_identifier = |id| GameEventIdentifier::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut trigger = Trigger::<GameEvent, GameAction>::new(
+        "".to_string(),
+        and(vec![event_count(kill(0), 3), event_count(kill(1), 5)]),
+        vec![],
+    )
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        trigger.condition().remaining_counts(),
+        vec![(kill_identifier(0), 3), (kill_identifier(1), 5)]
+    );
+
+    trigger.execute_event(&kill(0));
+    trigger.execute_event(&kill(1));
+    trigger.execute_event(&kill(1));
+    assert_eq!(
+        trigger.condition().remaining_counts(),
+        vec![(kill_identifier(0), 2), (kill_identifier(1), 3)]
+    );
+
+    trigger.execute_event(&kill(0));
+    trigger.execute_event(&kill(0));
+    assert_eq!(
+        trigger.condition().remaining_counts(),
+        vec![(kill_identifier(1), 3)]
+    );
+}
+
+#[test]
+fn test_duplicate_subscriptions() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            and(vec![
+                event_count(kill.clone(), 1),
+                event_count(kill.clone(), 2),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            any_n(
+                vec![
+                    event_count(kill.clone(), 1),
+                    event_count(kill.clone(), 3),
+                    never(),
+                ],
+                2,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The first sub-conditions complete and unsubscribe, but the others are still subscribed to the same identifier.
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    triggers.execute_event(&kill);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(1) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_coalesce_per_event() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let refresh = GameAction::ActivateQuest { id: QuestHandle(0) };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill.clone(), 1),
+            vec![refresh.clone()],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill.clone(), 1),
+            vec![
+                refresh.clone(),
+                GameAction::CompleteQuest { id: QuestHandle(0) },
+            ],
+        ),
+        // Would complete if both actions were executed as events.
+        Trigger::new(
+            "".to_string(),
+            event_count(GameEvent::Action(refresh.clone()), 2),
+            vec![GameAction::FailQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert!(!triggers.coalesce_per_event());
+    triggers.set_coalesce_per_event(true);
+    assert!(triggers.coalesce_per_event());
+
+    triggers.execute_event(&kill);
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![refresh, GameAction::CompleteQuest { id: QuestHandle(0) }]
+    );
+    assert_eq!(triggers.progress(2.into()), Some((1.0, 2.0)));
+}
+
+#[test]
+fn test_execute_event_limited() {
+    let activate = |id| GameAction::ActivateQuest {
+        id: QuestHandle(id),
+    };
+    let mut chain = vec![Trigger::new(
+        "".to_string(),
+        event_count(
+            GameEvent::KilledMonster {
+                id: MonsterHandle(0),
+            },
+            1,
+        ),
+        vec![activate(0)],
+    )];
+    chain.extend((0..5).map(|id| {
+        Trigger::new(
+            "".to_string(),
+            event_count(GameEvent::Action(activate(id)), 1),
+            vec![activate(id + 1)],
+        )
+    }));
+    let mut triggers = Triggers::new(chain).compile(&|x| x, &|x| x);
+
+    assert!(triggers.execute_event_limited(
+        &GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        2
+    ));
+    assert!(triggers.has_pending_events());
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![activate(0), activate(1)]
+    );
+
+    triggers.pump();
+    assert!(!triggers.has_pending_events());
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        (2..6).map(activate).collect::<Vec<_>>()
+    );
+
+    // The limit only applies to the limited event.
+    assert!(!triggers.execute_event_limited(
+        &GameEvent::KilledMonster {
+            id: MonsterHandle(1),
+        },
+        0
+    ));
+}
+
+#[test]
+fn test_deferred_cascade() {
+    let activate = |id| GameAction::ActivateQuest {
+        id: QuestHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![activate(0)],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(GameEvent::Action(activate(0)), 1),
+            vec![activate(1)],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(GameEvent::Action(activate(1)), 1),
+            vec![activate(2)],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.set_cascade_mode(CascadeMode::Deferred);
+    assert_eq!(triggers.cascade_mode(), CascadeMode::Deferred);
+
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(triggers.consume_action(), Some(activate(0)));
+    assert_eq!(triggers.consume_action(), None);
+    assert!(triggers.has_pending_events());
+
+    triggers.pump();
+    assert_eq!(triggers.consume_action(), Some(activate(1)));
+    assert_eq!(triggers.consume_action(), None);
+    assert!(triggers.has_pending_events());
+
+    triggers.pump();
+    assert_eq!(triggers.consume_action(), Some(activate(2)));
+    assert_eq!(triggers.consume_action(), None);
+
+    // The event of the last action has no subscribers, so it changes nothing.
+    triggers.pump();
+    assert_eq!(triggers.consume_action(), None);
+    assert!(!triggers.has_pending_events());
+}
+
+#[test]
+fn test_deferred_cascade_repeat_self_feeding() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let echo = GameAction::ActivateQuest { id: QuestHandle(0) };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "kill".to_string(),
+            event_count(kill.clone(), 1),
+            vec![echo.clone()],
+        ),
+        Trigger::new(
+            "echo".to_string(),
+            event_count(GameEvent::Action(echo.clone()), 1),
+            vec![echo.clone()],
+        )
+        .with_repeat(true),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.set_cascade_mode(CascadeMode::Deferred);
+
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.consume_action(), Some(echo.clone()));
+    assert_eq!(triggers.consume_action(), None);
+
+    // The echo trigger fires on the action of the kill trigger, but not on its own action in the next pump.
+    triggers.pump();
+    assert_eq!(triggers.consume_action(), Some(echo.clone()));
+    assert!(triggers.has_pending_events());
+    triggers.pump();
+    assert_eq!(triggers.consume_action(), None);
+    assert!(!triggers.has_pending_events());
+}
+
+#[test]
+fn test_remove_trigger() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let quest = |id| {
+        Trigger::new(
+            format!("quest {id}"),
+            event_count(kill(0), id + 1),
+            vec![GameAction::CompleteQuest {
+                id: QuestHandle(id),
+            }],
+        )
+        .with_tags(vec!["quest".to_string()])
+    };
+    let mut triggers = Triggers::new(vec![quest(0), quest(1), quest(2)]).compile(&|x| x, &|x| x);
+
+    let removed = triggers.remove_trigger(1.into()).unwrap();
+    assert_eq!(removed.id_str, "quest 1");
+    assert!(triggers.remove_trigger(1.into()).is_none());
+    assert!(triggers.remove_trigger(3.into()).is_none());
+    assert_eq!(triggers.progress(1.into()), None);
+    assert_eq!(triggers.handles_by_tag("quest"), vec![0.into(), 2.into()]);
+
+    for _ in 0..3 {
+        triggers.execute_event(&kill(0));
+    }
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(2) },
+        ]
+    );
+    assert_eq!(triggers.progress(2.into()), Some((3.0, 3.0)));
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+}
+
+#[test]
+fn test_decaying_count() {
+    let collect = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let penalty = GameEvent::HealthChanged { health: 0 };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        decaying_count(collect.clone(), 3, penalty.clone()),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    // The count floors at zero.
+    triggers.execute_event(&penalty);
+    triggers.execute_event(&penalty);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+
+    triggers.execute_events(&[collect.clone(), collect.clone(), penalty.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 3.0)));
+    triggers.execute_events(&[collect.clone(), penalty.clone(), collect.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+    assert_eq!(triggers.consume_action(), None);
+
+    triggers.execute_event(&collect);
+    assert_eq!(triggers.progress(0.into()), Some((3.0, 3.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+
+    let mut condition = decaying_count(collect.clone(), 2, penalty.clone());
+    condition.scale_requirements(2.0);
+    assert_eq!(
+        condition,
+        decaying_count(collect.clone(), 4, penalty.clone())
+    );
+    assert_eq!(decaying_count(collect, 0, penalty).simplify(), none());
+}
+
+#[test]
+fn test_add_trigger() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(
+            GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+            1,
+        ),
+        vec![GameAction::ActivateMonster {
+            id: MonsterHandle(1),
+        }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&kill(0));
+
+    // A completed trigger fires immediately, including its cascade.
+    let handle = triggers.add_trigger(
+        Trigger::<GameEvent, _>::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        )
+        .compile(&|x| x, &|x| x),
+    );
+    assert_eq!(handle, 1.into());
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(1)
+            },
+        ]
+    );
+
+    let handle = triggers.add_trigger(
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        )
+        .with_tags(vec!["dynamic".to_string()])
+        .compile(&|x| x, &|x| x),
+    );
+    assert_eq!(handle, 2.into());
+    assert_eq!(triggers.handles_by_tag("dynamic"), vec![handle]);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill(0));
+    assert_eq!(triggers.progress(handle), Some((1.0, 2.0)));
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(1) })
+    );
+}
+
+#[test]
+fn test_quest_summary() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "low health".to_string(),
+            geq(GameEvent::HealthChanged { health: 10 }),
+            vec![GameAction::ActivateMonster {
+                id: MonsterHandle(0),
+            }],
+        ),
+        Trigger::new(
+            "monster".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::Action(GameAction::ActivateMonster {
+                        id: MonsterHandle(0),
+                    }),
+                    1,
+                ),
+                geq(GameEvent::MonsterHealthChanged {
+                    id: MonsterHandle(0),
+                    health: 20,
+                }),
+            ]),
+            vec![GameAction::DeactivateMonster {
+                id: MonsterHandle(0),
+            }],
+        ),
+        Trigger::new(
+            "no damage".to_string(),
+            not(geq(GameEvent::HealthChanged { health: 7 }), 5),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&GameEvent::HealthChanged { health: 5 });
+    triggers.execute_event(&GameEvent::HealthChanged { health: 10 });
+    let summary = triggers.quest_summary();
+    assert_eq!(summary.len(), 3);
+
+    assert_eq!(summary[0].handle, 0.into());
+    assert_eq!(summary[0].id, "low health");
+    assert_eq!(summary[0].status, TriggerStatus::Completed);
+    assert_eq!((summary[0].current, summary[0].required), (1.0, 1.0));
+    assert_eq!(summary[0].waiting_on, vec![]);
+
+    assert_eq!(summary[1].id, "monster");
+    assert_eq!(summary[1].status, TriggerStatus::Active);
+    assert_eq!(
+        (summary[1].current, summary[1].required),
+        triggers.progress(1.into()).unwrap()
+    );
+    assert_eq!(
+        summary[1].waiting_on,
+        vec![GameEventIdentifier::MonsterHealthChanged {
+            id: MonsterHandle(0)
+        }]
+    );
+
+    assert_eq!(summary[2].id, "no damage");
+    assert_eq!(summary[2].status, TriggerStatus::Failed);
+    assert_eq!(summary[2].waiting_on, vec![]);
+}
+
+#[test]
+fn test_reset_trigger() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill.clone(), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::<GameEvent, _>::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.consume_all_actions().count(), 1);
+
+    triggers.execute_events(&[kill.clone(), kill.clone()]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+
+    // The reset trigger needs the required events again.
+    triggers.reset_trigger(0.into());
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 2.0)));
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.consume_action(), None);
+
+    // Resetting a trigger that is completed from the start fires it again immediately.
+    triggers.reset_trigger(1.into());
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(1) }]
+    );
+    triggers.reset_trigger(2.into());
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+}
+
+#[test]
+fn test_new_with_priority() {
+    let triggers = || {
+        vec![
+            Trigger::<GameEvent, _>::new(
+                "spawn monster".to_string(),
+                none(),
+                vec![GameAction::ActivateMonster {
+                    id: MonsterHandle(0),
+                }],
+            ),
+            Trigger::new(
+                "activate region".to_string(),
+                none(),
+                vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+            ),
+            Trigger::new(
+                "monster spawned in region".to_string(),
+                sequence(vec![
+                    event_count(
+                        GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(0) }),
+                        1,
+                    ),
+                    event_count(
+                        GameEvent::Action(GameAction::ActivateMonster {
+                            id: MonsterHandle(0),
+                        }),
+                        1,
+                    ),
+                ]),
+                vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+            ),
+        ]
+        .into_iter()
+        .map(|trigger| trigger.compile(&|x| x, &|x| x))
+        .collect::<Vec<_>>()
+    };
+
+    // In trigger order, the monster is spawned before the region is activated.
+    let mut compiled_triggers = CompiledTriggers::new(triggers());
+    assert_eq!(
+        compiled_triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateMonster {
+                id: MonsterHandle(0)
+            },
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+        ]
+    );
+
+    // Activating the region first lets the dependent trigger fire.
+    let mut compiled_triggers = CompiledTriggers::new_with_priority(triggers(), |action| {
+        matches!(action, GameAction::ActivateQuest { .. })
+    });
+    assert_eq!(
+        compiled_triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(0) },
+            GameAction::ActivateMonster {
+                id: MonsterHandle(0)
+            },
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+        ]
+    );
+}
+
+#[test]
+fn test_recompute() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut condition = any_n(
+        vec![
+            event_count(kill(0), 1),
+            event_count(kill(1), 1),
+            event_count(kill(2), 4),
+        ],
+        3,
+    )
+    .compile(&|x| x);
+    condition.execute_event(&kill(0));
+    condition.execute_event(&kill(2));
+    assert_eq!(condition.required_progress(), 6.0);
+    assert_eq!(condition.current_progress(), 2.5);
+
+    // Lowering the threshold below the number of fulfilled sub-conditions completes the condition,
+    // which then does not need its events anymore.
+    assert_eq!(
+        condition.set_any_n_threshold(1),
+        vec![
+            TriggerConditionUpdate::Unsubscribe(GameEventIdentifier::KilledMonster {
+                id: MonsterHandle(1)
+            }),
+            TriggerConditionUpdate::Unsubscribe(GameEventIdentifier::KilledMonster {
+                id: MonsterHandle(2)
+            }),
+        ]
+    );
+    assert!(condition.completed());
+    assert_eq!(condition.required_progress(), 1.0);
+    assert_eq!(condition.current_progress(), 1.0);
+
+    // Raising it again makes the condition incomplete, with progress clamped to the new requirement.
+    assert_eq!(condition.set_any_n_threshold(2).len(), 2);
+    assert!(!condition.completed());
+    assert_eq!(condition.required_progress(), 2.0);
+    assert_eq!(condition.current_progress(), 1.0);
+    assert!(condition.execute_event(&kill(1)).1);
+
+    // Requiring more sub-conditions than exist makes the condition fail.
+    condition.set_any_n_threshold(4);
+    assert!(condition.failed());
+    assert_eq!(condition.recompute(), vec![]);
+    assert!(condition.failed());
+
+    // In a trigger system, a trigger completes when recomputing completes its condition.
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            any_n(vec![event_count(kill(0), 1), event_count(kill(1), 1)], 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new("".to_string(), event_count(kill(1), 1), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&kill(0));
+    assert!(!triggers.set_any_n_threshold(1.into(), 1));
+    assert!(triggers.set_any_n_threshold(0.into(), 1));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }]
+    );
+    assert_eq!(triggers.is_completed(0.into()), Some(true));
+    assert_eq!(triggers.active_trigger_count(), 1);
+    triggers.remove_trigger(1.into());
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+}
+
+#[test]
+fn test_progress_by_id() {
+    let triggers = Triggers::<_, CountedAction>::new(vec![
+        Trigger::new("a".to_string(), event_count(CountedEvent(0), 2), vec![]),
+        Trigger::new("b".to_string(), event_count(CountedEvent(1), 4), vec![]),
+    ]);
+    let mut triggers = triggers.compile(&|x| x, &|x| x);
+    triggers.execute_event(&CountedEvent(1));
+
+    let handle = triggers.handle_by_id("b").unwrap();
+    assert_eq!(Some(handle), triggers.handle_for_index(1));
+    assert_eq!(triggers.progress_by_id("b"), triggers.progress(handle));
+    assert_eq!(triggers.progress_by_id("b"), Some((1.0, 4.0)));
+    assert_eq!(triggers.progress_by_id("c"), None);
+
+    triggers.remove_trigger(handle);
+    assert_eq!(triggers.progress_by_id("b"), None);
+}
+
+#[test]
+fn test_or_pruned() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let kill_identifier = |id| GameEventIdentifier::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        or_pruned(
+            vec![
+                event_count(kill(0), 3),
+                event_count(kill(1), 3),
+                event_count(kill(2), 100),
+            ],
+            1,
+        ),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    // The branch requiring far more progress than the others is pruned from the start.
+    assert_eq!(
+        triggers.quest_summary()[0].waiting_on,
+        vec![kill_identifier(0), kill_identifier(1)]
+    );
+
+    // Once the first branch leads by more than the lag, the second branch is pruned as well.
+    triggers.execute_events(&[kill(0), kill(0)]);
+    assert_eq!(
+        triggers.quest_summary()[0].waiting_on,
+        vec![kill_identifier(0)]
+    );
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+
+    // Pruned branches are not evaluated anymore.
+    triggers.execute_events(&[kill(1), kill(1), kill(1)]);
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+
+    // The leading branch still completes the condition.
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert!(triggers.quest_summary()[0].waiting_on.is_empty());
+
+    // Resetting restores all branches before pruning again.
+    triggers.reset_trigger(0.into());
+    let mut waiting_on = triggers.quest_summary().remove(0).waiting_on;
+    waiting_on.sort();
+    assert_eq!(waiting_on, vec![kill_identifier(0), kill_identifier(1)]);
+    triggers.execute_events(&[kill(1), kill(1), kill(1)]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_diff() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let triggers = Triggers::new(vec![
+        Trigger::new(
+            "first".to_string(),
+            event_count(kill(0), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "second".to_string(),
+            event_count(kill(1), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    let mut left = triggers.clone();
+    let mut right = triggers;
+    assert_eq!(left.diff(&right), vec![]);
+
+    left.execute_events(&[kill(0), kill(1), kill(1)]);
+    right.execute_events(&[kill(0), kill(1), kill(0)]);
+    let diff = left.diff(&right);
+    assert_eq!(diff.len(), 2);
+    assert_eq!(diff[0].handle, 0.into());
+    assert_eq!(diff[0].left.as_ref().unwrap().current, 1.0);
+    assert_eq!(
+        diff[0].right.as_ref().unwrap().status,
+        TriggerStatus::Completed
+    );
+    assert_eq!(diff[1].handle, 1.into());
+    assert_eq!(
+        diff[1].left.as_ref().unwrap().status,
+        TriggerStatus::Completed
+    );
+    assert_eq!(diff[1].right.as_ref().unwrap().current, 1.0);
+
+    // Triggers existing in only one system are reported as well.
+    left.remove_trigger(0.into());
+    let mut right = left.clone();
+    right.once(
+        GameEventIdentifier::KilledMonster {
+            id: MonsterHandle(2),
+        },
+        vec![],
+    );
+    let diff: Vec<TriggerDiff<_>> = left.diff(&right);
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].handle, 2.into());
+    assert!(diff[0].left.is_none());
+    assert!(diff[0].right.is_some());
+}
+
+#[test]
+fn test_iter_active_triggers() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(1), 1),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    let active = |triggers: &CompiledTriggers<GameEvent>| {
+        triggers
+            .iter_active_triggers()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(active(&triggers), vec![1.into(), 2.into()]);
+
+    triggers.execute_event(&kill(0));
+    assert_eq!(active(&triggers), vec![1.into(), 2.into()]);
+    triggers.execute_event(&kill(0));
+    assert_eq!(
+        triggers.peek_actions().last(),
+        Some(&GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(active(&triggers), vec![2.into()]);
+
+    triggers.reset_trigger(1.into());
+    assert_eq!(active(&triggers), vec![1.into(), 2.into()]);
+    triggers.remove_trigger(2.into());
+    assert_eq!(active(&triggers), vec![1.into()]);
+    let handle = triggers.add_trigger(
+        Trigger::new("".to_string(), event_count(kill(2), 1), vec![]).compile(&|x| x, &|x| x),
+    );
+    assert_eq!(active(&triggers), vec![1.into(), handle]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_active_triggers() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::<_, GameAction>::new(
+            "".to_string(),
+            geq(GameEvent::HealthChanged { health: 10 }),
+            vec![],
+        ),
+        Trigger::new(
+            "".to_string(),
+            geq(GameEvent::HealthChanged { health: 5 }),
+            vec![],
+        ),
+        Trigger::new("".to_string(), none(), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&GameEvent::HealthChanged { health: 7 });
+
+    // The active triggers are not serialized, but rebuilt from the conditions.
+    let save = serde_json::to_value(&triggers).unwrap();
+    assert!(save["trigger_system"].get("active").is_none());
+    let mut loaded: CompiledTriggers<GameEvent> = serde_json::from_value(save).unwrap();
+    let active = |triggers: &CompiledTriggers<GameEvent>| {
+        triggers
+            .iter_active_triggers()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(active(&loaded), vec![0.into()]);
+
+    loaded.execute_event(&GameEvent::HealthChanged { health: 12 });
+    assert_eq!(active(&loaded), vec![]);
+}
+
+#[test]
+fn test_leaf_events() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let health = GameEvent::HealthChanged { health: 10 };
+    let condition = sequence(vec![event_count(kill.clone(), 2), geq(health.clone())]);
+    assert_eq!(
+        condition.leaf_events().collect::<Vec<_>>(),
+        vec![
+            (&kill, ConditionKindTag::EventCount),
+            (&health, ConditionKindTag::Geq)
+        ]
+    );
+    assert_eq!(none::<GameEvent>().leaf_events().count(), 0);
+}
+
+#[test]
+fn test_is_completed() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill.clone(), 2),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.is_completed(0.into()), Some(false));
+
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.is_completed(0.into()), Some(false));
+    assert!(!triggers.has_pending_actions());
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.is_completed(0.into()), Some(true));
+    assert!(triggers.has_pending_actions());
+
+    assert_eq!(triggers.is_completed(1.into()), None);
+}
+
+#[test]
+fn test_hold_between() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let tick = GameEvent::Action(GameAction::ActivateMonster {
+        id: MonsterHandle(0),
+    });
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        hold_between(health(40), health(60), tick.clone(), 3),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    // Ticks before the first value do not count.
+    triggers.execute_events(&[tick.clone(), health(50), tick.clone(), tick.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+
+    // Leaving the range mid-hold resets the count, and ticks out of range do not count.
+    triggers.execute_events(&[health(61), tick.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+
+    // Values within the range do not reset the count.
+    triggers.execute_events(&[health(40), tick.clone(), health(60), tick.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&tick);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert!(triggers.quest_summary()[0].waiting_on.is_empty());
+}
+
+#[test]
+fn test_total_progress() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let triggers = Triggers::new(vec![
+        Trigger::new("".to_string(), none(), vec![]),
+        Trigger::new("".to_string(), event_count(kill.clone(), 3), vec![]),
+        Trigger::new(
+            "".to_string(),
+            geq(GameEvent::HealthChanged { health: 10 }),
+            vec![],
+        ),
+    ]);
+    let mut triggers = triggers.compile(&|x| x, &|x| x);
+    assert_eq!(triggers.total_progress(), (0.0, 4.0));
+
+    triggers.execute_events(&[kill.clone(), GameEvent::HealthChanged { health: 5 }]);
+    assert_eq!(triggers.total_progress(), (1.5, 4.0));
+
+    triggers.execute_events(&[kill.clone(), kill, GameEvent::HealthChanged { health: 10 }]);
+    assert_eq!(triggers.total_progress(), (4.0, 4.0));
+
+    let triggers: CompiledTriggers<GameEvent> =
+        Triggers::<_, GameAction>::new(vec![Trigger::new("".to_string(), none(), vec![])])
+            .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.total_progress(), (0.0, 0.0));
+}
+
+#[test]
+fn test_repeat_self_feeding() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let echo = GameAction::ActivateQuest { id: QuestHandle(0) };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "kill".to_string(),
+            event_count(kill.clone(), 1),
+            vec![echo.clone()],
+        ),
+        Trigger::new(
+            "echo".to_string(),
+            event_count(GameEvent::Action(echo.clone()), 1),
+            vec![echo.clone()],
+        )
+        .with_repeat(true),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    // The echo trigger fires on the action of the kill trigger, but not on its own action.
+    triggers.execute_event(&kill);
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![echo.clone(), echo.clone()]
+    );
+
+    // The echo trigger stays armed for events from other sources.
+    triggers.execute_event(&GameEvent::Action(echo.clone()));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![echo.clone()]
+    );
+
+    // The source of a deferred action is kept, so the echo trigger does not fire on its own action when pumping.
+    triggers.reset_trigger(0.into());
+    assert!(triggers.execute_event_limited(&kill, 2));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![echo.clone(), echo]
+    );
+    assert!(triggers.has_pending_events());
+    triggers.pump();
+    assert_eq!(triggers.consume_action(), None);
+    assert!(!triggers.has_pending_events());
+}
+
+#[test]
+fn test_set_enabled() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill.clone(), 2),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    let handle = triggers.handle_for_index(0).unwrap();
+
+    triggers.execute_event(&kill);
+    triggers.set_enabled(handle, false);
+    triggers.execute_events(&[kill.clone(), kill.clone()]);
+    assert_eq!(triggers.progress(handle), Some((1.0, 2.0)));
+    assert_eq!(triggers.consume_action(), None);
+
+    // Re-enabling keeps the progress made before disabling.
+    triggers.set_enabled(handle, true);
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.progress(handle), Some((2.0, 2.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+
+    // A trigger added disabled with a condition completed from the start fires when it is enabled.
+    let instant = triggers.add_trigger(
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        )
+        .compile(&|x| x, &|x| x)
+        .with_enabled(false),
+    );
+    assert_eq!(triggers.consume_action(), None);
+    triggers.set_enabled(instant, true);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(1) })
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_lenient() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill.clone(), 1),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+        ],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&kill);
+
+    // Simulate a save from a version that had an additional action variant.
+    let mut save = serde_json::to_value(&triggers).unwrap();
+    save["action_queue"][0] = serde_json::json!({ "GrantReward": { "amount": 3 } });
+    assert!(serde_json::from_value::<CompiledTriggers<GameEvent>>(save.clone()).is_err());
+
+    let mut loaded = CompiledTriggers::<GameEvent>::deserialize_lenient(save).unwrap();
+    assert_eq!(
+        loaded.consume_all_actions().collect::<Vec<_>>(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(1) }]
+    );
+    assert_eq!(loaded.progress(0.into()), Some((1.0, 1.0)));
+
+    // Unknown actions whose events are pending are dropped as well.
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill.clone(), 1),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.set_cascade_mode(CascadeMode::Deferred);
+    triggers.execute_event(&kill);
+    assert!(triggers.has_pending_events());
+
+    let mut save = serde_json::to_value(&triggers).unwrap();
+    save["trigger_system"]["pending_actions"][0] =
+        serde_json::json!({ "GrantReward": { "amount": 3 } });
+    assert!(serde_json::from_value::<CompiledTriggers<GameEvent>>(save.clone()).is_err());
+
+    let loaded = CompiledTriggers::<GameEvent>::deserialize_lenient(save).unwrap();
+    assert!(!loaded.has_pending_events());
+    assert_eq!(
+        loaded.peek_actions().collect::<Vec<_>>(),
+        vec![&GameAction::CompleteQuest { id: QuestHandle(0) }]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_lenient_panic() {
+    /// Reads the given bytes, then panics.
+    struct PanickingReader(std::io::Cursor<Vec<u8>>);
+
+    impl std::io::Read for PanickingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => panic!("the save is truncated"),
+                read => Ok(read),
+            }
+        }
+    }
+
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(kill.clone(), 1),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&kill);
+    let mut save = serde_json::to_value(&triggers).unwrap();
+    save["action_queue"][0] = serde_json::json!({ "GrantReward": { "amount": 3 } });
+
+    let mut truncated = serde_json::to_vec(&save).unwrap();
+    truncated.pop();
+    let result = std::panic::catch_unwind(|| {
+        CompiledTriggers::<GameEvent>::deserialize_lenient(
+            &mut serde_json::Deserializer::from_reader(PanickingReader(std::io::Cursor::new(
+                truncated,
+            ))),
+        )
+    });
+    assert!(result.is_err());
+
+    // The deserialization on this thread is strict again.
+    assert!(serde_json::from_value::<CompiledTriggers<GameEvent>>(save).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_triggers() {
+    let json = r#"{"triggers": [
+        {
+            "id_str": "hunt",
+            "condition": {"Sequence": {"conditions": [
+                {"EventCount": {"event": {"KilledMonster": {"id": 0}}, "required": 2}},
+                {"Or": {"conditions": [
+                    {"Geq": {"event": {"HealthChanged": {"health": 10}}}},
+                    {"Not": {"condition": "Never", "window": 1}}
+                ]}}
+            ]}},
+            "actions": [{"CompleteQuest": {"id": 0}}],
+            "tags": ["main"]
+        }
+    ]}"#;
+    let definitions: Triggers<GameEvent, GameAction> = serde_json::from_str(json).unwrap();
+    let mut triggers = definitions.clone().compile(&|x| x, &|x| x);
+    assert_eq!(triggers.handles_by_tag("main"), vec![0.into()]);
+
+    triggers.execute_events(&[
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+    ]);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&GameEvent::HealthChanged { health: 12 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+
+    // The recursive condition round-trips.
+    let round_trip: Triggers<GameEvent, GameAction> =
+        serde_json::from_str(&serde_json::to_string(&definitions).unwrap()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&round_trip).unwrap(),
+        serde_json::to_value(&definitions).unwrap()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_old_snapshot() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "first".to_string(),
+            geq(health(10)),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "second".to_string(),
+            sequence(vec![geq(health(10)), geq(health(50))]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&health(20));
+
+    // The same state as serialized by version 0.7.6, where the actions of fired triggers are `null`.
+    let old_save = include_str!("saves/version_0.json");
+    let mut loaded: CompiledTriggers<GameEvent> = serde_json::from_str(old_save).unwrap();
+    let round_trip: CompiledTriggers<GameEvent> =
+        serde_json::from_value(serde_json::to_value(&loaded).unwrap()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&round_trip).unwrap(),
+        serde_json::to_value(&loaded).unwrap()
+    );
+    assert_eq!(loaded.progress(1.into()), triggers.progress(1.into()));
+
+    // The fired trigger does not fire again.
+    assert_eq!(loaded.flush_ready(), vec![]);
+    assert_eq!(loaded.active_trigger_count(), 1);
+    loaded.execute_event(&health(60));
+    assert_eq!(
+        loaded.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(0) },
+            GameAction::CompleteQuest { id: QuestHandle(1) },
+        ]
+    );
+
+    // Newer saves keep triggers that completed without firing, even without the fields added alongside the version.
+    let triggers = CompiledTriggers::new(vec![Trigger::<GameEvent, _>::new(
+        "".to_string(),
+        none(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+    )
+    .compile(&|x| x, &|x| x)
+    .with_enabled(false)]);
+    let mut save = serde_json::to_value(&triggers).unwrap();
+    assert_eq!(save["trigger_system"]["format_version"], 1);
+    save["trigger_system"]["triggers"][0]["enabled"] = serde_json::json!(true);
+    save["trigger_system"]
+        .as_object_mut()
+        .unwrap()
+        .remove("last_advances");
+    let mut loaded: CompiledTriggers<GameEvent> = serde_json::from_value(save).unwrap();
+    assert_eq!(
+        loaded.flush_ready(),
+        vec![GameAction::ActivateQuest { id: QuestHandle(1) }]
+    );
+}
+
+#[test]
+fn test_active_trigger_count() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new("".to_string(), event_count(kill(0), 1), vec![]),
+        Trigger::new("".to_string(), event_count(kill(1), 1), vec![]),
+        Trigger::new("".to_string(), event_count(kill(2), 1), vec![]),
+        Trigger::new("".to_string(), not(event_count(kill(3), 1), 2), vec![]),
+        Trigger::<_, GameAction>::new("".to_string(), none(), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.active_trigger_count(), 4);
+
+    triggers.set_enabled(1.into(), false);
+    assert_eq!(triggers.active_trigger_count(), 3);
+    triggers.execute_event(&kill(0));
+    assert_eq!(triggers.active_trigger_count(), 2);
+    triggers.execute_event(&kill(3));
+    assert_eq!(triggers.active_trigger_count(), 1);
+    triggers.set_enabled(1.into(), true);
+    assert_eq!(triggers.active_trigger_count(), 2);
+}
+
+#[test]
+fn test_max_cascade() {
+    let step = |id| GameAction::ActivateMonster {
+        id: MonsterHandle(id),
+    };
+    // A chain of repeating triggers, each step producing the next one twice.
+    let triggers = (0..5)
+        .map(|id| {
+            Trigger::new(
+                "".to_string(),
+                event_count(GameEvent::Action(step(id)), 1),
+                vec![step(id + 1), step(id + 1)],
+            )
+            .with_repeat(true)
+        })
+        .collect();
+    let mut triggers = Triggers::new(triggers).compile(&|x| x, &|x| x);
+    triggers.set_max_cascade(Some(2));
+
+    // Depth zero produces two actions, whose events at depth one produce four, whose events at depth two produce eight.
+    triggers.execute_event(&GameEvent::Action(step(0)));
+    assert_eq!(triggers.action_queue_len(), 2 + 4 + 8);
+    assert_eq!(triggers.dropped_cascade_events(), 8);
+
+    triggers.consume_all_actions().for_each(drop);
+    triggers.set_max_cascade(Some(0));
+    triggers.execute_event(&GameEvent::Action(step(1)));
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![step(2), step(2)]
+    );
+    assert_eq!(triggers.dropped_cascade_events(), 10);
+}
+
+#[test]
+fn test_retry() {
+    let enter = GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(0) });
+    let hit = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let death = GameEvent::FailedMonster {
+        id: MonsterHandle(0),
+    };
+    let boss_fight = || {
+        Triggers::new(vec![Trigger::new(
+            "boss".to_string(),
+            sequence(vec![
+                event_count(enter.clone(), 1),
+                retry(event_count(hit.clone(), 2), death.clone(), 2),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        )])
+        .compile(&|x| x, &|x| x)
+    };
+
+    // Succeeding within the budget.
+    let mut triggers = boss_fight();
+    triggers.execute_events(&[death.clone(), enter.clone(), hit.clone(), death.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 3.0)));
+    triggers.execute_events(&[hit.clone(), death.clone(), hit.clone(), hit.clone()]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+
+    // Exhausting the budget.
+    let mut triggers = boss_fight();
+    triggers.execute_events(&[enter.clone(), death.clone(), death.clone(), hit.clone()]);
+    assert_eq!(triggers.quest_summary()[0].status, TriggerStatus::Active);
+    triggers.execute_event(&death);
+    assert_eq!(triggers.quest_summary()[0].status, TriggerStatus::Failed);
+    assert!(triggers.quest_summary()[0].waiting_on.is_empty());
+    triggers.execute_events(&[hit.clone(), hit]);
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_deep_cascade() {
+    const DEPTH: usize = 10_000;
+    let quest = |id| GameAction::ActivateQuest {
+        id: QuestHandle(id),
+    };
+    let mut triggers = Triggers::new(
+        (0..DEPTH)
+            .map(|id| {
+                Trigger::new(
+                    id.to_string(),
+                    event_count(GameEvent::Action(quest(id)), 1),
+                    vec![quest(id + 1)],
+                )
+            })
+            .collect(),
+    )
+    .compile(&|x| x, &|x| x);
+
+    // Run on a small stack, such that a recursive cascade would overflow.
+    std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(move || {
+            triggers.execute_event(&GameEvent::Action(quest(0)));
+            for id in 1..=DEPTH {
+                assert_eq!(triggers.consume_action(), Some(quest(id)));
+            }
+            assert_eq!(triggers.consume_action(), None);
+            assert_eq!(triggers.active_trigger_count(), 0);
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn test_many_events_without_subscribers() {
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        event_count(
+            GameEvent::KilledMonster {
+                id: MonsterHandle(0),
+            },
+            1,
+        ),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    for health in 0..100_000 {
+        triggers.execute_event(&GameEvent::HealthChanged { health });
+    }
+    assert_eq!(triggers.consume_action(), None);
+    #[cfg(feature = "stats")]
+    assert_eq!(triggers.stats().events_without_subscribers, 100_000);
+
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_eq_any() {
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        eq_any(vec![
+            GameEvent::HealthChanged { health: 10 },
+            GameEvent::HealthChanged { health: 20 },
+            GameEvent::MonsterHealthChanged {
+                id: MonsterHandle(0),
+                health: 5,
+            },
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        triggers.quest_summary()[0].waiting_on,
+        vec![
+            GameEventIdentifier::HealthChanged,
+            GameEventIdentifier::MonsterHealthChanged {
+                id: MonsterHandle(0)
+            }
+        ]
+    );
+
+    triggers.execute_events(&[
+        GameEvent::HealthChanged { health: 5 },
+        GameEvent::HealthChanged { health: 15 },
+        GameEvent::HealthChanged { health: 25 },
+        GameEvent::MonsterHealthChanged {
+            id: MonsterHandle(1),
+            health: 5,
+        },
+    ]);
+    assert_eq!(triggers.consume_action(), None);
+
+    triggers.execute_event(&GameEvent::HealthChanged { health: 20 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert!(triggers.quest_summary()[0].waiting_on.is_empty());
+}
+
+#[test]
+fn test_execute_event_collect() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            none(),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    assert_eq!(
+        triggers.execute_event_collect(&GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        }),
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::CompleteQuest { id: QuestHandle(1) },
+        ]
+    );
+    assert_eq!(
+        triggers.execute_event_collect(&GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        }),
+        vec![]
+    );
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::ActivateQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_set_trigger_id() {
+    let mut triggers = Triggers::<GameEvent, GameAction>::new(vec![
+        Trigger::new("first".to_string(), never(), vec![]),
+        Trigger::new("second".to_string(), never(), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    assert!(triggers.set_trigger_id(0.into(), "renamed"));
+    assert_eq!(triggers.handle_by_id("renamed"), Some(0.into()));
+    assert_eq!(triggers.handle_by_id("first"), None);
+
+    assert!(!triggers.set_trigger_id(0.into(), "second"));
+    assert!(!triggers.set_trigger_id(2.into(), "third"));
+    assert!(triggers.set_trigger_id(1.into(), "second"));
+    assert_eq!(triggers.handle_by_id("renamed"), Some(0.into()));
+    assert_eq!(triggers.handle_by_id("second"), Some(1.into()));
+}
+
+#[test]
+fn test_predicate() {
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        sequence(vec![
+            predicate(GameEvent::HealthChanged { health: 0 }, "even health"),
+            predicate(GameEvent::HealthChanged { health: 0 }, "even health"),
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    // Unregistered predicates never match.
+    triggers.execute_event(&GameEvent::HealthChanged { health: 2 });
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 2.0)));
+
+    triggers.register_predicate(
+        "even health",
+        |event| matches!(event, GameEvent::HealthChanged { health } if health % 2 == 0),
+    );
+    triggers.execute_events(&[
+        GameEvent::HealthChanged { health: 3 },
+        GameEvent::MonsterHealthChanged {
+            id: MonsterHandle(0),
+            health: 4,
+        },
+    ]);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 2.0)));
+    triggers.execute_event(&GameEvent::HealthChanged { health: 4 });
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 2.0)));
+    triggers.execute_event(&GameEvent::HealthChanged { health: 7 });
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&GameEvent::HealthChanged { health: 8 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_accumulate() {
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        accumulate(GameEvent::GoldDropped { amount: 0 }, 100.0, "gold"),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.register_extractor("gold", |event| match event {
+        GameEvent::GoldDropped { amount } => *amount as f64,
+        _ => 0.0,
+    });
+
+    triggers.execute_events(&[
+        GameEvent::GoldDropped { amount: 30 },
+        GameEvent::HealthChanged { health: 100 },
+        GameEvent::GoldDropped { amount: 30 },
+    ]);
+    assert_eq!(triggers.progress(0.into()), Some((60.0, 100.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&GameEvent::GoldDropped { amount: 50 });
+    assert_eq!(triggers.progress(0.into()), Some((100.0, 100.0)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_accumulate_fractional_target() {
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        accumulate(GameEvent::GoldDropped { amount: 0 }, 2.5, "gold"),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.register_extractor("gold", |event| match event {
+        GameEvent::GoldDropped { amount } => *amount as f64 / 2.0,
+        _ => 0.0,
+    });
+
+    triggers.execute_events(&[
+        GameEvent::GoldDropped { amount: 2 },
+        GameEvent::GoldDropped { amount: 2 },
+    ]);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 2.5)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&GameEvent::GoldDropped { amount: 1 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_non_finite_value_try_compile() {
+    let gold = GameEvent::GoldDropped { amount: 0 };
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        for condition in [
+            accumulate(gold.clone(), value, "gold"),
+            delta_geq(gold.clone(), value, "gold"),
+        ] {
+            assert_eq!(
+                condition
+                    .clone()
+                    .simplify()
+                    .try_compile(&|x| x)
+                    .unwrap_err(),
+                CompileError::NonFiniteValue
+            );
+            assert_eq!(
+                Trigger::<GameEvent, GameAction>::new("".to_string(), condition, vec![])
+                    .try_compile(&|x| x, &|x| x)
+                    .unwrap_err(),
+                CompileError::NonFiniteValue
+            );
+        }
+    }
+    assert_eq!(
+        accumulate(gold, 0.0, "gold").simplify(),
+        TriggerCondition::None
+    );
+}
+
+#[test]
+fn test_geq_incomparable() {
+    // Readings without a value cannot be compared, even though they have the same identifier.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct Reading(Option<usize>);
+
+    impl From<()> for Reading {
+        fn from(_: ()) -> Self {
+            Reading(None)
+        }
+    }
+
+    impl TriggerEvent for Reading {
+        type Action = ();
+        type Identifier = ();
+
+        fn identifier(&self) -> Self::Identifier {}
+
+        fn value_geq(&self, other: &Self) -> Option<bool> {
+            Some(self.0? >= other.0?)
+        }
+
+        fn value_geq_progress(&self, other: &Self) -> Option<f64> {
+            Some((self.0? as f64 / other.0? as f64).min(1.0))
+        }
+    }
+
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        geq(Reading(Some(10))),
+        vec![()],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&Reading(Some(5)));
+    triggers.execute_event(&Reading(None));
+    assert_eq!(triggers.progress(0.into()), Some((0.5, 1.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&Reading(Some(10)));
+    assert_eq!(triggers.consume_action(), Some(()));
+
+    // Sub-conditions of an `and` receive the events of their siblings, which are not comparable.
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        and(vec![
+            geq(GameEvent::HealthChanged { health: 10 }),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_events(&[
+        GameEvent::HealthChanged { health: 5 },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+    ]);
+    assert_eq!(triggers.progress(0.into()), Some((1.5, 2.0)));
+    triggers.execute_event(&GameEvent::HealthChanged { health: 10 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_replay_without_actions() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                2,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::FailedMonster {
+                        id: MonsterHandle(3),
+                    },
+                    1,
+                ),
+                event_count(
+                    GameEvent::KilledMonster {
+                        id: MonsterHandle(3),
+                    },
+                    1,
+                ),
+            ]),
+            vec![GameAction::DeactivateMonster {
+                id: MonsterHandle(3),
+            }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.replay_without_actions(&[
+        GameEvent::FailedMonster {
+            id: MonsterHandle(2),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        GameEvent::KilledMonster {
+            id: MonsterHandle(3),
+        },
+        GameEvent::FailedMonster {
+            id: MonsterHandle(3),
+        },
+    ]);
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 2.0)));
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 1.0)));
+    // The cascade of the discarded actions is suppressed as well.
+    assert_eq!(triggers.progress(2.into()), Some((0.0, 1.0)));
+    assert_eq!(triggers.progress(3.into()), Some((1.0, 2.0)));
+
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(3),
+    });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::DeactivateMonster {
+            id: MonsterHandle(3)
+        })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_any_n_progress() {
+    let killed = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            any_n((0..5).map(|id| event_count(killed(id), 1)).collect(), 3),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new("".to_string(), event_count(killed(0), 1), vec![]),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.any_n_progress(0.into()), Some((0, 3)));
+    assert_eq!(triggers.any_n_progress(1.into()), None);
+
+    triggers.execute_events(&[killed(1), killed(1), killed(4)]);
+    assert_eq!(triggers.any_n_progress(0.into()), Some((2, 3)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&killed(0));
+    assert_eq!(triggers.any_n_progress(0.into()), Some((3, 3)));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_predicate_any_event() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "boss".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::KilledMonster {
+                        id: MonsterHandle(0),
+                    },
+                    1,
+                ),
+                predicate_any_event("boss"),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "both".to_string(),
+            and(vec![
+                event_count(
+                    GameEvent::KilledMonster {
+                        id: MonsterHandle(7),
+                    },
+                    2,
+                ),
+                predicate_any_event("boss"),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.register_predicate("boss", |event| {
+        matches!(
+            event,
+            GameEvent::FailedMonster {
+                id: MonsterHandle(7)
+            } | GameEvent::MonsterHealthChanged {
+                id: MonsterHandle(7),
+                ..
+            }
+        )
+    });
+
+    // No trigger is subscribed to this identifier specifically, but the wildcard subscription of the second trigger receives it.
+    let boss_event = GameEvent::MonsterHealthChanged {
+        id: MonsterHandle(7),
+        health: 10,
+    };
+    triggers.execute_event(&boss_event);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 2.0)));
+    assert_eq!(triggers.progress(1.into()), Some((1.0, 3.0)));
+
+    // Triggers subscribed to an identifier as well as to all events receive the event only once.
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(7),
+    });
+    assert_eq!(triggers.progress(1.into()), Some((2.0, 3.0)));
+
+    triggers.execute_events(&[
+        GameEvent::KilledMonster {
+            id: MonsterHandle(0),
+        },
+        boss_event,
+    ]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_predicate_any_event_cascade() {
+    let spawn = GameAction::ActivateMonster {
+        id: MonsterHandle(7),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "spawn".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![spawn.clone()],
+        ),
+        Trigger::new(
+            "boss".to_string(),
+            predicate_any_event("boss"),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.register_predicate("boss", |event| {
+        matches!(event, GameEvent::Action(GameAction::ActivateMonster { .. }))
+    });
+
+    // No trigger is subscribed to the identifier of the cascaded event, but the wildcard subscription receives it.
+    let mut breadth_first = triggers.clone();
+    breadth_first.set_cascade_order(CascadeOrder::BreadthFirst);
+
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            spawn.clone(),
+            GameAction::CompleteQuest { id: QuestHandle(0) }
+        ]
+    );
+
+    breadth_first.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(
+        breadth_first.consume_all_actions().collect::<Vec<_>>(),
+        vec![spawn, GameAction::CompleteQuest { id: QuestHandle(0) }]
+    );
+}
+
+#[test]
+fn test_mixed_subscription_cleanup() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(7),
+    };
+    let other = Trigger::new(
+        "other".to_string(),
+        event_count(kill.clone(), 2),
+        vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+    );
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "mixed".to_string(),
+            and(vec![
+                event_count(kill.clone(), 1),
+                predicate_any_event("low health"),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        other.clone(),
+    ])
+    .compile(&|x| x, &|x| x);
+    let predicate_calls = Arc::new(AtomicUsize::new(0));
+    let calls = Arc::clone(&predicate_calls);
+    triggers.register_predicate("low health", move |event| {
+        calls.fetch_add(1, atomic::Ordering::SeqCst);
+        matches!(event, GameEvent::HealthChanged { health } if *health < 10)
+    });
+    let other_only = Triggers::new(vec![other]).compile(&|x| x, &|x| x);
+
+    // Completing the trigger with an exact and a wildcard subscription removes both,
+    // but keeps the exact subscription of the other trigger to the same identifier.
+    triggers.execute_event(&kill);
+    triggers.execute_event(&GameEvent::HealthChanged { health: 5 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(
+        triggers.memory_report().subscriptions,
+        other_only.memory_report().subscriptions
+    );
+
+    // The completed trigger receives no events through either of its former subscriptions.
+    let calls = predicate_calls.load(atomic::Ordering::SeqCst);
+    triggers.execute_event(&GameEvent::HealthChanged { health: 3 });
+    assert_eq!(predicate_calls.load(atomic::Ordering::SeqCst), calls);
+    #[cfg(feature = "metrics")]
+    let evaluations = triggers.hottest_triggers(2);
+    triggers.execute_event(&kill);
+    assert_eq!(predicate_calls.load(atomic::Ordering::SeqCst), calls);
+    #[cfg(feature = "metrics")]
+    {
+        let (mixed, other) = (TriggerHandle::from(0), TriggerHandle::from(1));
+        let count = |evaluations: &[(TriggerHandle, u64)], handle| {
+            evaluations
+                .iter()
+                .find(|(evaluated, _)| *evaluated == handle)
+                .map(|(_, count)| *count)
+        };
+        let after = triggers.hottest_triggers(2);
+        assert_eq!(count(&after, mixed), count(&evaluations, mixed));
+        assert_eq!(
+            count(&after, other),
+            count(&evaluations, other).map(|count| count + 1)
+        );
+    }
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(1) })
+    );
+    assert_eq!(triggers.memory_report().subscriptions, 0);
+}
+
+#[test]
+fn test_builder() {
+    let mut triggers = CompiledTriggersBuilder::new()
+        .trigger(Trigger::new(
+            "kill".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ))
+        .triggers(vec![Trigger::new("".to_string(), never(), vec![])])
+        .validate_all()
+        .event_compiler(|event| event)
+        .action_compiler(|action| action)
+        .build()
+        .unwrap();
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_builder_validation() {
+    let builder = || {
+        CompiledTriggersBuilder::new()
+            .trigger(Trigger::<GameEvent, GameAction>::new(
+                "empty".to_string(),
+                or(vec![never(), and(vec![])]),
+                vec![],
+            ))
+            .event_compiler(|event| event)
+            .action_compiler(|action| action)
+    };
+    assert!(builder().build().is_ok());
+    assert_eq!(
+        builder().validate_all().build().err(),
+        Some(BuildError::InvalidCondition {
+            id_str: "empty".to_string(),
+            error: ConditionError::EmptyOperator("and"),
+        })
+    );
+
+    assert_eq!(
+        any_n(vec![never::<GameEvent>()], 2).validate(),
+        Err(ConditionError::ThresholdTooHigh { n: 2, available: 1 })
+    );
+    let result = CompiledTriggersBuilder::new()
+        .triggers(vec![
+            Trigger::<GameEvent, GameAction>::new("quest".to_string(), never(), vec![]),
+            Trigger::new("quest".to_string(), never(), vec![]),
+        ])
+        .validate_all()
+        .event_compiler(|event| event)
+        .action_compiler(|action| action)
+        .build();
+    assert_eq!(
+        result.err(),
+        Some(BuildError::DuplicateId("quest".to_string()))
+    );
+
+    // Compile errors are returned even without validation.
+    let result = CompiledTriggersBuilder::new()
+        .trigger(Trigger::<GameEvent, GameAction>::new(
+            "stale".to_string(),
+            stale_for("unknown", 3),
+            vec![],
+        ))
+        .event_compiler(|event| event)
+        .action_compiler(|action| action)
+        .build();
+    assert_eq!(
+        result.err(),
+        Some(BuildError::Compile(CompileError::UnknownTriggerId {
+            index: 0
+        }))
+    );
+}
+
+#[test]
+fn test_count_within() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let other = GameEvent::HealthChanged { health: 10 };
+    let new_triggers = || {
+        Triggers::new(vec![Trigger::new(
+            "".to_string(),
+            count_within(kill.clone(), 3, 10),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        )])
+        .compile(&|x| x, &|x| x)
+    };
+
+    // Spaced more than ten events apart, the kills never complete the condition.
+    let mut triggers = new_triggers();
+    for _ in 0..5 {
+        triggers.execute_event(&kill);
+        triggers.execute_events(&vec![other.clone(); 10]);
+    }
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+
+    // Older kills fall out of the window.
+    let mut triggers = new_triggers();
+    triggers.execute_events(&[kill.clone(), kill.clone()]);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+    triggers.execute_events(&vec![other.clone(); 8]);
+    triggers.execute_event(&other);
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 3.0)));
+    triggers.execute_events(&[kill.clone(), other, kill.clone()]);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&kill);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_count_within_event_stream() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let spawn = |id| GameAction::ActivateMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            count_within(kill.clone(), 2, 3),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(kill.clone(), 1),
+            vec![spawn(1), spawn(2)],
+        )
+        .with_repeat(true),
+    ])
+    .compile(&|x| x, &|x| x);
+    let mut disabled = triggers.clone();
+
+    // The events created from the actions of the second trigger count, so the first kill falls out of the window.
+    triggers.execute_events(&[kill.clone(), kill.clone()]);
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![spawn(1), spawn(2), spawn(1), spawn(2)]
+    );
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 2.0)));
+
+    // Events executed while the trigger is disabled count as well.
+    disabled.set_enabled(1.into(), false);
+    disabled.execute_event(&kill);
+    disabled.set_enabled(0.into(), false);
+    disabled.execute_events(&vec![GameEvent::HealthChanged { health: 0 }; 2]);
+    disabled.set_enabled(0.into(), true);
+    disabled.execute_event(&kill);
+    assert_eq!(disabled.progress(0.into()), Some((1.0, 2.0)));
+    assert_eq!(disabled.consume_action(), None);
+}
+
+#[test]
+fn test_fail_on() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let death = GameEvent::HealthChanged { health: 0 };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "quest".to_string(),
+            event_count(kill(0), 3),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        )
+        .with_fail_on(
+            vec![death.clone()],
+            vec![GameAction::FailQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "completed quest".to_string(),
+            event_count(kill(1), 1),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        )
+        .with_fail_on(
+            vec![death.clone()],
+            vec![GameAction::FailQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_events(&[kill(0), kill(1)]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(1) })
+    );
+    assert_eq!(triggers.progress(0.into()), Some((1.0, 3.0)));
+
+    // Only the quest in progress fails, and it does not make progress anymore.
+    triggers.execute_event(&death);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::FailQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+    let handle = triggers.handle_by_id("quest").unwrap();
+    assert_eq!(
+        triggers.iter_triggers().next().unwrap().1.status(),
+        TriggerStatus::Failed
+    );
+    triggers.execute_events(&[kill(0), kill(0), death.clone()]);
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(handle), Some((1.0, 3.0)));
+
+    // Resetting the quest allows it to complete again.
+    triggers.reset_trigger(handle);
+    triggers.execute_events(&[kill(0), kill(0), kill(0), death]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_strict_sequence() {
+    let input = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let (a, b, c) = (input(0), input(1), input(2));
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "combo".to_string(),
+        strict_sequence(vec![
+            event_count(a.clone(), 1),
+            event_count(b.clone(), 1),
+            event_count(c.clone(), 1),
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    // A wrong input of the combo restarts it, and events outside of the combo are ignored.
+    triggers.execute_events(&[a.clone(), b.clone(), b.clone(), c.clone()]);
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress(0.into()), Some((0.0, 3.0)));
+    triggers.execute_events(&[a.clone(), b.clone(), input(3)]);
+    assert_eq!(triggers.progress(0.into()), Some((2.0, 3.0)));
+
+    // A wrong input that starts the combo counts as its first input.
+    triggers.execute_events(&[a.clone(), b.clone(), c]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert_eq!(triggers.progress(0.into()), Some((3.0, 3.0)));
+
+    // With a single identifier, the inputs are distinguished by their values.
+    let press = |health| GameEvent::HealthChanged { health };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "combo".to_string(),
+        strict_sequence(vec![
+            eq_any(vec![press(1)]),
+            eq_any(vec![press(2)]),
+            eq_any(vec![press(3)]),
+        ]),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_events(&[press(1), press(2), press(4), press(3)]);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_events(&[press(1), press(2), press(3)]);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_actions_preview() {
+    let kill = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let rewards = vec![
+        GameAction::CompleteQuest { id: QuestHandle(0) },
+        GameAction::ActivateQuest { id: QuestHandle(1) },
+    ];
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "quest".to_string(),
+        event_count(kill.clone(), 2),
+        rewards.clone(),
+    )])
+    .compile(&|x| x, &|x| x);
+    let handle = triggers.handle_for_index(0).unwrap();
+
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.actions(handle), Some(rewards.as_slice()));
+    assert_eq!(triggers.consume_action(), None);
+
+    triggers.execute_event(&kill);
+    assert_eq!(triggers.actions(handle), None);
+    assert_eq!(triggers.consume_all_actions().collect::<Vec<_>>(), rewards);
+
+    triggers.remove_trigger(handle);
+    assert_eq!(triggers.actions(handle), None);
+}
+
+#[test]
+fn test_numeric_events() {
+    let mut triggers = Triggers::new(vec![Trigger::new("".to_string(), geq(10i64), vec![1i64])])
+        .compile(&|x| x, &|x| x);
+    triggers.execute_event(&9);
+    assert_eq!(triggers.progress(0.into()), Some((0.9, 1.0)));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&11);
+    assert_eq!(triggers.consume_action(), Some(1));
+
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        and(vec![geq(10u64), event_count(0u64, 2)]),
+        vec![0u64],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&10);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&10);
+    assert_eq!(triggers.consume_action(), Some(0));
+
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        between(0.5f64, 1.5f64),
+        vec![f64::NAN],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&f64::NAN);
+    triggers.execute_event(&2.0);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&1.5);
+    assert!(triggers.consume_action().unwrap().is_nan());
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Health(usize);
+
+impl NumericTriggerEvent for Health {
+    type Action = ();
+    type Identifier = ();
+
+    fn identifier(&self) -> Self::Identifier {}
+
+    fn numeric_value(&self) -> Option<f64> {
+        Some(self.0 as f64)
+    }
+}
+
+impl From<()> for Health {
+    fn from(_: ()) -> Self {
+        Self(0)
+    }
+}
+
+#[test]
+fn test_numeric_trigger_event() {
+    let mut numeric = Triggers::new(vec![Trigger::new("".to_string(), geq(Health(80)), vec![])])
+        .compile(&|x| x, &|x| x);
+    let mut hand_written = Triggers::new(vec![Trigger::new(
+        "".to_string(),
+        geq(GameEvent::HealthChanged { health: 80 }),
+        vec![],
+    )])
+    .compile(&|x| x, &|x| x);
+
+    for health in [0, 20, 60, 79, 80, 100] {
+        numeric.execute_event(&Health(health));
+        hand_written.execute_event(&GameEvent::HealthChanged { health });
+        assert_eq!(numeric.progress(0.into()), hand_written.progress(0.into()));
+    }
+    assert_eq!(numeric.progress(0.into()), Some((1.0, 1.0)));
+}
+
+/// Records the messages of all events.
+#[cfg(feature = "tracing")]
+#[derive(Default)]
+struct MessageRecorder {
+    messages: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::field::Visit for &MessageRecorder {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.messages.lock().unwrap().push(format!("{value:?}"));
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::Subscriber for &'static MessageRecorder {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        event.record(&mut &**self);
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[test]
+#[cfg(feature = "tracing")]
+fn test_tracing() {
+    let recorder: &'static MessageRecorder = Box::leak(Box::default());
+    tracing::subscriber::with_default(recorder, || {
+        let mut triggers = Triggers::new(vec![Trigger::new(
+            "".to_string(),
+            event_count(GameEvent::GoldDropped { amount: 0 }, 2),
+            vec![],
+        )])
+        .compile(&|x| x, &|x| x);
+        triggers.execute_event(&GameEvent::GoldDropped { amount: 0 });
+        triggers.execute_event(&GameEvent::GoldDropped { amount: 0 });
+    });
+
+    assert_eq!(
+        *recorder.messages.lock().unwrap(),
+        vec![
+            "subscribe",
+            "execute event",
+            "trigger progressed",
+            "execute event",
+            "trigger completed",
+            "unsubscribe",
+        ]
+    );
+}
+
+#[test]
+fn test_cascade_order() {
+    let quest = |id| GameAction::ActivateQuest {
+        id: QuestHandle(id),
+    };
+    let cascade = |id| event_count(GameEvent::Action(quest(id)), 1);
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![quest(1), quest(2)],
+        ),
+        Trigger::new("".to_string(), cascade(1), vec![quest(3)]),
+        Trigger::new("".to_string(), cascade(2), vec![quest(4)]),
+        Trigger::new("".to_string(), cascade(3), vec![quest(5)]),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(triggers.cascade_order(), CascadeOrder::DepthFirst);
+    let mut breadth_first = triggers.clone();
+    breadth_first.set_cascade_order(CascadeOrder::BreadthFirst);
+
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(
+        triggers.consume_all_actions().collect::<Vec<_>>(),
+        vec![quest(1), quest(2), quest(3), quest(5), quest(4)]
+    );
+
+    breadth_first.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    assert_eq!(
+        breadth_first.consume_all_actions().collect::<Vec<_>>(),
+        vec![quest(1), quest(2), quest(3), quest(4), quest(5)]
+    );
+}
+
+#[test]
+fn test_stale_for() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let nudge = GameAction::ActivateMonster {
+        id: MonsterHandle(9),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "quest".to_string(),
+            event_count(kill(0), 3),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "nudge".to_string(),
+            stale_for("quest", 3),
+            vec![nudge.clone()],
+        ),
+        Trigger::new(
+            "late".to_string(),
+            sequence(vec![event_count(kill(1), 1), stale_for("quest", 2)]),
+            vec![GameAction::FailQuest { id: QuestHandle(0) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_event(&kill(0));
+    triggers.execute_event(&GameEvent::HealthChanged { health: 1 });
+    triggers.execute_event(&GameEvent::HealthChanged { health: 2 });
+    // Advancing the quest restarts the count.
+    triggers.execute_event(&kill(0));
+    for health in 0..3 {
+        triggers.execute_event(&GameEvent::HealthChanged { health });
+    }
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress_by_id("nudge"), Some((2.0, 3.0)));
+
+    // The next event after three idle events fires the nudge.
+    triggers.execute_event(&GameEvent::HealthChanged { health: 3 });
+    assert_eq!(triggers.consume_action(), Some(nudge));
+    assert_eq!(triggers.consume_action(), None);
+
+    // Idle events count also before the condition becomes active.
+    triggers.execute_event(&kill(1));
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&GameEvent::HealthChanged { health: 4 });
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::FailQuest { id: QuestHandle(0) })
+    );
+
+    // Resetting the quest restarts the count.
+    triggers.reset_trigger(0.into());
+    triggers.reset_trigger(1.into());
+    triggers.execute_event(&GameEvent::HealthChanged { health: 5 });
+    assert_eq!(triggers.progress_by_id("nudge"), Some((0.0, 3.0)));
+
+    assert_eq!(
+        Triggers::new(vec![
+            Trigger::new("quest".to_string(), none(), vec![]),
+            Trigger::new("nudge".to_string(), stale_for("missing", 1), vec![]),
+        ])
+        .try_compile(&|x: GameEvent| x, &|x: GameAction| x)
+        .err(),
+        Some(CompileError::UnknownTriggerId { index: 1 })
+    );
+
+    // Without a trigger with its trigger id, the condition never completes.
+    let mut triggers = CompiledTriggers::new(vec![
+        Trigger::new("quest".to_string(), event_count(kill(0), 1), vec![]).compile(&|x| x, &|x| x),
+        Trigger::new(
+            "nudge".to_string(),
+            stale_for("missing", 1),
+            vec![GameAction::FailQuest { id: QuestHandle(1) }],
+        )
+        .compile(&|x| x, &|x| x),
+    ]);
+    triggers.add_trigger(
+        Trigger::new(
+            "added".to_string(),
+            stale_for("missing", 0),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        )
+        .compile(&|x| x, &|x| x),
+    );
+    for health in 0..5 {
+        triggers.execute_event(&GameEvent::HealthChanged { health });
+    }
+    assert_eq!(triggers.consume_action(), None);
+    assert_eq!(triggers.progress_by_id("nudge"), Some((0.0, 1.0)));
+}
+
+#[test]
+fn test_execute_events_grouped() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(0), 1),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            event_count(
+                GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                1,
+            ),
+            vec![GameAction::FailQuest { id: QuestHandle(2) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    assert_eq!(
+        triggers.execute_events_grouped(&[kill(0), GameEvent::HealthChanged { health: 0 }]),
+        vec![
+            vec![
+                GameAction::ActivateQuest { id: QuestHandle(1) },
+                GameAction::FailQuest { id: QuestHandle(2) },
+            ],
+            vec![],
+        ]
+    );
+    assert_eq!(triggers.consume_action(), None);
+}
+
+#[test]
+fn test_trigger_state() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let definitions = Triggers::new(vec![
+        Trigger::new(
+            "".to_string(),
+            event_count(kill(2), 1),
+            vec![GameAction::ActivateQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "".to_string(),
+            sequence(vec![event_count(kill(0), 1), event_count(kill(1), 2)]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+    ]);
+    let mut original = definitions.clone().compile(&|x| x, &|x| x);
+    let mut copy = definitions.compile(&|x| x, &|x| x);
+    let handle = TriggerHandle::from(1);
+
+    original.execute_event(&kill(0));
+    original.execute_event(&kill(1));
+    let state: TriggerState<GameEvent> = original.trigger_state(handle).unwrap();
+    #[cfg(feature = "serde")]
+    let state = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+    original.execute_event(&kill(1));
+    assert_eq!(original.progress(handle), Some((3.0, 3.0)));
+
+    copy.apply_trigger_state(handle, state);
+    assert_eq!(copy.progress(handle), Some((2.0, 3.0)));
+    assert_eq!(copy.progress(0.into()), Some((0.0, 1.0)));
+
+    // The trigger is resubscribed to the events of the current step only.
+    copy.execute_event(&kill(0));
+    assert_eq!(copy.progress(handle), Some((2.0, 3.0)));
+    copy.execute_event(&kill(1));
+    assert_eq!(
+        copy.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+    assert!(original.trigger_state(TriggerHandle::from(2)).is_none());
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "kills".to_string(),
+            and(vec![event_count(kill(0), 2), event_count(kill(1), 1)]),
+            vec![GameAction::ActivateQuest { id: QuestHandle(1) }],
+        ),
+        Trigger::new(
+            "follow-up".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::Action(GameAction::ActivateQuest { id: QuestHandle(1) }),
+                    1,
+                ),
+                event_count(kill(2), 2),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+
+    triggers.execute_events(&[kill(0), kill(1)]);
+    let snapshot: TriggerSnapshot<GameEvent> = triggers.snapshot();
+    let events = [kill(0), kill(2), kill(2), kill(1)];
+    let run = |triggers: &mut CompiledTriggers<GameEvent>| {
+        triggers.execute_events(&events);
+        (
+            triggers.consume_all_actions().collect::<Vec<_>>(),
+            triggers.progress(1.into()),
+        )
+    };
+
+    let first = run(&mut triggers);
+    assert_eq!(
+        first.0,
+        vec![
+            GameAction::ActivateQuest { id: QuestHandle(1) },
+            GameAction::CompleteQuest { id: QuestHandle(1) },
+        ]
+    );
+    #[cfg(feature = "stats")]
+    let stats = triggers.stats().clone();
+    #[cfg(feature = "metrics")]
+    let hottest = triggers.hottest_triggers(2);
+    triggers.restore(snapshot.clone());
+    assert_eq!(run(&mut triggers), first);
+    triggers.restore(snapshot);
+    assert_eq!(run(&mut triggers), first);
+
+    // The statistics and metrics are rolled back as well.
+    #[cfg(feature = "stats")]
+    assert_eq!(triggers.stats(), &stats);
+    #[cfg(feature = "metrics")]
+    assert_eq!(triggers.hottest_triggers(2), hottest);
+}
+
+#[cfg(feature = "shared")]
+#[test]
+fn test_shared() {
+    use event_trigger_action_system::shared;
+    use std::sync::Arc;
+
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let template = Arc::new(and(vec![event_count(kill(0), 2), event_count(kill(1), 1)]));
+    let quests = |condition: &dyn Fn() -> TriggerCondition<GameEvent>| {
+        Triggers::new(
+            (0..100)
+                .map(|id| {
+                    Trigger::new(
+                        format!("quest {id}"),
+                        sequence(vec![condition(), event_count(kill(id + 2), 1)]),
+                        vec![GameAction::CompleteQuest {
+                            id: QuestHandle(id),
+                        }],
+                    )
+                })
+                .collect(),
+        )
+    };
+    let shared_quests = quests(&|| shared(template.clone()));
+    assert_eq!(Arc::strong_count(&template), 101);
+    let cloned_quests = quests(&|| template.as_ref().clone());
+
+    let mut shared_quests = shared_quests.compile(&|x| x, &|x| x);
+    let mut cloned_quests = cloned_quests.compile(&|x| x, &|x| x);
+    let events = [kill(0), kill(1), kill(5), kill(0), kill(5), kill(7)];
+    shared_quests.execute_events(&events);
+    cloned_quests.execute_events(&events);
+    assert_eq!(shared_quests.quest_summary(), cloned_quests.quest_summary());
+    assert_eq!(
+        shared_quests.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(3) },
+            GameAction::CompleteQuest { id: QuestHandle(5) },
+        ]
+    );
+    assert_eq!(
+        cloned_quests.consume_all_actions().collect::<Vec<_>>(),
+        vec![
+            GameAction::CompleteQuest { id: QuestHandle(3) },
+            GameAction::CompleteQuest { id: QuestHandle(5) },
+        ]
+    );
+}
+
+#[test]
+fn test_shared_evaluation() {
+    let boss = GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    };
+    let mut triggers = Triggers::new(
+        (0..100)
+            .map(|id| {
+                Trigger::new(
+                    format!("quest {id}"),
+                    if id % 2 == 0 {
+                        predicate(boss.clone(), "boss")
+                    } else {
+                        and(vec![
+                            predicate(boss.clone(), "boss"),
+                            accumulate(GameEvent::GoldDropped { amount: 0 }, 10.0, "gold"),
+                        ])
+                    },
+                    vec![GameAction::CompleteQuest {
+                        id: QuestHandle(id),
+                    }],
+                )
+            })
+            .collect(),
+    )
+    .compile(&|x| x, &|x| x);
+    let predicate_calls = Arc::new(AtomicUsize::new(0));
+    let calls = Arc::clone(&predicate_calls);
+    triggers.register_predicate("boss", move |event| {
+        calls.fetch_add(1, atomic::Ordering::SeqCst);
+        matches!(event, GameEvent::KilledMonster { id } if *id == MonsterHandle(0))
+    });
+    let extractor_calls = Arc::new(AtomicUsize::new(0));
+    let calls = Arc::clone(&extractor_calls);
+    triggers.register_extractor("gold", move |event| {
+        calls.fetch_add(1, atomic::Ordering::SeqCst);
+        match event {
+            GameEvent::GoldDropped { amount } => *amount as f64,
+            _ => 0.0,
+        }
+    });
+
+    // The identical leaves of all triggers evaluate the registered functions once per event, and all of them advance.
+    triggers.execute_event(&boss);
+    assert_eq!(predicate_calls.load(atomic::Ordering::SeqCst), 1);
+    let completed: Vec<_> = triggers.consume_all_actions().collect();
+    assert_eq!(completed.len(), 50);
+    assert_eq!(triggers.active_trigger_count(), 50);
+
+    triggers.execute_event(&GameEvent::GoldDropped { amount: 10 });
+    assert_eq!(extractor_calls.load(atomic::Ordering::SeqCst), 1);
+    assert_eq!(triggers.consume_all_actions().count(), 50);
+
+    // Triggers added later share the evaluation as well, and each event is evaluated anew.
+    triggers.add_trigger(
+        Trigger::new(
+            "late quest".to_string(),
+            predicate(boss.clone(), "boss"),
+            vec![GameAction::FailQuest { id: QuestHandle(0) }],
+        )
+        .compile(&|x| x, &|x| x),
+    );
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(1),
+    });
+    assert_eq!(predicate_calls.load(atomic::Ordering::SeqCst), 1);
+    triggers.execute_event(&boss);
+    assert_eq!(predicate_calls.load(atomic::Ordering::SeqCst), 2);
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::FailQuest { id: QuestHandle(0) })
+    );
+}
+
+#[test]
+fn test_collect_triggers() {
+    let kill = |id| GameEvent::KilledMonster {
+        id: MonsterHandle(id),
+    };
+    let quest = |id| {
+        Trigger::new(
+            format!("quest {id}"),
+            event_count(kill(id % 2), id + 1),
+            vec![GameAction::CompleteQuest {
+                id: QuestHandle(id),
+            }],
+        )
+    };
+    let main_quests = (0..2).map(quest);
+    let side_quests = (2..4).map(quest);
+
+    let mut collected: Triggers<_, _> = main_quests.chain(side_quests).take(3).collect();
+    assert_eq!(collected.len(), 3);
+    collected.push(quest(3));
+    collected.extend((4..6).map(quest));
+    assert_eq!(collected.len(), 6);
+    assert!(!collected.is_empty());
+    assert!(Triggers::<GameEvent, GameAction>::new(vec![]).is_empty());
+
+    let mut collected = collected.compile(&|x| x, &|x| x);
+    let mut expected = Triggers::new((0..6).map(quest).collect()).compile(&|x| x, &|x| x);
+    let events = [kill(0), kill(1), kill(0), kill(1), kill(0), kill(1)];
+    collected.execute_events(&events);
+    expected.execute_events(&events);
+    assert_eq!(collected.quest_summary(), expected.quest_summary());
+    assert_eq!(
+        collected.consume_all_actions().collect::<Vec<_>>(),
+        expected.consume_all_actions().collect::<Vec<_>>()
+    );
+    assert_eq!(collected.quest_summary()[4].status, TriggerStatus::Active);
+}
+
+#[test]
+fn test_progress_csv() {
+    let mut triggers = Triggers::new(vec![
+        Trigger::new(
+            "kill".to_string(),
+            event_count(
+                GameEvent::KilledMonster {
+                    id: MonsterHandle(0),
+                },
+                1,
+            ),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "heal".to_string(),
+            sequence(vec![
+                event_count(
+                    GameEvent::Action(GameAction::CompleteQuest { id: QuestHandle(0) }),
+                    1,
+                ),
+                geq(GameEvent::MonsterHealthChanged {
+                    id: MonsterHandle(0),
+                    health: 20,
+                }),
+            ]),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    triggers.execute_event(&GameEvent::KilledMonster {
+        id: MonsterHandle(0),
+    });
+    triggers.execute_event(&GameEvent::MonsterHealthChanged {
+        id: MonsterHandle(0),
+        health: 15,
+    });
+    assert_eq!(
+        triggers.progress_csv(),
+        "id_str,status,current,required,completion_fraction\n\
+         kill,completed,1,1,1\n\
+         heal,active,1.75,2,0.875\n"
+    );
+}
+
+#[test]
+fn test_progress_csv_escaping() {
+    let triggers = Triggers::new(vec![
+        Trigger::new(
+            "kill 2, then rest".to_string(),
+            event_count(GameEvent::HealthChanged { health: 0 }, 2),
+            vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+        ),
+        Trigger::new(
+            "the \"boss\"".to_string(),
+            never(),
+            vec![GameAction::CompleteQuest { id: QuestHandle(1) }],
+        ),
+    ])
+    .compile(&|x| x, &|x| x);
+    assert_eq!(
+        triggers.progress_csv(),
+        "id_str,status,current,required,completion_fraction\n\
+         \"kill 2, then rest\",active,0,2,0\n\
+         \"the \"\"boss\"\"\",active,0,1,0\n"
+    );
+}
+
+#[test]
+fn test_linear_progress() {
+    use event_trigger_action_system::progress::linear_progress;
+    use std::cmp::Ordering;
+
+    // At least the target.
+    assert_eq!(linear_progress(0.0, 20.0, Ordering::Greater), Some(0.0));
+    assert_eq!(linear_progress(15.0, 20.0, Ordering::Greater), Some(0.75));
+    assert_eq!(linear_progress(20.0, 20.0, Ordering::Greater), Some(1.0));
+    assert_eq!(linear_progress(30.0, 20.0, Ordering::Greater), Some(1.0));
+    assert_eq!(linear_progress(0.0, 0.0, Ordering::Greater), Some(1.0));
+
+    // At most the target.
+    assert_eq!(linear_progress(40.0, 10.0, Ordering::Less), Some(0.25));
+    assert_eq!(linear_progress(20.0, 10.0, Ordering::Less), Some(0.5));
+    assert_eq!(linear_progress(5.0, 10.0, Ordering::Less), Some(1.0));
+    assert_eq!(linear_progress(5.0, 0.0, Ordering::Less), Some(0.0));
+
+    // Exactly the target, approached from either side.
+    assert_eq!(linear_progress(5.0, 10.0, Ordering::Equal), Some(0.5));
+    assert_eq!(linear_progress(20.0, 10.0, Ordering::Equal), Some(0.5));
+    assert_eq!(linear_progress(10.0, 10.0, Ordering::Equal), Some(1.0));
+
+    // Invalid values.
+    assert_eq!(linear_progress(f64::NAN, 10.0, Ordering::Greater), None);
+    assert_eq!(linear_progress(5.0, -1.0, Ordering::Greater), None);
+
+    // The progress grows monotonically while approaching the target.
+    for ordering in [Ordering::Greater, Ordering::Less, Ordering::Equal] {
+        let towards_target: Vec<f64> = if ordering == Ordering::Less {
+            (10..=100).rev().map(f64::from).collect()
+        } else {
+            (0..=10).map(f64::from).collect()
+        };
+        let progress: Vec<_> = towards_target
+            .iter()
+            .map(|current| linear_progress(*current, 10.0, ordering).unwrap())
+            .collect();
+        assert!(progress.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(progress
+            .iter()
+            .all(|progress| (0.0..=1.0).contains(progress)));
+        assert_eq!(progress.last(), Some(&1.0));
+    }
+}
+
+#[test]
+fn test_delta_geq() {
+    let health = |health| GameEvent::HealthChanged { health };
+    let mut triggers = Triggers::new(vec![Trigger::new(
+        "big heal".to_string(),
+        delta_geq(health(0), 20.0, "health"),
+        vec![GameAction::CompleteQuest { id: QuestHandle(0) }],
+    )])
+    .compile(&|x| x, &|x| x);
+    triggers.register_extractor("health", |event| match event {
+        GameEvent::HealthChanged { health } => *health as f64,
+        _ => 0.0,
+    });
+
+    // A gradual change by more than the threshold does not count.
+    triggers.execute_events(&[health(50), health(60), health(70), health(80), health(10)]);
+    assert_eq!(triggers.consume_action(), None);
+    triggers.execute_event(&health(25));
+    assert_eq!(triggers.progress(0.into()), Some((0.75, 1.0)));
+
+    triggers.execute_event(&health(45));
+    assert_eq!(
+        triggers.consume_action(),
+        Some(GameAction::CompleteQuest { id: QuestHandle(0) })
+    );
+}